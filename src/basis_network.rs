@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
-use std::collections::{HashSet, HashMap, VecDeque};
+use std::collections::{BTreeSet, HashSet, HashMap, VecDeque};
 
 use crate::prelude::*;
 use crate::transformation::NetworkTransformation;
@@ -11,6 +11,9 @@ use crate::normal_meta_context::NormalMetaContext;
 use crate::basis_node::BasisNode;
 use crate::xpath::XPath;
 
+// Used for records of networks that have not been given a name by a network transformation
+const DEFAULT_NETWORK_NAME: &str = "record";
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BasisNetworkMetadata {
     pub prompts: Vec<Hash>
@@ -84,19 +87,60 @@ impl BasisNetwork {
                 })?
         };
 
-        let all_contexts: Vec<(Arc<BasisNode>, Arc<Context>)> = self.basis_nodes
+        let mut all_contexts: Vec<(Arc<BasisNode>, Arc<Context>)> = self.basis_nodes
             .iter()
             .flat_map(|basis_node| {
                 basis_node_contexts
                     .get(&basis_node.id)
-                    .unwrap()
-                    .clone()
+                    .cloned()
+                    .unwrap_or_default()
                     .iter()
                     .map(|context| (basis_node.clone(), context.clone()))
                     .collect::<Vec<_>>()
             })
             .collect();
 
+        // Records are emitted in the order they appear in the original document
+        let document_order = meta_context.get_document_order();
+        all_contexts.sort_by_key(|(_, context)| {
+            document_order
+                .get(&read_lock!(context.graph_node).id)
+                .cloned()
+                .unwrap_or(usize::MAX)
+        });
+
+        // Contexts of basis nodes in an equal relationship, indexed by lineage and then by
+        // each of their field values, so records are matched without rescanning every context
+        let equal_lineages: HashSet<String> = self.relationships
+            .iter()
+            .filter(|relationship| matches!(relationship.relationship_type, NodeRelationshipType::Equal))
+            .flat_map(|relationship| vec![
+                relationship.left_basis_lineage.to_string(),
+                relationship.right_basis_lineage.to_string(),
+            ])
+            .collect();
+
+        let mut equal_data_nodes: HashMap<usize, DataNode> = HashMap::new();
+        let mut equal_index: HashMap<String, HashMap<(String, String), Vec<usize>>> = HashMap::new();
+
+        for (position, (basis_node, context)) in all_contexts.iter().enumerate() {
+            let lineage = basis_node.lineage.to_string();
+
+            if !equal_lineages.contains(&lineage) {
+                continue;
+            }
+
+            if let Some(equal_data_node) = basis_node.apply(context.clone())? {
+                let fields_index = equal_index.entry(lineage).or_default();
+
+                for field in equal_data_node.fields.iter() {
+                    fields_index.entry(field.clone()).or_default().push(position);
+                }
+
+                equal_data_nodes.insert(position, equal_data_node);
+            }
+        }
+
         let mut processed_contexts: HashSet<ContextID> = HashSet::new();
 
         for (basis_node, context) in &all_contexts {
@@ -119,6 +163,8 @@ impl BasisNetwork {
             };
             processed_contexts.insert(context.id.clone());
 
+            let mut record_contexts: Vec<Arc<Context>> = vec![context.clone()];
+
             let mut queue: VecDeque<(Arc<Context>, Lineage)> = VecDeque::new();
             queue.push_back((context.clone(), basis_node.lineage.clone()));
 
@@ -183,6 +229,7 @@ impl BasisNetwork {
                                 }
 
                                 queue.push_back((target_context.clone(), target_basis_node.lineage.clone()));
+                                record_contexts.push(target_context.clone());
                             } else {
                                 log::warn!("Could not find target context with current network");
                             }
@@ -195,7 +242,51 @@ impl BasisNetwork {
                         processed_relationships.insert(relationship.id.clone());
                     },
                     NodeRelationshipType::Equal => {
-                        unimplemented!()
+                        let target_lineage = if relationship.left_basis_lineage == *current_lineage {
+                            relationship.right_basis_lineage.clone()
+                        } else {
+                            relationship.left_basis_lineage.clone()
+                        };
+
+                        // Contexts of the equal basis node describe the same entity as this
+                        // record if they share at least one field value with it. Candidates
+                        // are visited in document order, so earlier values win on conflict.
+                        if let Some(fields_index) = equal_index.get(&target_lineage.to_string()) {
+                            let mut candidates: BTreeSet<usize> = data_node.fields
+                                .iter()
+                                .filter_map(|field| fields_index.get(field))
+                                .flatten()
+                                .cloned()
+                                .collect();
+
+                            while let Some(position) = candidates.pop_first() {
+                                let target_context = &all_contexts[position].1;
+
+                                if processed_contexts.contains(&target_context.id) {
+                                    continue;
+                                }
+
+                                data_node = DataNode::from_equal_data_nodes(vec![
+                                    data_node,
+                                    equal_data_nodes[&position].clone(),
+                                ]);
+
+                                // Values taken from this context can match contexts further on
+                                candidates.extend(
+                                    data_node.fields
+                                        .iter()
+                                        .filter_map(|field| fields_index.get(field))
+                                        .flatten()
+                                        .filter(|candidate| **candidate > position)
+                                );
+
+                                queue.push_back((target_context.clone(), target_lineage.clone()));
+                                record_contexts.push(target_context.clone());
+                                processed_contexts.insert(target_context.id.clone());
+                            }
+                        }
+
+                        processed_relationships.insert(relationship.id.clone());
                     },
                     NodeRelationshipType::NoRelationship => {
                         return Err(Errors::UnexpectedError("Did not expect a NoRelationship here..".to_string()));
@@ -203,8 +294,9 @@ impl BasisNetwork {
                 }
             }
 
-
-
+            if data_node.is_empty() {
+                continue;
+            }
 
             let data_node = Arc::new(data_node.clone());
 
@@ -219,11 +311,11 @@ impl BasisNetwork {
 
             let normal_context = Arc::new(NormalContext {
                 id: ID::new(),
-                network_name: Some("placeholdernetworkname".to_string()),
-                network_description: Some("placeholderdescription".to_string()),
+                network_name: Some(self.get_name()),
+                network_description: self.get_description(),
                 data_node,
                 graph_node: Arc::clone(&graph_node),
-                contexts: Vec::new(),
+                contexts: record_contexts,
             });
 
             normal_contexts.insert(normal_context.id.clone(), Arc::clone(&normal_context));
@@ -238,6 +330,19 @@ impl BasisNetwork {
         })
     }
 
+    pub fn get_name(&self) -> String {
+        self.transformations
+            .first()
+            .map(|transformation| transformation.image.clone())
+            .unwrap_or_else(|| DEFAULT_NETWORK_NAME.to_string())
+    }
+
+    pub fn get_description(&self) -> Option<String> {
+        self.transformations
+            .first()
+            .map(|transformation| transformation.description.clone())
+    }
+
     pub fn _apply(
         &self,
         normalization_context: Arc<RwLock<NormalizationContext>>,
@@ -400,3 +505,138 @@ impl BasisNetwork {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::document::{Document, DocumentMetadata, DocumentType};
+    use crate::basis_node::BasisNodeMetadata;
+    use crate::transformation::{FieldMetadata, FieldTransformation};
+
+    fn basis_node(name: &str, fields: &[&str]) -> Arc<BasisNode> {
+        Arc::new(BasisNode {
+            id: ID::new(),
+            lineage: Lineage::new().with_hash(Hash::from_str(name)),
+            transformations: fields
+                .iter()
+                .map(|field| FieldTransformation {
+                    id: ID::new(),
+                    description: field.to_string(),
+                    field: field.to_string(),
+                    image: field.to_string(),
+                    meta: FieldMetadata {
+                        data_type: "string".to_string(),
+                        format: None,
+                    },
+                })
+                .collect(),
+            metadata: BasisNodeMetadata { prompts: Vec::new() },
+        })
+    }
+
+    // Loads the document and assigns each context to the basis node whose key fields it has
+    fn normalization_context(
+        data: serde_json::Value,
+        basis_nodes: &[(&Arc<BasisNode>, &str)],
+    ) -> Arc<RwLock<NormalizationContext>> {
+        let document = Document {
+            document_type: DocumentType::Json,
+            data: data.to_string(),
+            metadata: DocumentMetadata {
+                origin: None,
+                date: None,
+                name: None,
+                description: None,
+                semantic_content_types: None,
+            },
+        };
+        let meta_context = document.to_meta_context().unwrap();
+
+        let basis_node_contexts: HashMap<BasisNodeID, Vec<Arc<Context>>> = basis_nodes
+            .iter()
+            .map(|(basis_node, key_field)| {
+                let contexts = meta_context.contexts
+                    .values()
+                    .filter(|context| context.data_node.fields.contains_key(key_field))
+                    .cloned()
+                    .collect();
+                (basis_node.id.clone(), contexts)
+            })
+            .collect();
+
+        let mut normalization_context = NormalizationContext::new();
+        normalization_context.update_meta_context(meta_context);
+        normalization_context.basis_node_contexts = Some(basis_node_contexts);
+
+        Arc::new(RwLock::new(normalization_context))
+    }
+
+    fn records(normal_meta_context: &NormalMetaContext) -> Vec<Vec<(String, String)>> {
+        read_lock!(normal_meta_context.graph_root)
+            .children
+            .iter()
+            .map(|child| {
+                normal_meta_context.contexts_lookup
+                    .get(&read_lock!(child).id)
+                    .unwrap()
+                    .data_node
+                    .fields
+                    .iter()
+                    .cloned()
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn merges_equal_contexts_that_share_a_field_value() {
+        let people = basis_node("people", &["id", "name"]);
+        let emails = basis_node("emails", &["id", "email"]);
+
+        let normalization_context = normalization_context(
+            json!({
+                "items": [
+                    { "id": "1", "name": "Ada" },
+                    { "id": "1", "email": "ada@example.com" },
+                    { "id": "2", "email": "grace@example.com" },
+                    { "id": "1", "email": "ada@example.org" }
+                ]
+            }),
+            &[(&people, "name"), (&emails, "email")],
+        );
+
+        let basis_network = BasisNetwork {
+            id: ID::new(),
+            basis_nodes: vec![Arc::clone(&people), Arc::clone(&emails)],
+            relationships: vec![Arc::new(NodeRelationship {
+                id: ID::new(),
+                left_basis_lineage: people.lineage.clone(),
+                right_basis_lineage: emails.lineage.clone(),
+                relationship_type: NodeRelationshipType::Equal,
+            })],
+            transformations: Vec::new(),
+            metadata: BasisNetworkMetadata { prompts: Vec::new() },
+        };
+
+        let parent: Graph = Arc::new(RwLock::new(GraphNode::from_data_node(
+            Arc::new(DataNode::new(Hash::new(), Lineage::new(), DataNodeFields::new(), String::new())),
+            Vec::new(),
+        )));
+
+        let normal_meta_context = basis_network.apply(normalization_context, parent).unwrap();
+
+        // Both contexts with id 1 join Ada's record and the first email seen is kept
+        assert_eq!(records(&normal_meta_context), vec![
+            fields(&[("id", "1"), ("name", "Ada"), ("email", "ada@example.com")]),
+            fields(&[("id", "2"), ("email", "grace@example.com")]),
+        ]);
+    }
+}
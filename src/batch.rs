@@ -0,0 +1,235 @@
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::document_format::DocumentFormat;
+use crate::normalization;
+use crate::package::Package;
+use crate::prelude::*;
+use crate::translation;
+
+#[derive(Clone, Debug)]
+pub struct BatchOptions {
+    // Documents analysed one after the other before the rest of the batch is started
+    pub sample_size: usize,
+    // Documents processed at the same time once the sample is done
+    pub concurrency: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            sample_size: 3,
+            concurrency: 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    // Documents the inferences stored for the sample did not cover, by index
+    pub needs_inference: Vec<usize>,
+}
+
+// Documents from the same origin share most of their structure, and the provider keeps
+// what was inferred for it by lineage. The sample is analysed first and the rest of the
+// batch is run offline against what it stored. Only the documents that need inferences
+// of their own are then analysed, one after the other so that each can use what the
+// previous one stored.
+pub async fn process_batch<P, R, F>(
+    provider: Arc<P>,
+    reasoner: Arc<R>,
+    documents: Vec<(String, Metadata)>,
+    translation: Option<(String, Metadata)>,
+    options: &Options,
+    document_format: &DocumentFormat,
+    batch_options: &BatchOptions,
+    execution_context: Arc<ExecutionContext>,
+    mut on_output: F,
+) -> Result<BatchSummary, Errors>
+where
+    P: Provider,
+    R: Reasoner,
+    F: FnMut(usize, Result<Package, Errors>) -> Result<(), Errors>,
+{
+    check_origin(&documents, options)?;
+
+    let total = documents.len();
+    let sample_size = batch_options.sample_size.clamp(1, total.max(1));
+    let mut summary = BatchSummary::default();
+    let mut documents = documents.into_iter().enumerate();
+
+    log::info!("Processing batch of {} documents, sample of {}", total, sample_size);
+
+    for (index, document) in documents.by_ref().take(sample_size) {
+        let result = process_document(
+            Arc::clone(&provider),
+            Arc::clone(&reasoner),
+            document,
+            translation.as_ref(),
+            options,
+            document_format,
+            Arc::clone(&execution_context),
+        ).await;
+
+        record_output(&mut summary, index, result, &mut on_output)?;
+    }
+
+    let offline_options = Options {
+        offline: true,
+        ..options.clone()
+    };
+    let mut pending = Vec::new();
+
+    {
+        let mut results = stream::iter(documents)
+            .map(|(index, document)| {
+                let provider = Arc::clone(&provider);
+                let reasoner = Arc::clone(&reasoner);
+                let document_context = execution_context.child();
+                let translation = translation.as_ref();
+                let offline_options = &offline_options;
+
+                async move {
+                    let result = process_document(
+                        provider,
+                        reasoner,
+                        document.clone(),
+                        translation,
+                        offline_options,
+                        document_format,
+                        Arc::clone(&document_context),
+                    ).await;
+
+                    (index, document, result, document_context)
+                }
+            })
+            .buffer_unordered(batch_options.concurrency.max(1));
+
+        while let Some((index, document, result, document_context)) = results.next().await {
+            // A run that was offline to begin with has nothing more to go on
+            if !options.offline && needs_inference(&result, &document_context) {
+                log::info!("Document {} needs inferences the sample did not make", index);
+                pending.push((index, document));
+                continue;
+            }
+
+            execution_context.absorb(&document_context);
+            record_output(&mut summary, index, result, &mut on_output)?;
+        }
+    }
+
+    pending.sort_by_key(|(index, _)| *index);
+
+    for (index, document) in pending {
+        let result = process_document(
+            Arc::clone(&provider),
+            Arc::clone(&reasoner),
+            document,
+            translation.as_ref(),
+            options,
+            document_format,
+            Arc::clone(&execution_context),
+        ).await;
+
+        summary.needs_inference.push(index);
+        record_output(&mut summary, index, result, &mut on_output)?;
+    }
+
+    log::info!(
+        "Batch done, {} succeeded, {} failed, {} needed inferences of their own",
+        summary.succeeded,
+        summary.failed,
+        summary.needs_inference.len(),
+    );
+
+    Ok(summary)
+}
+
+pub async fn process_document<P: Provider, R: Reasoner>(
+    provider: Arc<P>,
+    reasoner: Arc<R>,
+    document: (String, Metadata),
+    translation: Option<&(String, Metadata)>,
+    options: &Options,
+    document_format: &DocumentFormat,
+    execution_context: Arc<ExecutionContext>,
+) -> Result<Package, Errors> {
+    let (document, metadata) = document;
+
+    log::debug!("metadata: {:?}", metadata);
+
+    if let Some((translation, translation_metadata)) = translation {
+        translation::translate_text_to_package(
+            provider,
+            reasoner,
+            (document, &metadata),
+            (translation.clone(), translation_metadata),
+            options,
+            document_format,
+            execution_context,
+        ).await
+    } else {
+        let normalized_document = normalization::normalize_text_to_document(
+            provider,
+            reasoner,
+            document,
+            options,
+            &metadata,
+            document_format,
+            execution_context,
+        ).await?;
+
+        Ok(Package {
+            document: normalized_document,
+            mutations: Vec::new(),
+        })
+    }
+}
+
+// The sample only stands in for the rest of the batch if the batch comes from one origin
+fn check_origin(documents: &[(String, Metadata)], options: &Options) -> Result<(), Errors> {
+    let origins: BTreeSet<&str> = documents
+        .iter()
+        .map(|(_, metadata)| options.origin.as_deref().unwrap_or(&metadata.origin))
+        .collect();
+
+    if origins.len() > 1 {
+        return Err(Errors::UnexpectedParameter(format!(
+            "Documents in a batch must share one origin, found {}",
+            origins.into_iter().collect::<Vec<_>>().join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+fn needs_inference(result: &Result<Package, Errors>, execution_context: &ExecutionContext) -> bool {
+    matches!(result, Err(Errors::InferenceNotAvailable(_))) ||
+        !execution_context.missing_inferences().is_empty()
+}
+
+// A document that fails does not stop the batch, unless it failed because there is no
+// budget left for any of the others either
+fn record_output<F>(
+    summary: &mut BatchSummary,
+    index: usize,
+    result: Result<Package, Errors>,
+    on_output: &mut F,
+) -> Result<(), Errors>
+where
+    F: FnMut(usize, Result<Package, Errors>) -> Result<(), Errors>,
+{
+    match result {
+        Err(e @ Errors::BudgetExceeded(_)) => return Err(e),
+        Err(ref e) => {
+            log::error!("Could not process document {}: {:?}", index, e);
+            summary.failed += 1;
+        }
+        Ok(_) => summary.succeeded += 1,
+    }
+
+    on_output(index, result)
+}
@@ -1,5 +1,6 @@
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 use std::sync::RwLock;
@@ -16,16 +17,119 @@ pub struct DevConfig {
     pub debug_dir: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub model: String,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    // Passed on to the backend as part of the request, e.g. `reasoning` or `provider`
+    #[serde(default)]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ModelRoute {
+    pub fn new(model: &str) -> Self {
+        ModelRoute {
+            model: model.to_string(),
+            temperature: None,
+            max_tokens: None,
+            parameters: serde_json::Map::new(),
+        }
+    }
+}
+
+// A model and the models to fall through to, in order, when it fails or is rate limited
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelConfig {
+    #[serde(flatten)]
+    pub route: ModelRoute,
+    #[serde(default)]
+    pub fallbacks: Vec<ModelRoute>,
+}
+
+// Models by capability, which can be overridden for each operation
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModelsConfig {
+    pub fast: Option<ModelConfig>,
+    pub capable: Option<ModelConfig>,
+    pub classify: Option<ModelConfig>,
+    pub basis_field: Option<ModelConfig>,
+    pub basis_group: Option<ModelConfig>,
+    pub basis_node: Option<ModelConfig>,
+    pub node_relationship: Option<ModelConfig>,
+    pub node_translation: Option<ModelConfig>,
+    pub network_translation: Option<ModelConfig>,
+    pub schema_to_instance: Option<ModelConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenAICompatibleConfig {
+    #[serde(default = "get_default_base_url")]
+    pub base_url: String,
+    // Name of the environment variable holding the API key, local servers usually need none
+    #[serde(default)]
+    pub api_key_variable: Option<String>,
+    #[serde(default = "get_default_completion_model")]
+    pub fast_model: String,
+    #[serde(default = "get_default_completion_model")]
+    pub capable_model: String,
+    #[serde(default = "get_default_embedding_model")]
+    pub embedding_model: String,
+    // Whether the server accepts a JSON schema as response format. When it turns out not to,
+    // JSON mode is used instead and responses are validated against the schema afterwards.
+    #[serde(default = "get_default_json_schema_support")]
+    pub json_schema_support: bool,
+    #[serde(default = "get_default_backend_concurrency")]
+    pub max_concurrency: usize,
+    #[serde(default = "get_default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for OpenAICompatibleConfig {
+    fn default() -> Self {
+        OpenAICompatibleConfig {
+            base_url: get_default_base_url(),
+            api_key_variable: None,
+            fast_model: get_default_completion_model(),
+            capable_model: get_default_completion_model(),
+            embedding_model: get_default_embedding_model(),
+            json_schema_support: get_default_json_schema_support(),
+            max_concurrency: get_default_backend_concurrency(),
+            timeout_seconds: get_default_timeout_seconds(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReasonerConfig {
+    #[serde(default = "get_default_backend")]
+    pub backend: String,
     #[serde(default = "get_default_prompts_location")]
     pub prompts_location: String,
+    #[serde(default = "get_default_fixtures_location")]
+    pub fixtures_location: String,
+    #[serde(default = "get_default_recording_mode")]
+    pub recording_mode: String,
+    #[serde(default = "get_default_recordings_location")]
+    pub recordings_location: String,
+    #[serde(default)]
+    pub openai_compatible: OpenAICompatibleConfig,
+    #[serde(default)]
+    pub models: ModelsConfig,
 }
 
 impl Default for ReasonerConfig {
     fn default() -> Self {
         ReasonerConfig {
+            backend: get_default_backend(),
             prompts_location: get_default_prompts_location(),
+            fixtures_location: get_default_fixtures_location(),
+            recording_mode: get_default_recording_mode(),
+            recordings_location: get_default_recordings_location(),
+            openai_compatible: OpenAICompatibleConfig::default(),
+            models: ModelsConfig::default(),
         }
     }
 }
@@ -38,6 +142,125 @@ impl Default for DevConfig {
     }
 }
 
+// Prices are per million tokens
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Budget {
+    pub max_tokens: Option<u64>,
+    pub max_cost: Option<f64>,
+}
+
+// Limits for the whole run and for individual stages, keyed by stage name, e.g.
+// `field_analysis`. Costs are estimated from the price of each model.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    #[serde(flatten)]
+    pub run: Budget,
+    #[serde(default)]
+    pub stages: HashMap<String, Budget>,
+    #[serde(default = "get_default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        BudgetConfig {
+            run: Budget::default(),
+            stages: HashMap::new(),
+            currency: get_default_currency(),
+            prices: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerConfig {
+    #[serde(default = "get_default_server_address")]
+    pub address: String,
+    // Request bodies carry whole documents, so the limit is well above the usual few megabytes
+    #[serde(default = "get_default_max_body_size")]
+    pub max_body_size: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            address: get_default_server_address(),
+            max_body_size: get_default_max_body_size(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriftConfig {
+    // Fraction of contexts whose acyclic lineage must be known for the origin, below it the
+    // drift policy given with --on-drift applies
+    #[serde(default = "get_default_drift_threshold")]
+    pub threshold: f64,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        DriftConfig {
+            threshold: get_default_drift_threshold(),
+        }
+    }
+}
+
+// Limits for the code translations run to convert field values, e.g. to reformat dates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransformationConfig {
+    #[serde(default = "get_default_transformation_timeout_milliseconds")]
+    pub timeout_milliseconds: u64,
+    // In bytes, for the embedded JavaScript runtime
+    #[serde(default = "get_default_transformation_memory_limit")]
+    pub memory_limit: usize,
+    // Embedded scripts that ran over cannot be stopped and are left running, once this many
+    // are no more are started
+    #[serde(default = "get_default_max_runaway_scripts")]
+    pub max_runaway_scripts: usize,
+    // Interpreters run for code written for the other runtimes
+    #[serde(default = "get_default_python_command")]
+    pub python_command: String,
+    #[serde(default = "get_default_node_command")]
+    pub node_command: String,
+    #[serde(default = "get_default_awk_command")]
+    pub awk_command: String,
+    // Environment variables the interpreters get, every other one is left out
+    #[serde(default = "get_default_environment_allowlist")]
+    pub environment_allowlist: Vec<String>,
+    // In bytes, the address space of Python and AWK and the heap of Node.js, on Unix only
+    #[serde(default = "get_default_subprocess_memory_limit")]
+    pub subprocess_memory_limit: usize,
+    // Run the interpreters in a network namespace of their own with nothing in it, which
+    // takes unprivileged user namespaces, on Linux only
+    #[serde(default = "get_default_isolate_network")]
+    pub isolate_network: bool,
+}
+
+impl Default for TransformationConfig {
+    fn default() -> Self {
+        TransformationConfig {
+            timeout_milliseconds: get_default_transformation_timeout_milliseconds(),
+            memory_limit: get_default_transformation_memory_limit(),
+            max_runaway_scripts: get_default_max_runaway_scripts(),
+            python_command: get_default_python_command(),
+            node_command: get_default_node_command(),
+            awk_command: get_default_awk_command(),
+            environment_allowlist: get_default_environment_allowlist(),
+            subprocess_memory_limit: get_default_subprocess_memory_limit(),
+            isolate_network: get_default_isolate_network(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub llm: LlmConfig,
@@ -45,6 +268,14 @@ pub struct Config {
     pub dev: DevConfig,
     #[serde(default)]
     pub reasoner: ReasonerConfig,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub drift: DriftConfig,
+    #[serde(default)]
+    pub transformation: TransformationConfig,
 }
 
 fn get_default_debug_dir() -> String {
@@ -55,6 +286,86 @@ fn get_default_debug_dir() -> String {
         .to_string()
 }
 
+fn get_default_server_address() -> String {
+    "127.0.0.1:8080".to_string()
+}
+
+fn get_default_max_body_size() -> usize {
+    64 * 1024 * 1024
+}
+
+fn get_default_drift_threshold() -> f64 {
+    0.9
+}
+
+fn get_default_transformation_timeout_milliseconds() -> u64 {
+    1000
+}
+
+fn get_default_transformation_memory_limit() -> usize {
+    32 * 1024 * 1024
+}
+
+fn get_default_max_runaway_scripts() -> usize {
+    4
+}
+
+fn get_default_python_command() -> String {
+    "python3".to_string()
+}
+
+fn get_default_node_command() -> String {
+    "node".to_string()
+}
+
+fn get_default_awk_command() -> String {
+    "awk".to_string()
+}
+
+fn get_default_environment_allowlist() -> Vec<String> {
+    ["PATH", "HOME", "LANG", "LC_ALL", "TZ"].iter().map(|name| name.to_string()).collect()
+}
+
+fn get_default_subprocess_memory_limit() -> usize {
+    512 * 1024 * 1024
+}
+
+fn get_default_isolate_network() -> bool {
+    true
+}
+
+fn get_default_currency() -> String {
+    "USD".to_string()
+}
+
+fn get_default_backend() -> String {
+    "openrouter".to_string()
+}
+
+fn get_default_base_url() -> String {
+    "http://localhost:11434/v1".to_string()
+}
+
+fn get_default_completion_model() -> String {
+    "llama3.1".to_string()
+}
+
+fn get_default_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn get_default_json_schema_support() -> bool {
+    true
+}
+
+fn get_default_backend_concurrency() -> usize {
+    4
+}
+
+fn get_default_timeout_seconds() -> u64 {
+    300
+}
+
 fn get_default_prompts_location() -> String {
     let current_dir = env::current_dir()
         .expect("Could not get current working directory")
@@ -65,6 +376,30 @@ fn get_default_prompts_location() -> String {
     format!("file://{}/prompts", current_dir)
 }
 
+fn get_default_fixtures_location() -> String {
+    let current_dir = env::current_dir()
+        .expect("Could not get current working directory")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    format!("{}/fixtures", current_dir)
+}
+
+fn get_default_recording_mode() -> String {
+    "off".to_string()
+}
+
+fn get_default_recordings_location() -> String {
+    let current_dir = env::current_dir()
+        .expect("Could not get current working directory")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    format!("{}/recordings", current_dir)
+}
+
 impl Default for Config {
     fn default() -> Self {
         let config = Config {
@@ -74,6 +409,10 @@ impl Default for Config {
             },
             dev: DevConfig::default(),
             reasoner: ReasonerConfig::default(),
+            budget: BudgetConfig::default(),
+            server: ServerConfig::default(),
+            drift: DriftConfig::default(),
+            transformation: TransformationConfig::default(),
         };
 
         config
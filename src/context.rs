@@ -155,8 +155,8 @@ impl Context {
 
     fn generate_positional_context(&self, meta_context: &MetaContext) -> Result<String, Errors> {
         match meta_context.document_type {
-            DocumentType::Json => self.generate_positional_context_json(meta_context),
-            DocumentType::Html => self.generate_positional_context_html(meta_context),
+            DocumentType::Json | DocumentType::JavaScript => self.generate_positional_context_json(meta_context),
+            DocumentType::Html | DocumentType::Xml | DocumentType::PlainText => self.generate_positional_context_html(meta_context),
             _ => unimplemented!()
         }
     }
@@ -170,9 +170,20 @@ impl Context {
         Ok(xpath.to_string())
     }
 
-    fn generate_positional_context_json(&self, meta_context: &MetaContext) -> Result<String, Errors> {
+    // Names of the networks from the root down to the context, e.g. "entries -> author"
+    pub fn get_network_path(&self, meta_context: &MetaContext) -> String {
+        let ancestor_path = self.get_ancestor_network_path(meta_context);
+
+        match (ancestor_path.is_empty(), self.network_name.is_empty()) {
+            (_, true) => ancestor_path,
+            (true, false) => self.network_name.clone(),
+            (false, false) => format!("{} -> {}", ancestor_path, self.network_name),
+        }
+    }
+
+    fn get_ancestor_network_path(&self, meta_context: &MetaContext) -> String {
         let root_to_target = get_path_to_target(Arc::clone(&self.graph_node));
-        let context_string = root_to_target.iter().fold(String::new(), |acc, graph| {
+        root_to_target.iter().fold(String::new(), |acc, graph| {
             let current_context = meta_context.contexts_lookup.get(&read_lock!(graph).id).unwrap();
 
             if current_context.network_name.is_empty() {
@@ -184,7 +195,11 @@ impl Context {
                     format!("{} -> {}", acc, current_context.network_name)
                 }
             }
-        });
+        })
+    }
+
+    fn generate_positional_context_json(&self, meta_context: &MetaContext) -> Result<String, Errors> {
+        let context_string = self.get_ancestor_network_path(meta_context);
         if self.data_node.fields.is_empty() {
             if self.network_name.is_empty() {
                 panic!("network name is empty");
@@ -230,6 +245,7 @@ impl Context {
                 wrap_text: None,
                 exclude_nulls: None,
                 custom_delimiter: None,
+                flatten: None,
             },
             Some(&neighbourhood)
         )?;
@@ -305,6 +321,17 @@ impl Context {
 }
 
 impl Context {
+    // Contexts are collected from hash maps and in parallel, so they are put in an order of
+    // their own wherever that order ends up in a prompt, to keep recorded responses usable
+    pub fn sort_contexts(contexts: &mut [Arc<Context>]) {
+        contexts.sort_by_cached_key(|context| {
+            (
+                context.lineage.to_string(),
+                context.data_node.fields.iter().cloned().collect::<Vec<(String, String)>>(),
+            )
+        });
+    }
+
     pub fn get_indexed_lineage(&self, depth: usize) -> Option<Lineage> {
         {
             let cache = read_lock!(self.indexed_lineages);
@@ -128,6 +128,34 @@ impl DataNode {
         }
     }
 
+    // Merges data nodes describing the same entity. Identical fields are kept once and,
+    // when a field has conflicting values, the value from the earliest data node wins.
+    pub fn from_equal_data_nodes(data_nodes: Vec<Self>) -> Self {
+        Self {
+            id: ID::new(),
+            hash: Hash::new(),
+            lineage: Lineage::new(),
+            fields: data_nodes.into_iter().fold(
+                DataNodeFields::new(),
+                |mut acc, data_node| {
+                    let existing = acc.clone();
+
+                    for (key, value) in data_node.fields {
+                        if existing.contains_key(&key) {
+                            if !existing.get(&key).contains(&&value) {
+                                log::debug!("Discarding conflicting value for field {}: {}", key, value);
+                            }
+                            continue;
+                        }
+                        acc.insert(key, value);
+                    }
+                    acc
+                }
+            ),
+            description: "Placeholder description".to_string()
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.fields.is_empty()
     }
@@ -0,0 +1,324 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::prelude::*;
+use crate::document::DocumentType;
+use crate::document_format::{DocumentFormat, FlattenRule};
+
+use super::writer::{get_line_ending, value_to_text};
+
+const DEFAULT_DELIMITER: char = '\t';
+
+struct Table {
+    name: String,
+    columns: Vec<String>,
+    rows: Vec<HashMap<String, String>>,
+}
+
+// Writes records as delimited tables (CSV, TSV), one table per network. Nested values are
+// flattened into columns named by their path, e.g. "seller.name". When there is more than
+// one table, each is preceded by a line holding the network name and separated from the
+// next by an empty line.
+pub struct Delimited;
+
+impl Delimited {
+    pub fn from_value(
+        value: &Value,
+        column_order: &HashMap<String, Vec<String>>,
+        document_format: &DocumentFormat,
+    ) -> Result<String, Errors> {
+        let delimiter = document_format.custom_delimiter.unwrap_or(match document_format.format_type {
+            DocumentType::Csv => ',',
+            _ => DEFAULT_DELIMITER,
+        });
+
+        if delimiter == '"' || delimiter == '\n' || delimiter == '\r' {
+            return Err(Errors::UnexpectedParameter(format!("Invalid delimiter: {:?}", delimiter)));
+        }
+
+        let line_ending = get_line_ending(document_format);
+        let rule = document_format.flatten.clone().unwrap_or_default();
+        let has_headers = document_format.headers.unwrap_or(true);
+        let quote_all = document_format.wrap_text.unwrap_or(false);
+        let exclude_nulls = document_format.exclude_nulls.unwrap_or(false);
+
+        let root = match value {
+            Value::Object(map) => map.clone(),
+            other => {
+                let mut map = Map::new();
+                map.insert(String::new(), other.clone());
+                map
+            }
+        };
+
+        let tables: Vec<Table> = get_records(&root)
+            .into_iter()
+            .map(|(name, records)| {
+                let order = column_order.get(&name).cloned().unwrap_or_default();
+                to_table(name, records, &order, &rule, exclude_nulls)
+            })
+            .collect();
+
+        let write_cell = |cell: &str| -> String {
+            let needs_quotes = quote_all ||
+                cell.contains(delimiter) ||
+                cell.contains(['"', '\n', '\r']) ||
+                cell.trim() != cell;
+
+            if needs_quotes {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        };
+
+        let mut result = String::new();
+
+        for (index, table) in tables.iter().enumerate() {
+            if tables.len() > 1 {
+                if index > 0 {
+                    result.push_str(&line_ending);
+                }
+                result.push_str(&write_cell(&table.name));
+                result.push_str(&line_ending);
+            }
+
+            if has_headers {
+                let header: Vec<String> = table.columns.iter().map(|column| write_cell(column)).collect();
+                result.push_str(&header.join(&delimiter.to_string()));
+                result.push_str(&line_ending);
+            }
+
+            for row in &table.rows {
+                let cells: Vec<String> = table.columns
+                    .iter()
+                    .map(|column| write_cell(row.get(column).map(|cell| cell.as_str()).unwrap_or("")))
+                    .collect();
+                result.push_str(&cells.join(&delimiter.to_string()));
+                result.push_str(&line_ending);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+// Every value at the top level holding objects is a network of records. If there are
+// none, the top level itself is the only record.
+fn get_records(root: &Map<String, Value>) -> Vec<(String, Vec<Map<String, Value>>)> {
+    let mut networks: Vec<(String, Vec<Map<String, Value>>)> = Vec::new();
+    let mut fields = Map::new();
+
+    for (key, value) in root {
+        let records: Vec<Map<String, Value>> = match value {
+            Value::Object(map) => vec![map.clone()],
+            Value::Array(items) if items.iter().any(|item| item.is_object()) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Object(map) => map.clone(),
+                    other => {
+                        let mut map = Map::new();
+                        map.insert(key.clone(), other.clone());
+                        map
+                    }
+                })
+                .collect(),
+            other => {
+                fields.insert(key.clone(), other.clone());
+                continue;
+            }
+        };
+
+        networks.push((key.clone(), records));
+    }
+
+    if networks.is_empty() && !fields.is_empty() {
+        networks.push((String::new(), vec![fields]));
+    }
+
+    networks
+}
+
+fn flatten(value: &Value, path: &str, cells: &mut Vec<(String, Vec<String>)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                flatten(child, &child_path, cells);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                flatten(item, path, cells);
+            }
+        }
+        Value::Null => {}
+        scalar => {
+            let text = value_to_text(scalar).trim().to_string();
+
+            match cells.iter_mut().find(|(column, _)| column == path) {
+                Some((_, values)) => values.push(text),
+                None => cells.push((path.to_string(), vec![text])),
+            }
+        }
+    }
+}
+
+fn to_table(
+    name: String,
+    records: Vec<Map<String, Value>>,
+    order: &[String],
+    rule: &FlattenRule,
+    exclude_nulls: bool,
+) -> Table {
+    let flattened: Vec<Vec<(String, Vec<String>)>> = records
+        .iter()
+        .map(|record| {
+            let mut cells = Vec::new();
+            flatten(&Value::Object(record.clone()), "", &mut cells);
+            cells
+        })
+        .collect();
+
+    // Known columns come first in the order given, followed by any other column in
+    // the order it was first seen
+    let mut paths: Vec<String> = order
+        .iter()
+        .filter(|column| flattened.iter().flatten().any(|(path, _)| path == *column))
+        .cloned()
+        .collect();
+
+    for (path, _) in flattened.iter().flatten() {
+        if !paths.contains(path) {
+            paths.push(path.clone());
+        }
+    }
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows: Vec<HashMap<String, String>> = vec![HashMap::new(); flattened.len()];
+
+    for path in paths {
+        let max_values = flattened
+            .iter()
+            .flatten()
+            .filter(|(column, _)| *column == path)
+            .map(|(_, values)| values.len())
+            .max()
+            .unwrap_or(0);
+
+        let path_columns: Vec<String> = match rule {
+            FlattenRule::Index if max_values > 1 => (1..=max_values)
+                .map(|index| format!("{}.{}", path, index))
+                .collect(),
+            _ => vec![path.clone()],
+        };
+
+        for (row, cells) in rows.iter_mut().zip(&flattened) {
+            let values = cells
+                .iter()
+                .find(|(column, _)| *column == path)
+                .map(|(_, values)| values.clone())
+                .unwrap_or_default();
+
+            match rule {
+                FlattenRule::Join(separator) => {
+                    let values: Vec<String> = values.into_iter().filter(|value| !value.is_empty()).collect();
+                    row.insert(path.clone(), values.join(separator));
+                }
+                FlattenRule::First => {
+                    row.insert(path.clone(), values.into_iter().next().unwrap_or_default());
+                }
+                FlattenRule::Index => {
+                    for (column, value) in path_columns.iter().zip(values) {
+                        row.insert(column.clone(), value);
+                    }
+                }
+            }
+        }
+
+        columns.extend(path_columns);
+    }
+
+    if exclude_nulls {
+        let is_empty = |row: &HashMap<String, String>, column: &String| {
+            row.get(column).map_or(true, |value| value.is_empty())
+        };
+
+        columns.retain(|column| rows.iter().any(|row| !is_empty(row, column)));
+        rows.retain(|row| columns.iter().any(|column| !is_empty(row, column)));
+    }
+
+    Table {
+        name,
+        columns,
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn write(value: Value, format_type: DocumentType, flatten: Option<FlattenRule>) -> String {
+        let document_format = DocumentFormat {
+            format_type,
+            line_ending: Some("lf".to_string()),
+            flatten,
+            ..DocumentFormat::default()
+        };
+
+        Delimited::from_value(&value, &HashMap::new(), &document_format).unwrap()
+    }
+
+    fn listing() -> Value {
+        json!({
+            "products": [
+                { "name": "Lamp", "tags": ["desk", "led"], "seller": { "name": "Acme" } },
+                { "name": "Chair", "tags": ["oak"] },
+            ]
+        })
+    }
+
+    #[test]
+    fn joins_repeated_values_by_default() {
+        let output = write(listing(), DocumentType::Csv, None);
+
+        assert_eq!(output, "name,seller.name,tags\nLamp,Acme,desk; led\nChair,,oak\n");
+    }
+
+    #[test]
+    fn keeps_the_first_value() {
+        let output = write(listing(), DocumentType::Csv, Some(FlattenRule::First));
+
+        assert_eq!(output, "name,seller.name,tags\nLamp,Acme,desk\nChair,,oak\n");
+    }
+
+    #[test]
+    fn numbers_columns_of_repeated_values() {
+        let output = write(listing(), DocumentType::Csv, Some(FlattenRule::Index));
+
+        assert_eq!(output, "name,seller.name,tags.1,tags.2\nLamp,Acme,desk,led\nChair,,oak,\n");
+    }
+
+    #[test]
+    fn separates_tsv_by_tabs_and_quotes_cells_when_needed() {
+        let value = json!({ "quotes": [{ "text": "say \"hi\"", "author": "A\tB" }] });
+        let output = write(value, DocumentType::Tsv, None);
+
+        assert_eq!(output, "author\ttext\n\"A\tB\"\t\"say \"\"hi\"\"\"\n");
+    }
+
+    #[test]
+    fn parses_flatten_rules() {
+        assert_eq!(FlattenRule::from_str("JOIN"), Ok(FlattenRule::Join("; ".to_string())));
+        assert_eq!(FlattenRule::from_str("first"), Ok(FlattenRule::First));
+        assert_eq!(FlattenRule::from_str("index"), Ok(FlattenRule::Index));
+        assert!(FlattenRule::from_str("spread").is_err());
+    }
+}
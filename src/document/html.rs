@@ -1,5 +1,8 @@
 use ego_tree::NodeRef;
+use lazy_static::lazy_static;
+use regex::Regex;
 use scraper::{Html as ScraperHtml, Node as ScraperNode};
+use serde_json::{Value, Map};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::sync::{Arc, RwLock};
@@ -14,6 +17,23 @@ use crate::document_node::{DocumentNode, DocumentNodeData};
 use crate::graph_node::{Graph, GraphNode};
 use crate::hash::Hash;
 use crate::document::{Document, DocumentType, DocumentMetadata};
+use crate::document_format::DocumentFormat;
+
+use super::javascript::ROOT_VALUE_KEY;
+use super::writer::{MarkupWriter, value_to_text};
+
+// Name given to the document that collects every top level microdata item of a page
+const MICRODATA_NAME: &str = "microdata";
+const DEFAULT_TITLE: &str = "Document";
+const MAX_HEADING_LEVEL: usize = 6;
+
+lazy_static! {
+    // Inline scripts are only kept when they hand state to the client, e.g.
+    // `window.__INITIAL_STATE__ = {...}` or `self["__data"] = JSON.parse("...")`
+    static ref STATE_ASSIGNMENT: Regex = Regex::new(
+        r#"\b(?:window|self|globalThis)\s*(?:\.\s*[A-Za-z_$][\w$]*|\[\s*["'][^"']+["']\s*\])\s*=[^=]"#
+    ).unwrap();
+}
 
 pub struct Html;
 
@@ -25,7 +45,15 @@ impl Html {
         log::trace!("In to_meta_context");
 
         let document_root = Self::get_document_node(data)?;
-        let document_root = Arc::new(RwLock::new(document_root.clone()));
+
+        Self::meta_context_from_root(metadata, document_root)
+    }
+
+    fn meta_context_from_root(
+        metadata: &DocumentMetadata,
+        document_root: DocumentNode,
+    ) -> Result<MetaContext, Errors> {
+        let document_root = Arc::new(RwLock::new(document_root));
 
         let contexts: Arc<RwLock<HashMap<ContextID, Arc<Context>>>> = Arc::new(RwLock::new(HashMap::new()));
         let contexts_lookup: Arc<RwLock<HashMap<ID, Arc<Context>>>> = Arc::new(RwLock::new(HashMap::new()));
@@ -199,13 +227,70 @@ impl Html {
         Ok(result)
     }
 
+    // Writes a JSON value as a semantic HTML page: scalar fields become a description
+    // list, nested objects become articles and lists of objects become sections
+    // containing an article per item. Original keys are kept in `data-key` attributes.
+    pub fn from_value(
+        title: Option<&str>,
+        description: Option<&str>,
+        value: &Value,
+        document_format: &DocumentFormat,
+    ) -> Result<String, Errors> {
+        let mut writer = MarkupWriter::new(document_format);
+        let title = writer.escape(&humanize(title.unwrap_or(DEFAULT_TITLE)));
+
+        writer.line(0, "<!DOCTYPE html>");
+        writer.line(0, "<html>");
+        writer.line(1, "<head>");
+        let charset = format!("<meta charset=\"{}\">", writer.escape(writer.encoding()));
+        writer.line(2, &charset);
+        writer.line(2, &format!("<title>{}</title>", title));
+        writer.line(1, "</head>");
+        writer.line(1, "<body>");
+        writer.line(2, "<main>");
+        writer.line(3, &format!("<h1>{}</h1>", title));
+
+        if let Some(description) = description.filter(|description| !description.trim().is_empty()) {
+            let description = writer.escape(description.trim());
+            writer.line(3, &format!("<p>{}</p>", description));
+        }
+
+        match value {
+            Value::Object(map) => write_html_object(&mut writer, map, 3, 2),
+            other => {
+                let mut map = Map::new();
+                map.insert(ROOT_VALUE_KEY.to_string(), other.clone());
+                write_html_object(&mut writer, &map, 3, 2);
+            }
+        }
+
+        writer.line(2, "</main>");
+        writer.line(1, "</body>");
+        writer.line(0, "</html>");
+
+        Ok(writer.finish())
+    }
+
+    // Structured data embedded in the page comes out of the same walk that builds the tree
+    pub fn to_meta_context_with_documents(
+        metadata: &DocumentMetadata,
+        data: String
+    ) -> Result<(MetaContext, Vec<Document>), Errors> {
+        let (document_root, extracted_docs) = Self::walk_document(data)?;
+        let meta_context = Self::meta_context_from_root(metadata, document_root)?;
+
+        Ok((meta_context, collect_documents(extracted_docs)))
+    }
+
     fn get_document_node(data: String) -> Result<DocumentNode, Errors> {
+        Self::walk_document(data).map(|(document_node, _)| document_node)
+    }
+
+    fn walk_document(data: String) -> Result<(DocumentNode, Vec<Document>), Errors> {
         if let Some(dom) = to_dom(data.clone()) {
             let _ = fs::create_dir("debug");
 
             let mut xml = String::from("");
-
-            // TODO: do we want to do anything with this?
             let mut extracted_docs: Vec<Document> = Vec::new();
 
             walk(&mut xml, dom.tree.root(), 0, &mut extracted_docs);
@@ -213,13 +298,14 @@ impl Html {
             let reader = std::io::Cursor::new(xml);
 
             match Element::parse(reader) {
-                Ok(element) => Ok(
+                Ok(element) => Ok((
                     DocumentNode::new(
                         DocumentNodeData::Xml(
                             xmltree::XMLNode::Element(element)
                         )
-                    )
-                ),
+                    ),
+                    extracted_docs,
+                )),
                 Err(e) => {
                     log::error!("Could not parse XML: {}", e);
 
@@ -232,9 +318,47 @@ impl Html {
     }
 }
 
+// Structured data embedded in the page: JSON-LD and other JSON scripts, inline scripts
+// assigning client state, and microdata, which is merged into a single document
+fn collect_documents(extracted_docs: Vec<Document>) -> Vec<Document> {
+    let mut documents: Vec<Document> = Vec::new();
+    let mut microdata_items: Vec<Value> = Vec::new();
+
+    for document in extracted_docs {
+        if document.document_type == DocumentType::Html {
+            // HTML found in attribute values is not structured data
+            continue;
+        }
+
+        if document.metadata.name.as_deref() == Some(MICRODATA_NAME) {
+            if let Ok(item) = serde_json::from_str::<Value>(&document.data) {
+                microdata_items.push(item);
+            }
+            continue;
+        }
+
+        documents.push(document);
+    }
+
+    if !microdata_items.is_empty() {
+        let mut root = Map::new();
+        root.insert("items".to_string(), Value::Array(microdata_items));
+        documents.push(json_document(
+            root,
+            Some(MICRODATA_NAME),
+            "Microdata items embedded in the page",
+        ));
+    }
+
+    log::debug!("Extracted {} embedded documents from HTML", documents.len());
+
+    documents
+}
+
+// Newlines are kept so that script contents can be parsed, they are removed from text
+// and attribute values while walking the document instead
 fn to_dom(data: String) -> Option<ScraperHtml> {
-    let sanitized = data.replace("\n", "");
-    Some(ScraperHtml::parse_document(&sanitized))
+    Some(ScraperHtml::parse_document(&data))
 }
 
 fn walk(
@@ -252,8 +376,8 @@ fn walk(
             }
         }
         ScraperNode::Text(text) => {
-            let text_content = text.trim();
-            let text = format!("{}{}\n", real_indent, escape_xml(text_content));
+            let text_content = text.trim().replace("\n", "");
+            let text = format!("{}{}\n", real_indent, escape_xml(&text_content));
 
             if !text.trim().is_empty() {
                 xhtml.push_str(&text);
@@ -286,6 +410,20 @@ fn process_element(
     let real_indent = " ".repeat(indent * 2);
 
     if let ScraperNode::Element(element) = node.value() {
+        if element.name() == "script" {
+            if let Some(document) = extract_script(node) {
+                extracted_docs.push(document);
+            }
+        }
+
+        if element.attr("itemscope").is_some() && element.attr("itemprop").is_none() {
+            extracted_docs.push(json_document(
+                microdata_item(node),
+                Some(MICRODATA_NAME),
+                "Microdata item embedded in the page",
+            ));
+        }
+
         let tag_name = preprocess_element(element.name())?;
 
         let mut has_attributes = false;
@@ -293,7 +431,7 @@ fn process_element(
 
         for (attr_name, attr_value) in element.attrs() {
             let attr_name = attr_name.trim().to_string();
-            let attr_value = attr_value.trim().to_string();
+            let attr_value = attr_value.trim().replace("\n", "");
 
             has_attributes = true;
 
@@ -408,6 +546,264 @@ fn count_element_nodes(node: NodeRef<ScraperNode>) -> usize {
     count
 }
 
+fn extract_script(node: NodeRef<ScraperNode>) -> Option<Document> {
+    let element = node.value().as_element()?;
+
+    let text: String = node
+        .children()
+        .filter_map(|child| child.value().as_text().map(|text| text.to_string()))
+        .collect();
+
+    if text.trim().is_empty() {
+        return None;
+    }
+
+    let script_type = element
+        .attr("type")
+        .map(|script_type| script_type.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match script_type.as_str() {
+        "application/ld+json" | "application/json" => {
+            let value = match serde_json::from_str::<Value>(text.trim()) {
+                Ok(value) => value,
+                Err(e) => {
+                    log::warn!("Skipping embedded JSON that could not be parsed: {}", e);
+                    return None;
+                }
+            };
+
+            let root = match value {
+                Value::Object(map) => map,
+                other => {
+                    let mut map = Map::new();
+                    map.insert(ROOT_VALUE_KEY.to_string(), other);
+                    map
+                }
+            };
+
+            if script_type == "application/ld+json" {
+                Some(json_document(root, Some("json-ld"), "JSON-LD structured data embedded in the page"))
+            } else {
+                Some(json_document(root, element.attr("id"), "JSON data embedded in the page"))
+            }
+        }
+        "" | "text/javascript" | "application/javascript" | "module" if STATE_ASSIGNMENT.is_match(&text) => {
+            Some(Document {
+                document_type: DocumentType::JavaScript,
+                data: text,
+                metadata: DocumentMetadata {
+                    origin: None,
+                    date: None,
+                    name: element.attr("id").map(|id| id.to_string()),
+                    description: Some("Inline script assigning client state in the page".to_string()),
+                    semantic_content_types: None,
+                },
+            })
+        }
+        _ => None,
+    }
+}
+
+fn json_document(root: Map<String, Value>, name: Option<&str>, description: &str) -> Document {
+    Document {
+        document_type: DocumentType::Json,
+        data: Value::Object(root).to_string(),
+        metadata: DocumentMetadata {
+            origin: None,
+            date: None,
+            name: name.map(|name| name.to_string()),
+            description: Some(description.to_string()),
+            semantic_content_types: None,
+        },
+    }
+}
+
+fn microdata_item(node: NodeRef<ScraperNode>) -> Map<String, Value> {
+    let mut item = Map::new();
+
+    if let Some(element) = node.value().as_element() {
+        if let Some(item_type) = element.attr("itemtype") {
+            item.insert("@type".to_string(), Value::String(item_type.trim().to_string()));
+        }
+
+        if let Some(item_id) = element.attr("itemid") {
+            item.insert("@id".to_string(), Value::String(item_id.trim().to_string()));
+        }
+    }
+
+    for child in node.children() {
+        collect_microdata_properties(child, &mut item);
+    }
+
+    item
+}
+
+fn collect_microdata_properties(node: NodeRef<ScraperNode>, item: &mut Map<String, Value>) {
+    let element = match node.value().as_element() {
+        Some(element) => element,
+        None => return,
+    };
+
+    let is_scope = element.attr("itemscope").is_some();
+
+    if let Some(names) = element.attr("itemprop") {
+        let value = if is_scope {
+            Value::Object(microdata_item(node))
+        } else {
+            Value::String(microdata_value(node))
+        };
+
+        for name in names.split_whitespace() {
+            match item.get_mut(name) {
+                Some(Value::Array(values)) => values.push(value.clone()),
+                Some(existing) => *existing = Value::Array(vec![existing.take(), value.clone()]),
+                None => {
+                    item.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+    }
+
+    // Properties below a nested item belong to that item
+    if is_scope {
+        return;
+    }
+
+    for child in node.children() {
+        collect_microdata_properties(child, item);
+    }
+}
+
+fn microdata_value(node: NodeRef<ScraperNode>) -> String {
+    let element = match node.value().as_element() {
+        Some(element) => element,
+        None => return String::new(),
+    };
+
+    let attribute = match element.name() {
+        "meta" => Some("content"),
+        "a" | "area" | "link" => Some("href"),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "object" => Some("data"),
+        "data" | "meter" => Some("value"),
+        "time" if element.attr("datetime").is_some() => Some("datetime"),
+        _ => None,
+    };
+
+    match attribute {
+        Some(attribute) => element.attr(attribute).unwrap_or_default().trim().to_string(),
+        None => node
+            .descendants()
+            .filter_map(|descendant| descendant.value().as_text().map(|text| text.to_string()))
+            .collect::<Vec<String>>()
+            .join(" ")
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" "),
+    }
+}
+
+fn write_html_object(writer: &mut MarkupWriter, map: &Map<String, Value>, depth: usize, level: usize) {
+    let is_nested = |value: &Value| match value {
+        Value::Object(_) => true,
+        Value::Array(items) => items.iter().any(|item| matches!(item, Value::Object(_) | Value::Array(_))),
+        _ => false,
+    };
+
+    let fields: Vec<(&String, &Value)> = map
+        .iter()
+        .filter(|(_, value)| !is_nested(value) && !writer.is_excluded(value))
+        .collect();
+
+    if !fields.is_empty() {
+        writer.line(depth, "<dl>");
+
+        for (key, value) in fields {
+            let term = writer.escape(&humanize(key));
+            writer.line(depth + 1, &format!("<dt data-key=\"{}\">{}</dt>", writer.escape(key), term));
+
+            let values = match value {
+                Value::Array(items) => items.iter().filter(|item| !writer.is_excluded(item)).collect(),
+                value => vec![value],
+            };
+
+            for value in values {
+                let definition = html_value(writer, value);
+                writer.line(depth + 1, &format!("<dd>{}</dd>", definition));
+            }
+        }
+
+        writer.line(depth, "</dl>");
+    }
+
+    let heading = level.min(MAX_HEADING_LEVEL);
+
+    for (key, value) in map.iter().filter(|(_, value)| is_nested(value)) {
+        let data_key = writer.escape(key);
+        let title = writer.escape(&humanize(key));
+
+        match value {
+            Value::Object(child) => {
+                writer.line(depth, &format!("<article data-key=\"{}\">", data_key));
+                writer.line(depth + 1, &format!("<h{}>{}</h{}>", heading, title, heading));
+                write_html_object(writer, child, depth + 1, level + 1);
+                writer.line(depth, "</article>");
+            }
+            Value::Array(items) => {
+                writer.line(depth, &format!("<section data-key=\"{}\">", data_key));
+                writer.line(depth + 1, &format!("<h{}>{}</h{}>", heading, title, heading));
+
+                let items: Vec<&Value> = items.iter().filter(|item| !writer.is_excluded(item)).collect();
+
+                for item in items {
+                    writer.line(depth + 1, "<article>");
+
+                    match item {
+                        Value::Object(child) => write_html_object(writer, child, depth + 2, level + 1),
+                        other => {
+                            let mut child = Map::new();
+                            child.insert(key.clone(), other.clone());
+                            write_html_object(writer, &child, depth + 2, level + 1);
+                        }
+                    }
+
+                    writer.line(depth + 1, "</article>");
+                }
+
+                writer.line(depth, "</section>");
+            }
+            _ => {}
+        }
+    }
+}
+
+fn html_value(writer: &MarkupWriter, value: &Value) -> String {
+    let text = value_to_text(value);
+    let text = text.trim();
+
+    if text.starts_with("http://") || text.starts_with("https://") {
+        let url = writer.escape(text);
+        format!("<a href=\"{}\">{}</a>", url, url)
+    } else {
+        writer.escape(text)
+    }
+}
+
+// e.g. "product_name" -> "Product name"
+fn humanize(key: &str) -> String {
+    let words = key
+        .trim_start_matches(['@', '_', '$'])
+        .replace(['_', '-'], " ");
+    let words = words.trim();
+
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => key.to_string(),
+    }
+}
+
 fn escape_xml(data: &str) -> String {
     data.replace("&", "&amp;")
         .replace("<", "&lt;")
@@ -0,0 +1,390 @@
+use serde_json::{Value, Map, Number as JsonNumber};
+use std::collections::HashSet;
+use swc_common::{sync::Lrc, FileName, SourceMap};
+use swc_ecma_ast::{
+    ArrayLit, AssignExpr, AssignTarget, CallExpr, Callee, Expr, Lit, MemberProp, ObjectLit,
+    Pat, Prop, PropName, PropOrSpread, SimpleAssignTarget, UnaryOp, VarDeclarator,
+};
+use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::prelude::*;
+use crate::meta_context::MetaContext;
+use crate::document::DocumentType;
+use crate::document::DocumentMetadata;
+
+use super::json::Json;
+
+// Key under which a document is placed when it consists of a single JSON value
+// that is not an object, e.g. the contents of a <script type="application/json">
+pub(super) const ROOT_VALUE_KEY: &str = "data";
+
+pub struct JavaScript;
+
+impl JavaScript {
+    pub fn to_meta_context(
+        metadata: &DocumentMetadata,
+        data: String
+    ) -> Result<MetaContext, Errors> {
+        log::trace!("In to_meta_context");
+
+        let root = Self::extract_literals(data)?;
+        let data = serde_json::to_string(&Value::Object(root))
+            .map_err(|e| Errors::JsonParseError(e.to_string()))?;
+
+        // Extracted literals are plain JSON, so the graph is built the same way
+        let mut meta_context = Json::to_meta_context(metadata, data)?;
+        meta_context.document_type = DocumentType::JavaScript;
+
+        Ok(meta_context)
+    }
+
+    pub fn from_meta_context(
+        meta_context: &MetaContext,
+        render_ids: Option<&HashSet<GraphNodeID>>,
+    ) -> Result<String, Errors> {
+        Json::from_meta_context(meta_context, render_ids)
+    }
+
+    fn extract_literals(data: String) -> Result<Map<String, Value>, Errors> {
+        // JSON-in-script is valid JSON but not necessarily a valid program
+        if let Ok(value) = serde_json::from_str::<Value>(&data) {
+            return Ok(match value {
+                Value::Object(map) => map,
+                other => {
+                    let mut map = Map::new();
+                    map.insert(ROOT_VALUE_KEY.to_string(), other);
+                    map
+                }
+            });
+        }
+
+        let source_map: Lrc<SourceMap> = Default::default();
+        let source_file = source_map.new_source_file(Lrc::new(FileName::Anon), data);
+
+        let mut parser = Parser::new(
+            Syntax::Es(EsSyntax::default()),
+            StringInput::from(&*source_file),
+            None
+        );
+
+        let program = parser.parse_program().map_err(|e| {
+            Errors::JavaScriptParseError(format!("{:?}", e.kind()))
+        })?;
+
+        let mut extractor = LiteralExtractor {
+            root: Map::new(),
+        };
+        program.visit_with(&mut extractor);
+
+        log::debug!("Extracted {} literals from JavaScript document", extractor.root.len());
+
+        Ok(extractor.root)
+    }
+}
+
+struct LiteralExtractor {
+    root: Map<String, Value>,
+}
+
+impl LiteralExtractor {
+    fn insert(&mut self, name: String, value: Value) {
+        match self.root.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                self.root.insert(name, value);
+            }
+        }
+    }
+
+    // Only object and array literals, and string constants are considered to be data.
+    // Returns false if the expression was not extracted so that its children are visited.
+    fn try_extract(&mut self, name: Option<String>, expr: &Expr) -> bool {
+        let value = match to_value(expr, false) {
+            Some(value @ (Value::Object(_) | Value::Array(_) | Value::String(_))) => value,
+            _ => return false,
+        };
+
+        if is_empty(&value) {
+            return false;
+        }
+
+        // Anonymous literals are only kept when nothing had to be dropped from them,
+        // otherwise they are likely code rather than data
+        let name = match name {
+            Some(name) => name,
+            None if matches!(value, Value::String(_)) => return false,
+            None if to_value(expr, true).is_none() => return false,
+            None => "literal".to_string(),
+        };
+
+        self.insert(name, value);
+
+        true
+    }
+}
+
+impl Visit for LiteralExtractor {
+    fn visit_var_declarator(&mut self, declarator: &VarDeclarator) {
+        if let (Pat::Ident(ident), Some(init)) = (&declarator.name, &declarator.init) {
+            if self.try_extract(Some(ident.id.sym.to_string()), init) {
+                return;
+            }
+        }
+
+        declarator.visit_children_with(self);
+    }
+
+    fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+        if let Some(name) = assign_target_name(&assign.left) {
+            if self.try_extract(Some(name), &assign.right) {
+                return;
+            }
+        }
+
+        assign.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        for arg in &call.args {
+            if arg.spread.is_none() && self.try_extract(None, &arg.expr) {
+                continue;
+            }
+
+            arg.visit_with(self);
+        }
+
+        call.callee.visit_with(self);
+    }
+}
+
+fn assign_target_name(target: &AssignTarget) -> Option<String> {
+    match target {
+        AssignTarget::Simple(SimpleAssignTarget::Ident(ident)) => Some(ident.id.sym.to_string()),
+        AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+            let object = expr_path(&member.obj)?;
+            let property = member_prop_name(&member.prop)?;
+            Some(format!("{}.{}", object, property))
+        }
+        _ => None,
+    }
+}
+
+// Dotted path of an expression such as `window.__INITIAL_STATE__`
+fn expr_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::This(_) => Some("this".to_string()),
+        Expr::Member(member) => {
+            let object = expr_path(&member.obj)?;
+            let property = member_prop_name(&member.prop)?;
+            Some(format!("{}.{}", object, property))
+        }
+        Expr::Paren(paren) => expr_path(&paren.expr),
+        _ => None,
+    }
+}
+
+fn member_prop_name(prop: &MemberProp) -> Option<String> {
+    match prop {
+        MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+        MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Lit(Lit::Str(s)) => Some(s.value.to_atom_lossy().to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn prop_name(name: &PropName) -> Option<String> {
+    match name {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_atom_lossy().to_string()),
+        PropName::Num(n) => Some(n.value.to_string()),
+        PropName::BigInt(b) => Some(b.value.to_string()),
+        PropName::Computed(_) => None,
+    }
+}
+
+// Converts a literal expression into a JSON value. When strict, any part that is not
+// a literal causes the whole expression to be rejected, otherwise it is left out.
+fn to_value(expr: &Expr, strict: bool) -> Option<Value> {
+    match expr {
+        Expr::Lit(lit) => match lit {
+            Lit::Str(s) => Some(Value::String(s.value.to_atom_lossy().to_string())),
+            Lit::Bool(b) => Some(Value::Bool(b.value)),
+            Lit::Null(_) => Some(Value::Null),
+            Lit::Num(n) => number_to_value(n.value),
+            Lit::BigInt(b) => Some(Value::String(b.value.to_string())),
+            _ => None,
+        },
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            let text = tpl.quasis
+                .iter()
+                .map(|quasi| {
+                    quasi.cooked
+                        .as_ref()
+                        .map(|cooked| cooked.to_atom_lossy().to_string())
+                        .unwrap_or_else(|| quasi.raw.to_string())
+                })
+                .collect::<String>();
+            Some(Value::String(text))
+        }
+        Expr::Unary(unary) => match (unary.op, &*unary.arg) {
+            (UnaryOp::Minus, Expr::Lit(Lit::Num(n))) => number_to_value(-n.value),
+            // Minifiers write booleans as !0 and !1, and undefined as void 0
+            (UnaryOp::Bang, Expr::Lit(Lit::Num(n))) => Some(Value::Bool(n.value == 0.0)),
+            (UnaryOp::Void, _) => Some(Value::Null),
+            _ => None,
+        },
+        Expr::Ident(ident) if &*ident.sym == "undefined" => Some(Value::Null),
+        Expr::Paren(paren) => to_value(&paren.expr, strict),
+        Expr::Array(array) => array_to_value(array, strict),
+        Expr::Object(object) => object_to_value(object, strict),
+        Expr::Call(call) => json_parse_to_value(call),
+        _ => None,
+    }
+}
+
+fn number_to_value(number: f64) -> Option<Value> {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Some(Value::Number(JsonNumber::from(number as i64)))
+    } else {
+        JsonNumber::from_f64(number).map(Value::Number)
+    }
+}
+
+fn array_to_value(array: &ArrayLit, strict: bool) -> Option<Value> {
+    let mut values = Vec::new();
+
+    for element in &array.elems {
+        let value = element
+            .as_ref()
+            .filter(|element| element.spread.is_none())
+            .and_then(|element| to_value(&element.expr, strict));
+
+        match value {
+            Some(value) => values.push(value),
+            None if strict => return None,
+            None => {}
+        }
+    }
+
+    Some(Value::Array(values))
+}
+
+fn object_to_value(object: &ObjectLit, strict: bool) -> Option<Value> {
+    let mut map = Map::new();
+
+    for prop in &object.props {
+        let entry = match prop {
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(key_value) => prop_name(&key_value.key)
+                    .and_then(|key| Some((key, to_value(&key_value.value, strict)?))),
+                _ => None,
+            },
+            PropOrSpread::Spread(_) => None,
+        };
+
+        match entry {
+            Some((key, value)) => {
+                map.insert(key, value);
+            }
+            None if strict => return None,
+            None => {}
+        }
+    }
+
+    Some(Value::Object(map))
+}
+
+// Handles data embedded as `JSON.parse("...")`
+fn json_parse_to_value(call: &CallExpr) -> Option<Value> {
+    let callee = match &call.callee {
+        Callee::Expr(expr) => expr_path(expr)?,
+        _ => return None,
+    };
+
+    if callee != "JSON.parse" || call.args.len() != 1 {
+        return None;
+    }
+
+    match to_value(&call.args[0].expr, true)? {
+        Value::String(json) => serde_json::from_str(&json).ok(),
+        _ => None,
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn extract(data: &str) -> Value {
+        Value::Object(JavaScript::extract_literals(data.to_string()).unwrap())
+    }
+
+    #[test]
+    fn extracts_object_and_array_literals() {
+        let data = r#"
+            var config = {
+                title: "Store",
+                items: [{ name: "Pen", price: 1.5 }, { name: "Ink", price: -2 }],
+                "on-sale": !0,
+            };
+            window.__INITIAL_STATE__ = { user: { id: 7 }, missing: void 0 };
+            self["__data"] = JSON.parse('{"pages":[1,2]}');
+            track("page", [1, 2, 3]);
+            var count = 3;
+        "#;
+
+        assert_eq!(extract(data), json!({
+            "config": {
+                "title": "Store",
+                "items": [{ "name": "Pen", "price": 1.5 }, { "name": "Ink", "price": -2 }],
+                "on-sale": true
+            },
+            "window.__INITIAL_STATE__": { "user": { "id": 7 }, "missing": null },
+            "self.__data": { "pages": [1, 2] },
+            "literal": [1, 2, 3]
+        }));
+    }
+
+    #[test]
+    fn leaves_out_parts_that_are_not_literals() {
+        let data = r#"
+            var state = { id: 1, total: price * 2, render() { return 1; } };
+            init({ handler: function () {} });
+        "#;
+
+        assert_eq!(extract(data), json!({ "state": { "id": 1 } }));
+    }
+
+    #[test]
+    fn reads_json_before_trying_javascript() {
+        // As a program, the object would be a block with a labelled statement
+        assert_eq!(
+            extract(r#"{"products": [{"id": 1, "tags": ["a", "b"]}], "next": null}"#),
+            json!({ "products": [{ "id": 1, "tags": ["a", "b"] }], "next": null })
+        );
+        assert_eq!(extract("[1, 2]"), json!({ ROOT_VALUE_KEY: [1, 2] }));
+    }
+
+    #[test]
+    fn reports_what_is_not_javascript() {
+        assert!(matches!(
+            JavaScript::extract_literals("var = ;".to_string()),
+            Err(Errors::JavaScriptParseError(_))
+        ));
+    }
+}
@@ -12,6 +12,8 @@ use crate::data_node::DataNode;
 use crate::meta_context::MetaContext;
 use crate::translation_node::TranslationNode;
 use crate::translation_network::TranslationNetwork;
+use crate::translation_context::TranslationWarning;
+use crate::transformation::Cardinality;
 use crate::normal_context::NormalContext;
 use crate::normal_meta_context::NormalMetaContext;
 
@@ -137,6 +139,17 @@ impl Json {
     pub fn from_normal_meta_context(
         normal_meta_context: &NormalMetaContext,
     ) -> Result<String, Errors> {
+        let result = Self::normal_meta_context_to_value(normal_meta_context)?;
+
+        let data = serde_json::to_string_pretty(&result).expect("Could not make a JSON string");
+
+        Ok(data)
+    }
+
+    // The value tree is shared with the writers of the other document types
+    pub fn normal_meta_context_to_value(
+        normal_meta_context: &NormalMetaContext,
+    ) -> Result<Value, Errors> {
         let graph_root = normal_meta_context.graph_root.clone();
 
         let mut result: Value = Value::Object(Map::new());
@@ -170,7 +183,10 @@ impl Json {
             }
 
             for child in &read_lock!(graph_node).children {
-                let child_context = normal_meta_context.contexts_lookup.get(&read_lock!(child).id).unwrap();
+                // A graph built from a single network leaves out the contexts of the others
+                let Some(child_context) = normal_meta_context.contexts_lookup.get(&read_lock!(child).id) else {
+                    continue;
+                };
 
                 if let Some(network_name) = &child_context.network_name {
                     let mut inner_result: Value = Value::Object(Map::new());
@@ -213,9 +229,7 @@ impl Json {
             &mut result
         );
 
-        let data = serde_json::to_string_pretty(&result).expect("Could not make a JSON string");
-
-        Ok(data)
+        Ok(result)
     }
 
     pub fn from_meta_context(
@@ -358,10 +372,10 @@ impl Json {
         Ok(data)
     }
 
-    pub fn from_translation(
+    pub fn translation_to_value(
         translation_context: Arc<RwLock<TranslationContext>>
-    ) -> Result<String, Errors> {
-        log::trace!("In from_translation");
+    ) -> Result<Value, Errors> {
+        log::trace!("In translation_to_value");
 
         let graph_root: Graph = {
             let lock = read_lock!(translation_context);
@@ -374,8 +388,9 @@ impl Json {
         fn recurse(
             translation_context: Arc<RwLock<TranslationContext>>,
             graph_node: Graph,
-            result: &mut Value
-        ) {
+            result: &mut Value,
+            warnings: &mut Vec<TranslationWarning>,
+        ) -> Result<(), Errors> {
             let current_context = {
                 let lock = read_lock!(translation_context);
                 let meta_context = lock.input_meta_context.as_ref().unwrap();
@@ -395,17 +410,14 @@ impl Json {
             if let Some(translation_node) = translation_node {
                 let data_node = &current_context.data_node;
 
-                let translated: Vec<DataNode> = translation_node
-                    .transformations
-                    .iter()
-                    .map(|transformation| transformation.transform(data_node.clone()).expect("Could not transform"))
-                    .collect();
-
-                for node in translated {
-                    for (key, value) in node.fields {
-                        let json_value = json!(value.trim().to_string());
+                for transformation in &translation_node.transformations {
+                    for value in transformation.transform(data_node.clone())? {
+                        let json_value = match value {
+                            Value::String(value) => json!(value.trim().to_string()),
+                            value => value,
+                        };
                         if let Value::Object(ref mut map) = result {
-                            map.insert(key.clone(), json_value);
+                            map.insert(transformation.image.clone(), json_value);
                         }
                     }
                 }
@@ -423,44 +435,72 @@ impl Json {
 
             if let Some(translation_network) = translation_network {
                 let transformation = &translation_network.transformation;
+                let children = read_lock!(graph_node).children.clone();
+
+                // An array holds its items as children, anything else is made of its children
+                let items: Vec<Value> = if transformation.source_cardinality() == Cardinality::Array {
+                    let mut items = Vec::new();
 
-                if transformation.cardinality == "array" {
-                    for child in &read_lock!(graph_node).children {
+                    for child in children {
                         let mut inner_result: Value = Value::Object(Map::new());
 
                         recurse(
                             Arc::clone(&translation_context),
-                            Arc::clone(&child),
-                            &mut inner_result
-                        );
+                            child,
+                            &mut inner_result,
+                            warnings,
+                        )?;
 
-                        if let Value::Object(ref mut map) = result {
-                            match map.entry(transformation.image.clone()) {
-                                serde_json::map::Entry::Vacant(entry) => {
-                                    entry.insert(json!(vec![inner_result]));
-                                }
-                                serde_json::map::Entry::Occupied(mut entry) => {
-                                    let existing = entry.get_mut();
-                                    if let Value::Array(ref mut arr) = existing {
-                                        arr.push(inner_result)
-                                    }
-                                }
-                            }
-                        }
+                        items.push(transformation.transform(inner_result)?);
                     }
+
+                    items
                 } else {
                     let mut inner_result: Value = Value::Object(Map::new());
 
-                    for child in &read_lock!(graph_node).children {
+                    for child in children {
                         recurse(
                             Arc::clone(&translation_context),
-                            Arc::clone(&child),
-                            &mut inner_result
-                        );
+                            child,
+                            &mut inner_result,
+                            warnings,
+                        )?;
                     }
 
-                    if let Value::Object(ref mut map) = result {
-                        map.insert(transformation.image.clone(), inner_result);
+                    vec![transformation.transform(inner_result)?]
+                };
+
+                let (shaped, loss) = transformation.shape(items);
+
+                let mut record_loss = |message: String| {
+                    log::warn!("Translating network {}: {}", transformation.image, message);
+                    warnings.push(TranslationWarning {
+                        network: transformation.image.clone(),
+                        source_cardinality: transformation.source_cardinality().to_string(),
+                        target_cardinality: transformation.target_cardinality().to_string(),
+                        message,
+                    });
+                };
+
+                if let Some(message) = loss {
+                    record_loss(message);
+                }
+
+                if let (Some(shaped), Value::Object(map)) = (shaped, &mut *result) {
+                    // Every occurrence of the network adds to the same array
+                    match (map.entry(transformation.image.clone()), shaped) {
+                        (serde_json::map::Entry::Occupied(mut entry), Value::Array(items)) if entry.get().is_array() => {
+                            if let Value::Array(ref mut existing) = entry.get_mut() {
+                                existing.extend(items);
+                            }
+                        }
+                        (serde_json::map::Entry::Occupied(mut entry), shaped) => {
+                            record_loss("replaced the value of an earlier occurrence".to_string());
+                            entry.insert(shaped);
+                        }
+                        (serde_json::map::Entry::Vacant(entry), shaped) => {
+                            entry.insert(shaped);
+                        }
                     }
                 }
 
@@ -469,19 +509,27 @@ impl Json {
                     recurse(
                         Arc::clone(&translation_context),
                         Arc::clone(&child),
-                        result
-                    );
+                        result,
+                        warnings,
+                    )?;
                 }
             }
+
+            Ok(())
         }
 
+        let mut warnings = Vec::new();
+
         recurse(
             Arc::clone(&translation_context),
             Arc::clone(&graph_root),
-            &mut result
-        );
+            &mut result,
+            &mut warnings,
+        )?;
+
+        write_lock!(translation_context).update_warnings(warnings);
 
-        Ok(serde_json::to_string_pretty(&result).expect("Could not make a JSON string"))
+        Ok(result)
     }
 
     pub fn from_normalized_graph(
@@ -489,6 +537,18 @@ impl Json {
     ) -> Result<String, Errors> {
         log::trace!("In from_normalized_graph_json");
 
+        let result = Self::normalized_graph_to_value(normalization_context)?;
+
+        let data = serde_json::to_string_pretty(&result).expect("Could not make a JSON string");
+
+        Ok(data)
+    }
+
+    pub fn normalized_graph_to_value(
+        normalization_context: Arc<RwLock<NormalizationContext>>,
+    ) -> Result<Value, Errors> {
+        log::trace!("In normalized_graph_to_value");
+
         let graph_root = read_lock!(normalization_context).normal_graph_root.clone().unwrap();
 
         let mut result: Map<String, Value> = Map::new();
@@ -558,9 +618,7 @@ for json_node in json_nodes {
             &mut result,
         );
 
-        let data = serde_json::to_string_pretty(&result).expect("Could not make a JSON string");
-
-        Ok(data)
+        Ok(Value::Object(result))
     }
 
     fn get_document_node(data: String) -> Result<DocumentNode, Errors> {
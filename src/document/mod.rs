@@ -1,21 +1,35 @@
 use std::sync::{Arc, RwLock};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::fmt;
 
 mod json;
 mod xml;
 mod html;
+mod javascript;
+mod plain_text;
+mod delimited;
+mod writer;
 
 use crate::prelude::*;
 use crate::document_format::DocumentFormat;
 use crate::provider::Provider;
-use crate::llm::LLM;
 use crate::normal_meta_context::NormalMetaContext;
+use crate::json_schema::JsonSchema;
+use crate::execution_context::StageContext;
 
 use json::Json;
 use html::Html;
+use xml::Xml;
+use javascript::JavaScript;
+use plain_text::PlainText;
+use delimited::Delimited;
+
+pub use xml::DOCUMENT_ROOT;
+
+// Root element name of written documents that have no classification to name them after
+const DEFAULT_ROOT_NAME: &str = "document";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum DocumentType {
@@ -24,6 +38,9 @@ pub enum DocumentType {
     JavaScript,
     Xml,
     Html,
+    // Output only, records written as delimited tables
+    Csv,
+    Tsv,
 }
 
 impl fmt::Display for DocumentType {
@@ -34,6 +51,8 @@ impl fmt::Display for DocumentType {
             DocumentType::JavaScript => write!(f, "JavaScript"),
             DocumentType::Xml => write!(f, "Xml"),
             DocumentType::Html => write!(f, "Html"),
+            DocumentType::Csv => write!(f, "Csv"),
+            DocumentType::Tsv => write!(f, "Tsv"),
         }
     }
 }
@@ -78,7 +97,8 @@ impl Document {
         reasoner: Arc<R>,
         value: String,
         options: &Options,
-        metadata: &Metadata
+        metadata: &Metadata,
+        stage_context: &StageContext,
     ) -> Result<Self, Errors> {
         log::trace!("In from_schema_string");
 
@@ -86,16 +106,44 @@ impl Document {
             return Err(Errors::DocumentNotProvided);
         }
 
+        // JSON Schemas describe the instance well enough to build it without the reasoner
+        if let Some(json_schema) = JsonSchema::parse(&value) {
+            return Ok(Document {
+                document_type: DocumentType::Json,
+                metadata: DocumentMetadata {
+                    origin: options.origin.clone().or_else(|| Some(metadata.origin.clone())),
+                    date: options.date.clone(),
+                    name: None,
+                    description: None,
+                    semantic_content_types: None,
+                },
+                data: serde_json::to_string_pretty(&json_schema.to_instance()).expect("Could not make a JSON string"),
+            });
+        }
+
         let mut hash = Hash::from_str(&value);
         hash.finalize();
 
-        if !options.regenerate {
+        if !options.regenerates(InferenceStage::Schema) {
             if let Some(instance) = provider.get_instance_document_by_schema_hash(&hash).await? {
                 return Ok(instance);
             }
         }
 
-        let (instance, (tokens,)) = LLM::schema_to_instance(value).await?;
+        if options.offline {
+            return Err(Errors::InferenceNotAvailable(format!("No instance document for schema {}", hash)));
+        }
+
+        stage_context.check_budget()?;
+
+        let (instance, reasoner_metadata) = reasoner.schema_to_instance(value).await?;
+
+        stage_context.record_usage(
+            "Schema to instance",
+            &reasoner_metadata.model,
+            reasoner_metadata.input_tokens.into(),
+            reasoner_metadata.output_tokens.into(),
+        )?;
 
         let document = Document {
             document_type: metadata.document_type.clone().unwrap(),
@@ -129,7 +177,7 @@ impl Document {
         let document = Document {
             document_type: metadata.document_type.clone().unwrap(),
             metadata: DocumentMetadata {
-                origin: options.origin.clone(),
+                origin: options.origin.clone().or_else(|| Some(metadata.origin.clone())),
                 date: options.date.clone(),
                 name: None,
                 description: None,
@@ -145,6 +193,28 @@ impl Document {
         self.data.clone()
     }
 
+    // Along with the documents embedded in this one, which inherit its origin and date
+    pub fn to_meta_context_with_documents(&self) -> Result<(MetaContext, Vec<Document>), Errors> {
+        let (meta_context, documents) = match self.document_type {
+            DocumentType::Html => Html::to_meta_context_with_documents(
+                &self.metadata,
+                self.data.clone()
+            )?,
+            _ => (self.to_meta_context()?, Vec::new()),
+        };
+
+        let documents = documents
+            .into_iter()
+            .map(|mut document| {
+                document.metadata.origin = self.metadata.origin.clone();
+                document.metadata.date = self.metadata.date.clone();
+                document
+            })
+            .collect();
+
+        Ok((meta_context, documents))
+    }
+
     pub fn to_meta_context(&self) -> Result<MetaContext, Errors> {
         log::trace!("In to_meta_context");
 
@@ -153,13 +223,23 @@ impl Document {
                 &self.metadata,
                 self.data.clone()
             ),
-            DocumentType::PlainText => unimplemented!(),
-            DocumentType::JavaScript => unimplemented!(),
-            DocumentType::Xml => unimplemented!(),
+            DocumentType::PlainText => PlainText::to_meta_context(
+                &self.metadata,
+                self.data.clone()
+            ),
+            DocumentType::JavaScript => JavaScript::to_meta_context(
+                &self.metadata,
+                self.data.clone()
+            ),
+            DocumentType::Xml => Xml::to_meta_context(
+                &self.metadata,
+                self.data.clone()
+            ),
             DocumentType::Html => Html::to_meta_context(
                 &self.metadata,
                 self.data.clone()
             ),
+            DocumentType::Csv | DocumentType::Tsv => Err(Errors::UnexpectedDocumentType),
         }
     }
 
@@ -173,10 +253,11 @@ impl Document {
         let data = {
             match document_format.format_type {
                 DocumentType::Json => Json::from_meta_context(meta_context, render_ids)?,
-                DocumentType::PlainText => unimplemented!(),
-                DocumentType::JavaScript => unimplemented!(),
-                DocumentType::Xml => unimplemented!(),
+                DocumentType::PlainText => PlainText::from_meta_context(meta_context, render_ids)?,
+                DocumentType::JavaScript => JavaScript::from_meta_context(meta_context, render_ids)?,
+                DocumentType::Xml => Xml::from_meta_context(meta_context, render_ids)?,
                 DocumentType::Html => Html::from_meta_context(meta_context, render_ids)?,
+                DocumentType::Csv | DocumentType::Tsv => return Err(Errors::UnexpectedDocumentType),
             }
         };
 
@@ -202,10 +283,23 @@ impl Document {
         let data = {
             match document_format.format_type {
                 DocumentType::Json => Json::from_normal_meta_context(normal_meta_context)?,
-                DocumentType::PlainText => unimplemented!(),
-                DocumentType::JavaScript => unimplemented!(),
-                DocumentType::Xml => unimplemented!(),
-                DocumentType::Html => unimplemented!(),
+                DocumentType::Csv | DocumentType::Tsv => Delimited::from_value(
+                    &Json::normal_meta_context_to_value(normal_meta_context)?,
+                    &HashMap::new(),
+                    document_format,
+                )?,
+                DocumentType::PlainText | DocumentType::JavaScript => return Err(Errors::UnexpectedDocumentType),
+                DocumentType::Xml => Xml::from_value(
+                    DEFAULT_ROOT_NAME,
+                    &Json::normal_meta_context_to_value(normal_meta_context)?,
+                    document_format,
+                )?,
+                DocumentType::Html => Html::from_value(
+                    None,
+                    None,
+                    &Json::normal_meta_context_to_value(normal_meta_context)?,
+                    document_format,
+                )?,
             }
         };
 
@@ -239,29 +333,60 @@ impl Document {
                 })?
         };
 
-        match document_format.format_type {
-            DocumentType::Json => {
-                let data = Json::from_normalized_graph(Arc::clone(&normalization_context))?;
-
-                let document = Document {
-                    document_type: DocumentType::Json,
-                    data,
-                    metadata: DocumentMetadata {
-                        origin: None,
-                        date: None,
-                        name: Some(classification.name.clone()),
-                        description: Some(classification.description.clone()),
-                        semantic_content_types: Some(classification.aliases.clone()),
-                    },
-                };
-
-                Ok(document)
+        let data = match document_format.format_type {
+            DocumentType::Json => Json::from_normalized_graph(Arc::clone(&normalization_context))?,
+            DocumentType::Csv | DocumentType::Tsv => Delimited::from_value(
+                &Json::normalized_graph_to_value(Arc::clone(&normalization_context))?,
+                &Self::get_column_order(Arc::clone(&normalization_context)),
+                document_format,
+            )?,
+            DocumentType::PlainText | DocumentType::JavaScript => return Err(Errors::UnexpectedDocumentType),
+            DocumentType::Xml => Xml::from_value(
+                &classification.name,
+                &Json::normalized_graph_to_value(Arc::clone(&normalization_context))?,
+                document_format,
+            )?,
+            DocumentType::Html => Html::from_value(
+                Some(&classification.name),
+                Some(&classification.description),
+                &Json::normalized_graph_to_value(Arc::clone(&normalization_context))?,
+                document_format,
+            )?,
+        };
+
+        let document = Document {
+            document_type: document_format.format_type.clone(),
+            data,
+            metadata: DocumentMetadata {
+                origin: None,
+                date: None,
+                name: Some(classification.name.clone()),
+                description: Some(classification.description.clone()),
+                semantic_content_types: Some(classification.aliases.clone()),
+            },
+        };
+
+        Ok(document)
+    }
+
+    // Columns of each network, in the order of the field transformations of its basis nodes
+    fn get_column_order(
+        normalization_context: Arc<RwLock<NormalizationContext>>,
+    ) -> HashMap<String, Vec<String>> {
+        let lock = read_lock!(normalization_context);
+        let mut column_order: HashMap<String, Vec<String>> = HashMap::new();
+
+        for basis_network in lock.basis_networks.iter().flat_map(|networks| networks.values()) {
+            let columns = column_order.entry(basis_network.get_name()).or_default();
+
+            for transformation in basis_network.basis_nodes.iter().flat_map(|basis_node| &basis_node.transformations) {
+                if !columns.contains(&transformation.image) {
+                    columns.push(transformation.image.clone());
+                }
             }
-            DocumentType::PlainText => unimplemented!(),
-            DocumentType::JavaScript => unimplemented!(),
-            DocumentType::Xml => unimplemented!(),
-            DocumentType::Html => unimplemented!(),
         }
+
+        column_order
     }
 
     pub fn from_translation(
@@ -270,28 +395,52 @@ impl Document {
     ) -> Result<Self, Errors> {
         log::trace!("In from_translation");
 
-        match document_format.format_type {
-            DocumentType::Json => {
-                let data = Json::from_translation(Arc::clone(&translation_context))?;
-
-                let document = Document {
-                    document_type: DocumentType::Json,
-                    data,
-                    metadata: DocumentMetadata {
-                        origin: None,
-                        date: None,
-                        name: None,
-                        description: None,
-                        semantic_content_types: None,
-                    },
-                };
-
-                Ok(document)
+        let mut value = Json::translation_to_value(Arc::clone(&translation_context))?;
+
+        // Whatever format it is written in, the output has to conform to a target schema
+        let target_schema = read_lock!(translation_context).target_schema.clone();
+        if let Some(target_schema) = target_schema {
+            target_schema.coerce(&mut value);
+
+            let violations = target_schema.validate(&value);
+            if !violations.is_empty() {
+                return Err(Errors::SchemaValidationError(violations));
             }
-            DocumentType::PlainText => unimplemented!(),
-            DocumentType::JavaScript => unimplemented!(),
-            DocumentType::Xml => unimplemented!(),
-            DocumentType::Html => unimplemented!(),
         }
+
+        let data = match document_format.format_type {
+            DocumentType::Json => serde_json::to_string_pretty(&value).expect("Could not make a JSON string"),
+            DocumentType::Csv | DocumentType::Tsv => Delimited::from_value(
+                &value,
+                &HashMap::new(),
+                document_format,
+            )?,
+            DocumentType::PlainText | DocumentType::JavaScript => return Err(Errors::UnexpectedDocumentType),
+            DocumentType::Xml => Xml::from_value(
+                DEFAULT_ROOT_NAME,
+                &value,
+                document_format,
+            )?,
+            DocumentType::Html => Html::from_value(
+                None,
+                None,
+                &value,
+                document_format,
+            )?,
+        };
+
+        let document = Document {
+            document_type: document_format.format_type.clone(),
+            data,
+            metadata: DocumentMetadata {
+                origin: None,
+                date: None,
+                name: None,
+                description: None,
+                semantic_content_types: None,
+            },
+        };
+
+        Ok(document)
     }
 }
@@ -0,0 +1,477 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use xmltree::{Element, EmitterConfig, XMLNode};
+
+use crate::prelude::*;
+use crate::meta_context::MetaContext;
+use crate::document::{DocumentType, DocumentMetadata};
+
+use super::xml::Xml;
+
+const DELIMITERS: [char; 4] = ['\t', '|', ',', ';'];
+const SEPARATOR_CHARACTERS: [char; 6] = ['-', '=', '*', '_', '~', '#'];
+const MIN_TABLE_ROWS: usize = 2;
+const MIN_TABLE_COLUMNS: usize = 2;
+const MIN_FIXED_WIDTH_ROWS: usize = 3;
+const MIN_PROSE_DELIMITED_ROWS: usize = 3;
+// Cells longer than this are more likely to be prose that happens to contain commas
+const MAX_DELIMITED_CELL_WORDS: usize = 8;
+
+lazy_static! {
+    // Terminal escape sequences, e.g. the colours of captured command output
+    static ref ESCAPE_SEQUENCE: Regex = Regex::new(
+        r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-_])"
+    ).unwrap();
+}
+
+// Plain text is segmented into a tree of elements, which is then handled as an XML document:
+//
+// <text>
+//   <section title="...">
+//     <paragraph><line>...</line></paragraph>
+//     <table delimiter=","><row><cell column="...">...</cell></row></table>
+//   </section>
+// </text>
+pub struct PlainText;
+
+impl PlainText {
+    pub fn to_meta_context(
+        metadata: &DocumentMetadata,
+        data: String
+    ) -> Result<MetaContext, Errors> {
+        log::trace!("In to_meta_context");
+
+        let root = Self::segment(&data);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        root.write_with_config(&mut buffer, EmitterConfig::new().write_document_declaration(false))
+            .map_err(|e| Errors::UnexpectedError(format!("Could not write segmented text: {}", e)))?;
+        let xml = String::from_utf8(buffer)
+            .map_err(|e| Errors::UnexpectedError(e.to_string()))?;
+
+        let mut meta_context = Xml::to_meta_context(metadata, xml)?;
+        meta_context.document_type = DocumentType::PlainText;
+
+        Ok(meta_context)
+    }
+
+    pub fn from_meta_context(
+        meta_context: &MetaContext,
+        render_ids: Option<&HashSet<GraphNodeID>>,
+    ) -> Result<String, Errors> {
+        Xml::from_meta_context(meta_context, render_ids)
+    }
+
+    fn segment(data: &str) -> Element {
+        let mut root = Element::new("text");
+        let mut section = Element::new("section");
+        let mut paragraph: Vec<&str> = Vec::new();
+
+        // Control characters cannot be written to XML, so only line breaks and tabs are kept
+        let data: String = ESCAPE_SEQUENCE
+            .replace_all(data, "")
+            .chars()
+            .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+            .collect();
+
+        let lines: Vec<&str> = data.lines().map(|line| line.trim_end()).collect();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line = lines[index];
+            let next_line = lines.get(index + 1).copied();
+
+            let heading = if let Some(title) = markdown_heading(line) {
+                Some((title.to_string(), 1))
+            } else if paragraph.is_empty() && !line.trim().is_empty() && !is_separator(line) && next_line.map_or(false, is_separator) {
+                Some((line.trim().to_string(), 2))
+            } else {
+                None
+            };
+
+            if let Some((title, consumed)) = heading {
+                push_paragraph(&mut section, &mut paragraph);
+                push_section(&mut root, section);
+
+                section = Element::new("section");
+                section.attributes.insert("title".to_string(), title);
+
+                index += consumed;
+                continue;
+            }
+
+            if is_separator(line) {
+                push_paragraph(&mut section, &mut paragraph);
+                push_section(&mut root, section);
+
+                section = Element::new("section");
+            } else if line.trim().is_empty() {
+                push_paragraph(&mut section, &mut paragraph);
+            } else {
+                paragraph.push(line);
+            }
+
+            index += 1;
+        }
+
+        push_paragraph(&mut section, &mut paragraph);
+        push_section(&mut root, section);
+
+        root
+    }
+}
+
+fn push_section(root: &mut Element, section: Element) {
+    if !section.children.is_empty() {
+        root.children.push(XMLNode::Element(section));
+    }
+}
+
+// A paragraph is emitted as a table if its lines are tabular, with any leading or
+// trailing lines that do not fit the table kept as lines of their own paragraphs
+fn push_paragraph(section: &mut Element, paragraph: &mut Vec<&str>) {
+    if paragraph.is_empty() {
+        return;
+    }
+
+    let lines = std::mem::take(paragraph);
+
+    match find_table(&lines) {
+        Some((start, end, table)) => {
+            push_lines(section, &lines[..start]);
+            section.children.push(XMLNode::Element(table));
+            push_lines(section, &lines[end..]);
+        }
+        None => push_lines(section, &lines),
+    }
+}
+
+fn push_lines(section: &mut Element, lines: &[&str]) {
+    if lines.is_empty() {
+        return;
+    }
+
+    let mut paragraph = Element::new("paragraph");
+
+    for line in lines {
+        let mut element = Element::new("line");
+        element.children.push(XMLNode::Text(line.trim().to_string()));
+        paragraph.children.push(XMLNode::Element(element));
+    }
+
+    section.children.push(XMLNode::Element(paragraph));
+}
+
+fn markdown_heading(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+
+    if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+        let title = trimmed[level..].trim().trim_end_matches('#').trim();
+        if !title.is_empty() {
+            return Some(title);
+        }
+    }
+
+    None
+}
+
+fn is_separator(line: &str) -> bool {
+    let trimmed = line.trim();
+
+    trimmed.chars().count() >= 3 && SEPARATOR_CHARACTERS.iter().any(|separator| {
+        trimmed.chars().all(|c| c == *separator)
+    })
+}
+
+// Tries the whole paragraph first, then without its first and last lines, so that
+// e.g. the "total" line of `ls -l` output does not prevent detection
+fn find_table(lines: &[&str]) -> Option<(usize, usize, Element)> {
+    let candidates = [
+        (0, lines.len()),
+        (1, lines.len()),
+        (0, lines.len().saturating_sub(1)),
+        (1, lines.len().saturating_sub(1)),
+    ];
+
+    for (start, end) in candidates {
+        if end <= start || end - start < MIN_TABLE_ROWS {
+            continue;
+        }
+
+        let rows = &lines[start..end];
+
+        if let Some(table) = delimited_table(rows).or_else(|| fixed_width_table(rows)) {
+            return Some((start, end, table));
+        }
+    }
+
+    None
+}
+
+fn delimited_table(lines: &[&str]) -> Option<Element> {
+    for delimiter in DELIMITERS {
+        // Markdown tables have a row consisting only of dashes and colons below the header
+        let rows: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|line| {
+                !(delimiter == '|' && line.chars().all(|c| matches!(c, '|' | '-' | ':' | '+' | ' ')))
+            })
+            .collect();
+
+        let min_rows = if matches!(delimiter, ',' | ';') {
+            MIN_PROSE_DELIMITED_ROWS
+        } else {
+            MIN_TABLE_ROWS
+        };
+
+        if rows.len() < min_rows {
+            continue;
+        }
+
+        let has_separator_row = rows.len() < lines.len();
+
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                let row = if delimiter == '|' {
+                    row.trim().trim_start_matches('|').trim_end_matches('|')
+                } else {
+                    row
+                };
+
+                row.split(delimiter).map(|cell| cell.trim().to_string()).collect()
+            })
+            .collect();
+
+        let columns = cells[0].len();
+
+        let is_prose = matches!(delimiter, ',' | ';') && cells.iter().flatten().any(|cell| {
+            cell.split_whitespace().count() > MAX_DELIMITED_CELL_WORDS
+        });
+
+        if columns >= MIN_TABLE_COLUMNS && !is_prose && cells.iter().all(|row| row.len() == columns) {
+            let mut table = to_table(cells, has_separator_row);
+            table.attributes.insert("delimiter".to_string(), delimiter.to_string());
+            return Some(table);
+        }
+    }
+
+    None
+}
+
+// Columns are separated by character positions that are blank in every row
+fn fixed_width_table(lines: &[&str]) -> Option<Element> {
+    if lines.len() < MIN_FIXED_WIDTH_ROWS {
+        return None;
+    }
+
+    let rows: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
+    let width = rows.iter().map(|row| row.len()).max()?;
+
+    let is_blank = |column: usize| {
+        rows.iter().all(|row| row.get(column).map_or(true, |c| c.is_whitespace()))
+    };
+
+    let mut boundaries: Vec<(usize, usize)> = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for column in 0..=width {
+        match (start, column < width && !is_blank(column)) {
+            (None, true) => start = Some(column),
+            (Some(s), false) => {
+                boundaries.push((s, column));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if boundaries.len() < MIN_TABLE_COLUMNS {
+        return None;
+    }
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            boundaries
+                .iter()
+                .map(|(start, end)| {
+                    row.iter()
+                        .skip(*start)
+                        .take(end - start)
+                        .collect::<String>()
+                        .trim()
+                        .to_string()
+                })
+                .collect()
+        })
+        .collect();
+
+    // Every row must have content in the first and last column, otherwise this is more
+    // likely to be prose whose words happen to line up
+    if cells.iter().any(|row| row.first().map_or(true, |cell| cell.is_empty()) || row.last().map_or(true, |cell| cell.is_empty())) {
+        return None;
+    }
+
+    let mut table = to_table(cells, false);
+    table.attributes.insert("layout".to_string(), "fixed-width".to_string());
+
+    Some(table)
+}
+
+fn to_table(mut cells: Vec<Vec<String>>, has_known_header: bool) -> Element {
+    let mut table = Element::new("table");
+
+    let header = if has_known_header || has_header(&cells) {
+        Some(cells.remove(0))
+    } else {
+        None
+    };
+
+    for row in cells {
+        let mut row_element = Element::new("row");
+
+        for (index, value) in row.into_iter().enumerate() {
+            let column = header
+                .as_ref()
+                .and_then(|header| header.get(index).cloned())
+                .unwrap_or_else(|| (index + 1).to_string());
+
+            let mut cell = Element::new("cell");
+            cell.attributes.insert("column".to_string(), column);
+
+            if !value.is_empty() {
+                cell.children.push(XMLNode::Text(value));
+            }
+
+            row_element.children.push(XMLNode::Element(cell));
+        }
+
+        table.children.push(XMLNode::Element(row_element));
+    }
+
+    table
+}
+
+// The first row is a header if none of its cells are empty or numeric, while some
+// column holds a number in every other row
+fn has_header(cells: &[Vec<String>]) -> bool {
+    if cells.len() <= MIN_TABLE_ROWS {
+        return false;
+    }
+
+    let is_numeric = |value: &str| {
+        !value.is_empty() && value.trim_end_matches('%').replace([',', '.'], "").parse::<f64>().is_ok()
+    };
+
+    let header = &cells[0];
+
+    if header.iter().any(|cell| cell.is_empty() || is_numeric(cell)) {
+        return false;
+    }
+
+    (0..header.len()).any(|column| {
+        cells[1..].iter().all(|row| row.get(column).map_or(false, |cell| is_numeric(cell)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> DocumentMetadata {
+        DocumentMetadata {
+            origin: None,
+            date: None,
+            name: None,
+            description: None,
+            semantic_content_types: None,
+        }
+    }
+
+    fn to_xml(data: &str) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        PlainText::segment(data)
+            .write_with_config(&mut buffer, EmitterConfig::new().write_document_declaration(false))
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    // Every field of the document, e.g. ("text", "...") for lines and ("column", "...") for cells
+    fn fields(data: &str) -> HashSet<(String, String)> {
+        PlainText::to_meta_context(&metadata(), data.to_string())
+            .unwrap()
+            .contexts
+            .values()
+            .flat_map(|context| context.data_node.fields.iter().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn field(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn keeps_paragraphs_apart_from_tables() {
+        let data = "\
+Inventory
+=========
+
+Stock at the end of the day.
+Counted twice.
+
+name,count,price
+pens,12,1.50
+ink,3,4.00
+clips,40,0.10
+
+NAME     SIZE  OWNER
+a.txt    120   root
+b.txt    4096  agent
+";
+
+        assert_eq!(to_xml(data), concat!(
+            r#"<text><section title="Inventory">"#,
+            r#"<paragraph><line>Stock at the end of the day.</line><line>Counted twice.</line></paragraph>"#,
+            r#"<table delimiter=",">"#,
+            r#"<row><cell column="name">pens</cell><cell column="count">12</cell><cell column="price">1.50</cell></row>"#,
+            r#"<row><cell column="name">ink</cell><cell column="count">3</cell><cell column="price">4.00</cell></row>"#,
+            r#"<row><cell column="name">clips</cell><cell column="count">40</cell><cell column="price">0.10</cell></row>"#,
+            r#"</table>"#,
+            r#"<table layout="fixed-width">"#,
+            r#"<row><cell column="NAME">a.txt</cell><cell column="SIZE">120</cell><cell column="OWNER">root</cell></row>"#,
+            r#"<row><cell column="NAME">b.txt</cell><cell column="SIZE">4096</cell><cell column="OWNER">agent</cell></row>"#,
+            r#"</table>"#,
+            r#"</section></text>"#,
+        ));
+    }
+
+    #[test]
+    fn keeps_markup_characters_as_text() {
+        let fields = fields("\
+Q&A: is 1 < 2?
+
+| name & role | <id> |
+|-------------|------|
+| Ada & co    | <1>  |
+| Grace       | <2>  |
+");
+
+        assert!(fields.contains(&field("text", "Q&A: is 1 < 2?")));
+        assert!(fields.contains(&field("column", "name & role")));
+        assert!(fields.contains(&field("column", "<id>")));
+        assert!(fields.contains(&field("text", "Ada & co")));
+        assert!(fields.contains(&field("text", "<1>")));
+    }
+
+    #[test]
+    fn drops_terminal_escape_sequences_and_control_characters() {
+        let fields = fields("\x1b[1;31mred\x1b[0m and a bell\x07\n\x1b]0;title\x07done\r\n");
+
+        assert!(fields.contains(&field("text", "red and a bell")));
+        assert!(fields.contains(&field("text", "done")));
+    }
+}
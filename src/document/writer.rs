@@ -0,0 +1,99 @@
+use serde_json::Value;
+
+use crate::document_format::DocumentFormat;
+
+const DEFAULT_INDENT: usize = 2;
+const DEFAULT_LINE_ENDING: &str = "\n";
+const DEFAULT_ENCODING: &str = "UTF-8";
+
+// Line based output shared by the markup writers, laid out according to a DocumentFormat.
+// An indent of zero writes the whole document on a single line.
+pub struct MarkupWriter {
+    indent: usize,
+    line_ending: String,
+    encoding: String,
+    exclude_nulls: bool,
+    output: String,
+}
+
+impl MarkupWriter {
+    pub fn new(document_format: &DocumentFormat) -> Self {
+        MarkupWriter {
+            indent: document_format.indent.unwrap_or(DEFAULT_INDENT),
+            line_ending: get_line_ending(document_format),
+            encoding: document_format.encoding.clone().unwrap_or_else(|| DEFAULT_ENCODING.to_string()),
+            exclude_nulls: document_format.exclude_nulls.unwrap_or(false),
+            output: String::new(),
+        }
+    }
+
+    pub fn encoding(&self) -> &str {
+        &self.encoding
+    }
+
+    pub fn line(&mut self, depth: usize, content: &str) {
+        if self.indent == 0 {
+            self.output.push_str(content);
+        } else {
+            self.output.push_str(&" ".repeat(depth * self.indent));
+            self.output.push_str(content);
+            self.output.push_str(&self.line_ending);
+        }
+    }
+
+    // The output is always a Rust string, so for encodings that cannot represent every
+    // character, anything outside of ASCII is written as a character reference instead
+    pub fn escape(&self, text: &str) -> String {
+        let is_unicode = self.encoding.to_ascii_lowercase().starts_with("utf");
+
+        let mut escaped = String::with_capacity(text.len());
+
+        for c in text.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c if !is_unicode && !c.is_ascii() => escaped.push_str(&format!("&#x{:X};", c as u32)),
+                c => escaped.push(c),
+            }
+        }
+
+        escaped
+    }
+
+    // Normalized values are strings, so a missing value is an empty string rather than null
+    pub fn is_excluded(&self, value: &Value) -> bool {
+        self.exclude_nulls && match value {
+            Value::Null => true,
+            Value::String(s) => s.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+// Line endings can be given literally or by name, e.g. "crlf"
+pub fn get_line_ending(document_format: &DocumentFormat) -> String {
+    match document_format.line_ending.as_deref() {
+        None => DEFAULT_LINE_ENDING.to_string(),
+        Some(line_ending) => match line_ending.to_ascii_lowercase().as_str() {
+            "lf" | "unix" => "\n".to_string(),
+            "crlf" | "windows" => "\r\n".to_string(),
+            "cr" => "\r".to_string(),
+            _ => line_ending.to_string(),
+        },
+    }
+}
+
+pub fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
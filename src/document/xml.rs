@@ -0,0 +1,409 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use xml_rs::namespace::Namespace;
+use xml_rs::reader::{EventReader, ParserConfig, XmlEvent};
+use xmltree::{Element, XMLNode};
+
+use crate::prelude::*;
+use crate::context::Context;
+use crate::data_node::DataNode;
+use crate::meta_context::MetaContext;
+use crate::document_node::{DocumentNode, DocumentNodeData};
+use crate::graph_node::{Graph, GraphNode};
+use crate::hash::Hash;
+use crate::document::{DocumentType, DocumentMetadata};
+use crate::document_format::DocumentFormat;
+
+use super::writer::{MarkupWriter, value_to_text};
+
+// Name of the synthetic element that holds the XML declaration and every top level node,
+// so that processing instructions outside of the root element survive a round trip
+pub const DOCUMENT_ROOT: &str = "#document";
+
+pub struct Xml;
+
+impl Xml {
+    pub fn to_meta_context(
+        metadata: &DocumentMetadata,
+        data: String
+    ) -> Result<MetaContext, Errors> {
+        log::trace!("In to_meta_context");
+
+        let document_root = Self::get_document_node(data)?;
+        let document_root = Arc::new(RwLock::new(document_root));
+
+        let mut contexts: HashMap<ContextID, Arc<Context>> = HashMap::new();
+        let mut contexts_lookup: HashMap<ID, Arc<Context>> = HashMap::new();
+
+        fn recurse(
+            document_node: Arc<RwLock<DocumentNode>>,
+            parent_lineage: &Lineage,
+            contexts: &mut HashMap<ContextID, Arc<Context>>,
+            contexts_lookup: &mut HashMap<ID, Arc<Context>>,
+            parents: Vec<Arc<RwLock<GraphNode>>>,
+        ) -> Arc<RwLock<GraphNode>> {
+            let (hash, lineage, fields, description, network_name) = {
+                let lock = read_lock!(document_node);
+                let hash = lock.get_hash();
+                let lineage = parent_lineage.with_hash(hash.clone());
+                (hash, lineage, lock.get_fields(), lock.get_description(), lock.get_name())
+            };
+
+            let data_node = Arc::new(DataNode::new(
+                hash,
+                lineage.clone(),
+                fields,
+                description,
+            ));
+
+            let graph_node = Arc::new(RwLock::new(GraphNode::from_data_node(
+                Arc::clone(&data_node),
+                parents.clone(),
+            )));
+
+            let context = Arc::new(Context {
+                id: ID::new(),
+                acyclic_lineage: data_node.lineage.acyclic(),
+                lineage: data_node.lineage.clone(),
+                indexed_lineages: Arc::new(RwLock::new(HashMap::new())),
+                document_node: Arc::clone(&document_node),
+                graph_node: Arc::clone(&graph_node),
+                data_node: Arc::clone(&data_node),
+                network_name,
+            });
+
+            contexts.insert(context.id.clone(), Arc::clone(&context));
+            contexts_lookup.insert(data_node.id.clone(), Arc::clone(&context));
+            contexts_lookup.insert(read_lock!(document_node).id.clone(), Arc::clone(&context));
+            contexts_lookup.insert(read_lock!(graph_node).id.clone(), Arc::clone(&context));
+
+            let children: Vec<Arc<RwLock<GraphNode>>> = read_lock!(document_node)
+                .get_children()
+                .into_iter()
+                .map(|child| {
+                    recurse(
+                        Arc::new(RwLock::new(child)),
+                        &data_node.lineage,
+                        contexts,
+                        contexts_lookup,
+                        vec![Arc::clone(&graph_node)],
+                    )
+                })
+                .collect();
+
+            {
+                let mut write_lock = write_lock!(graph_node);
+
+                let child_hashes: Vec<Hash> = children
+                    .iter()
+                    .map(|child| read_lock!(child).hash.clone())
+                    .collect();
+
+                let mut subgraph_hash = Hash::from_items(child_hashes);
+                let subgraph_hash = subgraph_hash
+                    .sort()
+                    .push(write_lock.hash.clone())
+                    .finalize();
+
+                write_lock.subgraph_hash = subgraph_hash.clone();
+                write_lock.children.extend(children);
+            }
+
+            graph_node
+        }
+
+        let origin_hash = Hash::from_str(&metadata.origin.clone().unwrap_or_default());
+        let initial_lineage = Lineage::new().with_hash(origin_hash);
+
+        let graph_root = recurse(
+            Arc::clone(&document_root),
+            &initial_lineage,
+            &mut contexts,
+            &mut contexts_lookup,
+            Vec::new(),
+        );
+
+        let acyclic_subgraph_hash = {
+            let lock = read_lock!(graph_root);
+            lock.acyclic_subgraph_hash()
+        };
+
+        Ok(MetaContext {
+            contexts,
+            graph_root,
+            contexts_lookup,
+            document_type: DocumentType::Xml,
+            acyclic_subgraph_hash,
+        })
+    }
+
+    pub fn from_meta_context(
+        meta_context: &MetaContext,
+        render_ids: Option<&HashSet<GraphNodeID>>,
+    ) -> Result<String, Errors> {
+        let graph_root = meta_context.graph_root.clone();
+
+        let mut result: String = String::new();
+
+        fn recurse(
+            meta_context: &MetaContext,
+            render_ids: Option<&HashSet<GraphNodeID>>,
+            graph_node: Graph,
+            result: &mut String,
+        ) -> Result<(), Errors> {
+            let current_id = read_lock!(graph_node).id.clone();
+            let current_context = meta_context.contexts_lookup
+                .get(&current_id)
+                .ok_or_else(|| {
+                    Errors::UnexpectedError(format!("Context not found for graph node {}", current_id.to_string()))
+                })?;
+            let document_node = read_lock!(current_context.document_node).clone();
+            let children = read_lock!(graph_node).children.clone();
+
+            let should_render = if let Some(render_ids) = render_ids {
+                render_ids.contains(&current_id)
+            } else {
+                true
+            };
+
+            let (opening, closing) = if document_node.get_element_name() == DOCUMENT_ROOT {
+                (Xml::get_declaration(&document_node), None)
+            } else {
+                document_node.to_serialized_components()?
+            };
+
+            if should_render {
+                result.push_str(&opening);
+            }
+
+            for child in children {
+                recurse(
+                    meta_context,
+                    render_ids,
+                    Arc::clone(&child),
+                    result,
+                )?;
+            }
+
+            if should_render {
+                result.push_str(closing.as_deref().unwrap_or(""));
+            }
+
+            Ok(())
+        }
+
+        recurse(
+            meta_context,
+            render_ids,
+            Arc::clone(&graph_root),
+            &mut result
+        )?;
+
+        Ok(result)
+    }
+
+    // Writes a JSON value as XML, with object keys as element names and array items as
+    // repeated elements. Keys that are not valid element names keep their original
+    // spelling in a `key` attribute.
+    pub fn from_value(
+        root_name: &str,
+        value: &Value,
+        document_format: &DocumentFormat,
+    ) -> Result<String, Errors> {
+        let mut writer = MarkupWriter::new(document_format);
+
+        let declaration = format!("<?xml version=\"1.0\" encoding=\"{}\"?>", writer.escape(writer.encoding()));
+        writer.line(0, &declaration);
+
+        fn write_element(writer: &mut MarkupWriter, key: &str, value: &Value, depth: usize) {
+            if writer.is_excluded(value) {
+                return;
+            }
+
+            let name = to_element_name(key);
+            let opening = if name == key {
+                name.clone()
+            } else {
+                format!("{} key=\"{}\"", name, writer.escape(key))
+            };
+
+            match value {
+                Value::Array(items) => {
+                    for item in items {
+                        if let Value::Array(_) = item {
+                            writer.line(depth, &format!("<{}>", opening));
+                            write_element(writer, "item", item, depth + 1);
+                            writer.line(depth, &format!("</{}>", name));
+                        } else {
+                            write_element(writer, key, item, depth);
+                        }
+                    }
+                }
+                Value::Object(map) if map.is_empty() => {
+                    writer.line(depth, &format!("<{}/>", opening));
+                }
+                Value::Object(map) => {
+                    writer.line(depth, &format!("<{}>", opening));
+                    for (child_key, child_value) in map {
+                        write_element(writer, child_key, child_value, depth + 1);
+                    }
+                    writer.line(depth, &format!("</{}>", name));
+                }
+                Value::Null => {
+                    writer.line(depth, &format!("<{}/>", opening));
+                }
+                scalar => {
+                    let text = writer.escape(value_to_text(scalar).trim());
+                    writer.line(depth, &format!("<{}>{}</{}>", opening, text, name));
+                }
+            }
+        }
+
+        match value {
+            Value::Object(_) => write_element(&mut writer, root_name, value, 0),
+            other => {
+                let name = to_element_name(root_name);
+                writer.line(0, &format!("<{}>", name));
+                write_element(&mut writer, "item", other, 1);
+                writer.line(0, &format!("</{}>", name));
+            }
+        }
+
+        Ok(writer.finish())
+    }
+
+    fn get_declaration(document_node: &DocumentNode) -> String {
+        let version = document_node
+            .get_attribute_value("version")
+            .unwrap_or_else(|| "1.0".to_string());
+
+        // Documents are always written out as UTF-8 strings, regardless of the original encoding
+        match document_node.get_attribute_value("standalone") {
+            Some(standalone) => format!(
+                "<?xml version=\"{}\" encoding=\"UTF-8\" standalone=\"{}\"?>",
+                version,
+                standalone
+            ),
+            None => format!("<?xml version=\"{}\" encoding=\"UTF-8\"?>", version),
+        }
+    }
+
+    fn get_document_node(data: String) -> Result<DocumentNode, Errors> {
+        let config = ParserConfig::new()
+            .ignore_comments(true)
+            .cdata_to_characters(false);
+        let mut reader = EventReader::new_with_config(std::io::Cursor::new(data), config);
+
+        let mut document = Element::new(DOCUMENT_ROOT);
+        let mut stack: Vec<(Element, Namespace)> = Vec::new();
+
+        loop {
+            let event = reader.next().map_err(|e| {
+                log::error!("Could not parse XML: {}", e);
+                Errors::XmlParseError
+            })?;
+
+            match event {
+                XmlEvent::StartDocument { version, standalone, .. } => {
+                    document.attributes.insert("version".to_string(), version.to_string());
+
+                    if let Some(standalone) = standalone {
+                        let standalone = if standalone { "yes" } else { "no" };
+                        document.attributes.insert("standalone".to_string(), standalone.to_string());
+                    }
+                }
+                XmlEvent::StartElement { name, attributes, namespace } => {
+                    let mut element = Element::new(&name.local_name);
+                    element.prefix = name.prefix.clone();
+                    element.namespace = name.namespace.clone();
+
+                    // Only namespaces declared on this element are kept as attributes,
+                    // inherited ones are already declared by an ancestor
+                    let parent_namespace = stack.last().map(|(_, namespace)| namespace);
+
+                    for (prefix, uri) in &namespace {
+                        if prefix == "xml" || prefix == "xmlns" {
+                            continue;
+                        }
+
+                        let inherited = parent_namespace.and_then(|namespace| namespace.get(prefix));
+                        let is_declared = match inherited {
+                            Some(inherited) => inherited != uri,
+                            None => !uri.is_empty(),
+                        };
+
+                        if is_declared {
+                            let attribute = if prefix.is_empty() {
+                                "xmlns".to_string()
+                            } else {
+                                format!("xmlns:{}", prefix)
+                            };
+                            element.attributes.insert(attribute, uri.to_string());
+                        }
+                    }
+
+                    for attribute in attributes {
+                        let key = match &attribute.name.prefix {
+                            Some(prefix) => format!("{}:{}", prefix, attribute.name.local_name),
+                            None => attribute.name.local_name.clone(),
+                        };
+                        element.attributes.insert(key, attribute.value);
+                    }
+
+                    element.namespaces = if namespace.is_essentially_empty() {
+                        None
+                    } else {
+                        Some(namespace.clone())
+                    };
+
+                    stack.push((element, namespace));
+                }
+                XmlEvent::EndElement { .. } => {
+                    let (element, _) = stack.pop().ok_or(Errors::XmlParseError)?;
+                    Self::push_node(&mut stack, &mut document, XMLNode::Element(element));
+                }
+                XmlEvent::Characters(text) => {
+                    Self::push_node(&mut stack, &mut document, XMLNode::Text(text));
+                }
+                XmlEvent::CData(text) => {
+                    Self::push_node(&mut stack, &mut document, XMLNode::CData(text));
+                }
+                XmlEvent::ProcessingInstruction { name, data } => {
+                    Self::push_node(&mut stack, &mut document, XMLNode::ProcessingInstruction(name, data));
+                }
+                XmlEvent::Whitespace(_) | XmlEvent::Comment(_) => {}
+                XmlEvent::EndDocument => break,
+            }
+        }
+
+        Ok(DocumentNode::new(DocumentNodeData::Xml(XMLNode::Element(document))))
+    }
+
+    fn push_node(stack: &mut [(Element, Namespace)], document: &mut Element, node: XMLNode) {
+        match stack.last_mut() {
+            Some((parent, _)) => parent.children.push(node),
+            None => document.children.push(node),
+        }
+    }
+}
+
+fn to_element_name(key: &str) -> String {
+    let mut name: String = key
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+        .collect();
+
+    let starts_with_name_character = name
+        .chars()
+        .next()
+        .map_or(false, |c| c.is_alphabetic() || c == '_');
+
+    // Names starting with "xml" are reserved
+    if !starts_with_name_character || name.to_ascii_lowercase().starts_with("xml") {
+        name.insert(0, '_');
+    }
+
+    name
+}
@@ -1,5 +1,38 @@
+use std::str::FromStr;
+
 use crate::document::DocumentType;
 
+// How values that occur more than once in a record, e.g. repeated fields or nested
+// records, are written to a single cell of tabular output
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlattenRule {
+    // All values are joined in one cell using the given separator
+    Join(String),
+    // Only the first value is kept
+    First,
+    // Every value gets a numbered column of its own
+    Index,
+}
+
+impl Default for FlattenRule {
+    fn default() -> Self {
+        FlattenRule::Join(String::from("; "))
+    }
+}
+
+impl FromStr for FlattenRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "join" => Ok(FlattenRule::default()),
+            "first" => Ok(FlattenRule::First),
+            "index" => Ok(FlattenRule::Index),
+            other => Err(format!("Invalid flatten rule: {}", other)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct DocumentFormat {
@@ -11,6 +44,7 @@ pub struct DocumentFormat {
     pub wrap_text: Option<bool>,
     pub exclude_nulls: Option<bool>,
     pub custom_delimiter: Option<char>,
+    pub flatten: Option<FlattenRule>,
 }
 
 impl Default for DocumentFormat {
@@ -24,6 +58,7 @@ impl Default for DocumentFormat {
             wrap_text: None,
             exclude_nulls: None,
             custom_delimiter: None,
+            flatten: None,
         }
     }
 }
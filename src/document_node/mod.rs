@@ -56,6 +56,13 @@ impl DocumentNode {
         }
     }
 
+    pub fn to_serialized_components(&self) -> Result<(String, Option<String>), Errors> {
+        match &self.data {
+            DocumentNodeData::Xml(node) => Ok(Xml::to_serialized_components(&node)),
+            DocumentNodeData::Json(_) => Err(Errors::UnexpectedDocumentType),
+        }
+    }
+
     pub fn get_fields(&self) -> DataNodeFields {
         match &self.data {
             DocumentNodeData::Xml(node) => Xml::get_fields(&node),
@@ -10,6 +10,7 @@ impl Xml {
     pub fn get_name(xml_node: &XMLNode) -> String {
         match xml_node {
             XMLNode::Element(element) => element.name.clone(),
+            XMLNode::ProcessingInstruction(target, _) => target.clone(),
             _ => String::from("text"),
         }
     }
@@ -28,6 +29,32 @@ impl Xml {
                 (opening_tag, Some(closing_tag))
             }
             XMLNode::Text(text_node) => (text_node.to_string(), None),
+            XMLNode::CData(cdata_node) => (format!("<![CDATA[{}]]>", cdata_node), None),
+            XMLNode::ProcessingInstruction(target, data) => (Self::get_processing_instruction(target, data), None),
+            _ => panic!("Unexpected XML node type"),
+        }
+    }
+
+    // Unlike to_string_components, does not shorten attribute values and escapes text
+    // so that the output can be parsed back into the same document
+    pub fn to_serialized_components(xml_node: &XMLNode) -> (String, Option<String>) {
+        match xml_node {
+            XMLNode::Element(element_node) => {
+                let mut tag = format!("<{}", Self::get_qualified_name(element_node));
+
+                let mut attributes: Vec<(&String, &String)> = element_node.attributes.iter().collect();
+                attributes.sort_by(|a, b| a.0.cmp(b.0));
+
+                for (attr, value) in attributes {
+                    tag.push_str(&format!(" {}=\"{}\"", attr, escape_xml(value)));
+                }
+                tag.push('>');
+
+                (tag, Some(Self::get_closing_tag(element_node)))
+            }
+            XMLNode::Text(text_node) => (escape_xml(text_node), None),
+            XMLNode::CData(cdata_node) => (format!("<![CDATA[{}]]>", cdata_node.replace("]]>", "]]]]><![CDATA[>")), None),
+            XMLNode::ProcessingInstruction(target, data) => (Self::get_processing_instruction(target, data), None),
             _ => panic!("Unexpected XML node type"),
         }
     }
@@ -37,9 +64,15 @@ impl Xml {
             XMLNode::Element(element_node) => {
                 DataNodeFields::from_hash_map(element_node.attributes.clone())
             }
-            XMLNode::Text(text_node) => {
+            XMLNode::Text(text_node) | XMLNode::CData(text_node) => {
                 DataNodeFields::from_hash_map(HashMap::from([("text".to_string(), text_node.trim().to_string())]))
             }
+            XMLNode::ProcessingInstruction(target, data) => {
+                DataNodeFields::from_hash_map(HashMap::from([
+                    ("target".to_string(), target.clone()),
+                    ("data".to_string(), data.clone().unwrap_or_default()),
+                ]))
+            }
             _ => panic!("Unexpected XML node type"),
         }
     }
@@ -56,7 +89,8 @@ impl Xml {
     pub fn get_description(xml_node: &XMLNode) -> String {
         match xml_node {
             XMLNode::Element(element_node) => element_node.name.clone(),
-            XMLNode::Text(text_node) => {
+            XMLNode::ProcessingInstruction(target, _) => format!("?{}", target),
+            XMLNode::Text(text_node) | XMLNode::CData(text_node) => {
                 let mut description = text_node.to_string();
 
                 let truncate_at = description
@@ -78,15 +112,16 @@ impl Xml {
                 .iter()
                 .map(|child| child.clone())
                 .collect(),
-            XMLNode::Text(_text_node) => Vec::new(),
+            XMLNode::Text(_) | XMLNode::CData(_) | XMLNode::ProcessingInstruction(_, _) => Vec::new(),
             _ => panic!("Unexpected XML node type"),
         }
     }
 
     pub fn get_element_name(xml_node: &XMLNode) -> String {
         match xml_node {
-            XMLNode::Element(element_node) => element_node.name.clone(),
-            XMLNode::Text(_) => "#text".to_string(),
+            XMLNode::Element(element_node) => Self::get_qualified_name(element_node),
+            XMLNode::Text(_) | XMLNode::CData(_) => "#text".to_string(),
+            XMLNode::ProcessingInstruction(_, _) => "#processing-instruction".to_string(),
             _ => panic!("Unexpected XML node type"),
         }
     }
@@ -101,13 +136,14 @@ impl Xml {
                 let combined = format!("{}:{}", element_node.name, attr_str);
                 Hash::from_str(&combined)
             }
-            XMLNode::Text(_) => Hash::from_str("text"),
+            XMLNode::Text(_) | XMLNode::CData(_) => Hash::from_str("text"),
+            XMLNode::ProcessingInstruction(target, _) => Hash::from_str(&format!("?{}", target)),
             _ => panic!("Unexpected XML node type"),
         }
     }
 
     fn get_opening_tag(element: &Element) -> String {
-        let mut tag = format!("<{}", element.name);
+        let mut tag = format!("<{}", Self::get_qualified_name(element));
 
         let mut attributes: Vec<(&String, &String)> = element.attributes.iter().collect();
 
@@ -133,6 +169,28 @@ impl Xml {
     }
 
     fn get_closing_tag(element: &Element) -> String {
-        format!("</{}>", element.name)
+        format!("</{}>", Self::get_qualified_name(element))
+    }
+
+    fn get_qualified_name(element: &Element) -> String {
+        match &element.prefix {
+            Some(prefix) => format!("{}:{}", prefix, element.name),
+            None => element.name.clone(),
+        }
     }
+
+    fn get_processing_instruction(target: &str, data: &Option<String>) -> String {
+        match data {
+            Some(data) => format!("<?{} {}?>", target, data),
+            None => format!("<?{}?>", target),
+        }
+    }
+}
+
+fn escape_xml(data: &str) -> String {
+    data.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+        .replace("'", "&apos;")
 }
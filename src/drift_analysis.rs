@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use crate::prelude::*;
+use crate::config::CONFIG;
+use crate::document::Document;
+use crate::provider::bundle::OriginIndex;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DriftPolicy {
+    // Stop before anything is inferred for the document
+    Fail,
+    // Infer whatever the stored inferences do not cover, e.g. basis fields of new fields
+    Regenerate,
+    // Carry on with the stored inferences only, as an offline run would
+    Proceed,
+}
+
+impl FromStr for DriftPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fail" => Ok(DriftPolicy::Fail),
+            "regenerate" => Ok(DriftPolicy::Regenerate),
+            "proceed" => Ok(DriftPolicy::Proceed),
+            other => Err(format!("Invalid drift policy: {}", other)),
+        }
+    }
+}
+
+// The structure of the last document normalized for an origin, which the next document
+// from the same origin is compared against
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StructureSnapshot {
+    pub acyclic_subgraph_hash: Hash,
+    pub acyclic_lineages: BTreeSet<String>,
+    pub field_names: BTreeSet<String>,
+}
+
+impl StructureSnapshot {
+    pub fn from_meta_context(meta_context: &MetaContext) -> Self {
+        StructureSnapshot {
+            acyclic_subgraph_hash: meta_context.acyclic_subgraph_hash.clone(),
+            acyclic_lineages: meta_context.contexts
+                .values()
+                .map(|context| context.acyclic_lineage.to_string())
+                .collect(),
+            field_names: get_field_names(meta_context),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DriftReport {
+    pub origin: String,
+    pub total_contexts: usize,
+    pub known_contexts: usize,
+    pub known_fraction: f64,
+    pub threshold: f64,
+    pub exceeds_threshold: bool,
+    pub new_lineages: Vec<String>,
+    pub vanished_lineages: Vec<String>,
+    pub new_fields: Vec<String>,
+    pub vanished_basis_fields: Vec<String>,
+    #[serde(skip)]
+    pub previous_acyclic_subgraph_hash: Hash,
+    #[serde(skip)]
+    pub previous_field_names: BTreeSet<String>,
+}
+
+// Compares the document against the structure recorded for its origin and returns the
+// options the rest of the normalization runs with
+pub async fn detect_drift<P: Provider>(
+    provider: Arc<P>,
+    normalization_context: Arc<RwLock<NormalizationContext>>,
+    options: &Options,
+    stage_context: &StageContext,
+) -> Result<Options, Errors> {
+    log::trace!("In detect_drift");
+
+    let Some(drift_policy) = &options.drift_policy else {
+        return Ok(options.clone());
+    };
+
+    let (origin, meta_context) = {
+        let lock = read_lock!(normalization_context);
+        let meta_context = lock.meta_context.clone().ok_or(Errors::DeficientNormalizationContextError("Meta context not provided in normalization context".to_string()))?;
+        (get_origin(&lock), meta_context)
+    };
+
+    let Some(origin) = origin else {
+        log::warn!("Document has no origin, skipping drift analysis");
+        return Ok(options.clone());
+    };
+
+    let threshold = options.drift_threshold.unwrap_or_else(|| read_lock!(CONFIG).drift.threshold);
+
+    let Some(drift_report) = analyze_drift(provider, &origin, &meta_context, threshold).await? else {
+        log::info!("No structure recorded for origin {}", origin);
+        return Ok(options.clone());
+    };
+
+    log::info!(
+        "{} of {} contexts known for origin {}, {} new and {} vanished lineages",
+        drift_report.known_contexts,
+        drift_report.total_contexts,
+        origin,
+        drift_report.new_lineages.len(),
+        drift_report.vanished_lineages.len(),
+    );
+
+    stage_context.record_drift_report(drift_report.clone());
+
+    let exceeds_threshold = drift_report.exceeds_threshold;
+    let known_fraction = drift_report.known_fraction;
+
+    {
+        let mut lock = write_lock!(normalization_context);
+        lock.update_drift_report(drift_report);
+    }
+
+    if !exceeds_threshold {
+        return Ok(options.clone());
+    }
+
+    match drift_policy {
+        DriftPolicy::Fail => Err(Errors::StructuralDrift(format!(
+            "only {:.1}% of contexts are known for origin {}, below the threshold of {:.1}%",
+            known_fraction * 100.0,
+            origin,
+            threshold * 100.0,
+        ))),
+        DriftPolicy::Regenerate => Ok(options.clone()),
+        DriftPolicy::Proceed => Ok(Options {
+            offline: true,
+            ..options.clone()
+        }),
+    }
+}
+
+pub async fn analyze_drift<P: Provider>(
+    provider: Arc<P>,
+    origin: &str,
+    meta_context: &MetaContext,
+    threshold: f64,
+) -> Result<Option<DriftReport>, Errors> {
+    let Some(snapshot) = provider.get_structure_snapshot(origin).await? else {
+        return Ok(None);
+    };
+
+    let current = StructureSnapshot::from_meta_context(meta_context);
+
+    let total_contexts = meta_context.contexts.len();
+    let known_contexts = meta_context.contexts
+        .values()
+        .filter(|context| snapshot.acyclic_lineages.contains(&context.acyclic_lineage.to_string()))
+        .count();
+    let known_fraction = if total_contexts == 0 {
+        1.0
+    } else {
+        known_contexts as f64 / total_contexts as f64
+    };
+
+    let basis_field_names: HashSet<String> = provider
+        .get_basis_fields_by_acyclic_subgraph_hash(&snapshot.acyclic_subgraph_hash).await?
+        .into_iter()
+        .map(|basis_field| basis_field.name)
+        .collect();
+
+    let mut vanished_basis_fields: Vec<String> = basis_field_names
+        .into_iter()
+        .filter(|name| name != "text" && !current.field_names.contains(name))
+        .collect();
+    vanished_basis_fields.sort();
+
+    Ok(Some(DriftReport {
+        origin: origin.to_string(),
+        total_contexts,
+        known_contexts,
+        known_fraction,
+        threshold,
+        exceeds_threshold: known_fraction < threshold,
+        new_lineages: current.acyclic_lineages.difference(&snapshot.acyclic_lineages).cloned().collect(),
+        vanished_lineages: snapshot.acyclic_lineages.difference(&current.acyclic_lineages).cloned().collect(),
+        new_fields: current.field_names.difference(&snapshot.field_names).cloned().collect(),
+        vanished_basis_fields,
+        previous_acyclic_subgraph_hash: snapshot.acyclic_subgraph_hash,
+        previous_field_names: snapshot.field_names,
+    }))
+}
+
+// Only documents normalized with a drift policy are recorded, and never from a run that
+// could not infer everything it needed
+pub async fn record_structure<P: Provider>(
+    provider: Arc<P>,
+    normalization_context: Arc<RwLock<NormalizationContext>>,
+    options: &Options,
+) -> Result<(), Errors> {
+    if options.drift_policy.is_none() || options.offline {
+        return Ok(());
+    }
+
+    let (origin, meta_context) = {
+        let lock = read_lock!(normalization_context);
+        (get_origin(&lock), lock.meta_context.clone())
+    };
+
+    if let (Some(origin), Some(meta_context)) = (origin, meta_context) {
+        provider.save_structure_snapshot(
+            &origin,
+            StructureSnapshot::from_meta_context(&meta_context),
+        ).await?;
+    }
+
+    Ok(())
+}
+
+// Unlike the structure, the lineages of every document normalized for an origin are kept,
+// embedded ones included, so that the inferences made for the origin can be found later
+pub async fn index_origin<P: Provider>(
+    provider: Arc<P>,
+    normalization_context: Arc<RwLock<NormalizationContext>>,
+    options: &Options,
+) -> Result<(), Errors> {
+    if options.offline {
+        return Ok(());
+    }
+
+    let (origin, meta_context) = {
+        let lock = read_lock!(normalization_context);
+        (get_origin(&lock), lock.meta_context.clone())
+    };
+
+    if let (Some(origin), Some(meta_context)) = (origin, meta_context) {
+        let snapshot = StructureSnapshot::from_meta_context(&meta_context);
+
+        provider.update_origin_index(&origin, OriginIndex {
+            acyclic_subgraph_hashes: snapshot.acyclic_subgraph_hash.to_string().into_iter().collect(),
+            acyclic_lineages: snapshot.acyclic_lineages,
+            lineages: get_lineages(&meta_context),
+        }).await?;
+    }
+
+    Ok(())
+}
+
+// Translations are made from the normalized document rather than the input, so it is the
+// lineages of the normalized document they are stored by
+pub async fn index_translation<P: Provider>(
+    provider: Arc<P>,
+    normalization_context: Arc<RwLock<NormalizationContext>>,
+    normalized_meta_context: &MetaContext,
+    options: &Options,
+) -> Result<(), Errors> {
+    if options.offline {
+        return Ok(());
+    }
+
+    let origin = get_origin(&read_lock!(normalization_context));
+
+    if let Some(origin) = origin {
+        provider.update_origin_index(&origin, OriginIndex {
+            lineages: get_lineages(normalized_meta_context),
+            ..OriginIndex::default()
+        }).await?;
+    }
+
+    Ok(())
+}
+
+fn get_origin(normalization_context: &NormalizationContext) -> Option<String> {
+    normalization_context
+        .get_document(DocumentVersion::InputDocument)
+        .and_then(|document: Arc<Document>| document.metadata.origin.clone())
+}
+
+fn get_lineages(meta_context: &MetaContext) -> BTreeSet<String> {
+    meta_context.contexts
+        .values()
+        .map(|context| context.lineage.to_string())
+        .collect()
+}
+
+fn get_field_names(meta_context: &MetaContext) -> BTreeSet<String> {
+    meta_context.contexts
+        .values()
+        .flat_map(|context| context.data_node.fields.keys().cloned())
+        .filter(|name| name != "text")
+        .collect()
+}
@@ -3,32 +3,44 @@ use clap::{Arg, ArgAction, ArgMatches, Command};
 use log::LevelFilter;
 use std::env;
 use std::io::stdout;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 use tracing_subscriber::{fmt, EnvFilter};
 
+use crate::batch::{self, BatchOptions};
 use crate::config::CONFIG;
+use crate::execution_context::{ProgressEvent, ProgressMode};
+use crate::json_schema::SchemaViolation;
 use crate::document::{DocumentType, DocumentRole};
 use crate::document_format;
-use crate::normalization;
+use crate::drift_analysis::DriftPolicy;
+use crate::options::{parse_regeneration_stages, EmbeddedDataPolicy};
 use crate::package::Package;
 use crate::prelude::*;
 use crate::provider::VoidProvider;
 use crate::provider::{Provider};
-use crate::translation;
+use crate::provider::bundle::{ProviderBundle, BUNDLE_FORMAT_VERSION};
 use crate::prompt_registry::PromptRegistry;
+use crate::transformation_runtime;
+use crate::translation_coverage::CoverageFormat;
 
 #[cfg(feature = "sqlite-provider")]
 use crate::provider::sqlite::SqliteProvider;
 #[cfg(feature = "yaml-provider")]
 use crate::provider::yaml::YamlFileProvider;
 
-#[cfg(feature = "openrouter-reasoner")]
-use crate::reasoner::openrouter::OpenRouterReasoner;
+#[cfg(all(
+    any(feature = "openrouter-reasoner", feature = "openai-compatible-reasoner"),
+    not(feature = "fixture-reasoner")
+))]
+use crate::reasoner::{ReasonerBackend, RecordingReasoner, RecordingMode};
+#[cfg(feature = "fixture-reasoner")]
+use crate::reasoner::fixture::FixtureReasoner;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const PROGRAM_NAME: &str = "parversion";
@@ -36,36 +48,96 @@ const PROGRAM_NAME: &str = "parversion";
 pub async fn run() -> Result<(), Errors> {
     let start = Instant::now();
 
-    let _ = ensure_prerequisites()?;
+    let matches = parse_arguments();
+
+    // Maintaining the provider never involves the reasoner
+    let uses_backend = !matches.get_flag("offline") && matches.subcommand_name() != Some("provider");
+    let _ = ensure_prerequisites(uses_backend)?;
 
     setup();
 
-    let matches = parse_arguments();
+    if let Some(provider_matches) = matches.subcommand_matches("provider") {
+        return run_provider_command(provider_matches).await;
+    }
 
-    let execution_context = init_execution_context();
+    #[cfg(feature = "server")]
+    if let Some(serve_matches) = matches.subcommand_matches("serve") {
+        return run_server(serve_matches).await;
+    }
+
+    let (execution_context, progress_writer) = init_execution_context(&matches)?;
     let provider = init_provider().await?;
     let reasoner = init_reasoner().await?;
     let options = get_options(&matches)?;
     let documents: Vec<(String, Metadata)> = get_documents(&matches).await?;
     let translation: Option<(String, Metadata)> = get_translation(&matches).await?;
-    let document_format = document_format::DocumentFormat::default();
-
-    let package = determine_documents(
-        provider,
-        reasoner,
-        documents,
-        translation,
-        options,
-        &document_format,
-        execution_context.clone(),
-    ).await?;
+    let document_format = get_document_format(&matches)?;
 
-    log::info!("Successfully processed document");
+    if translation.is_some() {
+        report_transformation_runtimes();
+    }
 
-    if matches.get_flag("output-metadata") {
-        println!("{}", serde_json::to_string(&package.document.metadata).expect("Failed to serialize document metadata"));
+    let metadata_only = matches.get_flag("output-metadata");
+
+    // More than one document makes a batch, whose outputs are written as they are done
+    let result = if let Some(batch_options) = get_batch_options(&matches, documents.len()) {
+        run_batch(
+            provider,
+            reasoner,
+            documents,
+            translation,
+            options,
+            &document_format,
+            &batch_options,
+            matches.get_one::<String>("output-directory").map(Path::new),
+            metadata_only,
+            execution_context.clone(),
+        ).await.map(|_| None)
     } else {
-        println!("{}", package.to_string());
+        determine_documents(
+            provider,
+            reasoner,
+            documents,
+            translation,
+            options,
+            &document_format,
+            execution_context.clone(),
+        ).await.map(Some)
+    };
+
+    // Reported even when the run fails, e.g. because it went over budget
+    report_usage(&execution_context);
+
+    // A drifted document may have been processed with stored inferences only
+    if matches.get_flag("offline") || matches.contains_id("on-drift") {
+        report_missing_inferences(&execution_context);
+    }
+
+    if matches.contains_id("on-drift") {
+        report_drift(&execution_context);
+    }
+
+    report_translation_warnings(&execution_context);
+
+    if let Some(coverage_format) = matches.get_one::<String>("coverage") {
+        let coverage_format = CoverageFormat::from_str(coverage_format).map_err(Errors::UnexpectedParameter)?;
+        report_coverage(&execution_context, &coverage_format);
+    }
+
+    if let Err(Errors::SchemaValidationError(violations)) = &result {
+        report_schema_violations(violations);
+    }
+
+    // Closing the channel lets the writer drain what is left and stop
+    drop(execution_context);
+    if let Some(progress_writer) = progress_writer {
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(1), progress_writer).await;
+    }
+
+    if let Some(package) = result? {
+        log::info!("Successfully processed document");
+
+        println!("{}", render_package(&package, metadata_only));
     }
 
     let elapsed = start.elapsed();
@@ -74,8 +146,18 @@ pub async fn run() -> Result<(), Errors> {
     Ok(())
 }
 
-fn ensure_prerequisites() -> Result<(), Errors> {
-    let env_vars = &["OPENROUTER_API_KEY"];
+fn ensure_prerequisites(uses_backend: bool) -> Result<(), Errors> {
+    // Only the OpenRouter backend needs a key, and neither replaying a recording nor an
+    // offline run needs a backend
+    let uses_openrouter = {
+        let config = read_lock!(CONFIG);
+        cfg!(not(feature = "fixture-reasoner")) &&
+            uses_backend &&
+            config.reasoner.backend == "openrouter" &&
+            !config.reasoner.recording_mode.eq_ignore_ascii_case("replay")
+    };
+
+    let env_vars: &[&str] = if uses_openrouter { &["OPENROUTER_API_KEY"] } else { &[] };
 
     for &var in env_vars {
         if env::var(var).is_err() {
@@ -136,7 +218,7 @@ fn handle_error(err: Errors) {
 }
 
 fn parse_arguments() -> clap::ArgMatches {
-    Command::new(PROGRAM_NAME)
+    let command = Command::new(PROGRAM_NAME)
         .version(VERSION)
         .arg(
             Arg::new("documents")
@@ -152,7 +234,20 @@ fn parse_arguments() -> clap::ArgMatches {
                 .short('f')
                 .long("document-format")
                 .value_name("DOCUMENT_FORMAT")
-                .help("The document type : html, xml, js"),
+                .help("The document type : html, xml, js, json, text"),
+        )
+        .arg(
+            Arg::new("output-format")
+                .short('o')
+                .long("output-format")
+                .value_name("OUTPUT_FORMAT")
+                .help("The output format : json, xml, html, csv, tsv"),
+        )
+        .arg(
+            Arg::new("flatten")
+                .long("flatten")
+                .value_name("FLATTEN")
+                .help("How repeated values are written to csv and tsv cells : join, first, index"),
         )
         .arg(
             Arg::new("origin")
@@ -178,12 +273,53 @@ fn parse_arguments() -> clap::ArgMatches {
                 .action(ArgAction::Append)
                 .help("Optional. Provide document as target output schema"),
         )
+        .arg(
+            Arg::new("coverage")
+                .long("coverage")
+                .value_name("FORMAT")
+                .value_parser(["table", "json"])
+                .requires("translation")
+                .help("Report which target fields the translation maps and which source fields it drops, as a table or JSON on stderr"),
+        )
         .arg(
             Arg::new("regenerate")
                 .short('g')
                 .long("regenerate")
+                .value_name("STAGES")
+                .num_args(0..=1)
+                .value_delimiter(',')
+                .default_missing_value("all")
+                .action(ArgAction::Append)
+                .help("Regenerate inferences of the given stages and every stage downstream of them, or of all stages: classification, fields, groups, nodes, relationships, schema, translations, all"),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
                 .action(ArgAction::SetTrue)
-                .help("Regenerate inferences"),
+                .conflicts_with("regenerate")
+                .help("Only apply inferences already in the provider, never call the reasoner"),
+        )
+        .arg(
+            Arg::new("on-drift")
+                .long("on-drift")
+                .value_name("POLICY")
+                .value_parser(["fail", "regenerate", "proceed"])
+                .help("Compare documents against the structure recorded for their origin, and fail, regenerate what is new or proceed with stored inferences when too little is known"),
+        )
+        .arg(
+            Arg::new("drift-threshold")
+                .long("drift-threshold")
+                .value_name("FRACTION")
+                .value_parser(clap::value_parser!(f64))
+                .requires("on-drift")
+                .help("Fraction of contexts that must be known for the origin, defaults to the configured threshold"),
+        )
+        .arg(
+            Arg::new("embedded-data")
+                .long("embedded-data")
+                .value_name("POLICY")
+                .value_parser(["merge", "prefer"])
+                .help("Records of structured data embedded in HTML pages: merge them with the records of the markup, or prefer them over those"),
         )
         .arg(
             Arg::new("output-metadata")
@@ -192,12 +328,291 @@ fn parse_arguments() -> clap::ArgMatches {
                 .action(ArgAction::SetTrue)
                 .help("Output only document metadata"),
         )
-        .get_matches()
+        .arg(
+            Arg::new("progress")
+                .long("progress")
+                .value_name("MODE")
+                .value_parser(["json", "human", "none"])
+                .default_value("human")
+                .help("Progress reporting: json (one event per line), human, none"),
+        )
+        .arg(
+            Arg::new("progress-fd")
+                .long("progress-fd")
+                .value_name("FD")
+                .value_parser(clap::value_parser!(i32))
+                .help("Optional. File descriptor to write progress to instead of stderr"),
+        )
+        .arg(
+            Arg::new("batch")
+                .long("batch")
+                .action(ArgAction::SetTrue)
+                .help("Process every document, implied when more than one is given"),
+        )
+        .arg(
+            Arg::new("batch-sample")
+                .long("batch-sample")
+                .value_name("COUNT")
+                .value_parser(clap::value_parser!(usize))
+                .help("Optional. Documents analysed before the rest of the batch, default 3"),
+        )
+        .arg(
+            Arg::new("batch-concurrency")
+                .long("batch-concurrency")
+                .value_name("COUNT")
+                .value_parser(clap::value_parser!(usize))
+                .help("Optional. Documents processed at the same time after the sample, default 4"),
+        )
+        .arg(
+            Arg::new("output-directory")
+                .long("output-directory")
+                .value_name("DIRECTORY")
+                .help("Optional. Write one file per batch document instead of JSON lines to stdout"),
+        )
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("provider")
+                .about("Inspect, export, import and prune the inferences stored in the provider")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("List stored inferences as JSON lines")
+                        .arg(
+                            Arg::new("origin")
+                                .short('s')
+                                .long("origin")
+                                .value_name("ORIGIN")
+                                .help("Optional. Only inferences made for documents of this origin"),
+                        )
+                        .arg(
+                            Arg::new("lineage")
+                                .short('l')
+                                .long("lineage")
+                                .value_name("LINEAGE")
+                                .help("Optional. Only inferences stored under this lineage or hash"),
+                        )
+                        .arg(
+                            Arg::new("kind")
+                                .short('t')
+                                .long("kind")
+                                .value_name("KIND")
+                                .value_parser(ProviderBundle::KINDS)
+                                .help("Optional. Only inferences of this kind"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("export")
+                        .about("Export stored inferences to a JSON bundle")
+                        .arg(
+                            Arg::new("origin")
+                                .short('s')
+                                .long("origin")
+                                .value_name("ORIGIN")
+                                .help("Optional. Only inferences made for documents of this origin"),
+                        )
+                        .arg(
+                            Arg::new("output")
+                                .short('w')
+                                .long("output")
+                                .value_name("FILE")
+                                .help("Optional. File to write the bundle to instead of stdout"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Import a JSON bundle, replacing entries with the same keys")
+                        .arg(
+                            Arg::new("bundle")
+                                .value_name("FILE")
+                                .required(true)
+                                .help("Bundle to import, or - for stdin"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("prune")
+                        .about("Delete the inferences of an origin that no other origin uses")
+                        .arg(
+                            Arg::new("origin")
+                                .short('s')
+                                .long("origin")
+                                .value_name("ORIGIN")
+                                .required(true)
+                                .help("Origin whose inferences are deleted"),
+                        ),
+                ),
+        );
+
+    #[cfg(feature = "server")]
+    let command = command
+        .subcommand(
+            Command::new("serve")
+                .about("Serve normalization and translation over HTTP")
+                .arg(
+                    Arg::new("address")
+                        .short('a')
+                        .long("address")
+                        .value_name("ADDRESS")
+                        .help("Optional. Address to listen on, e.g. 127.0.0.1:8080"),
+                ),
+        );
+
+    command.get_matches()
+}
+
+async fn run_provider_command(matches: &ArgMatches) -> Result<(), Errors> {
+    let provider = init_provider().await?;
+
+    match matches.subcommand() {
+        Some(("list", list_matches)) => {
+            let mut bundle = provider.export_bundle().await?;
+
+            if let Some(origin) = list_matches.get_one::<String>("origin") {
+                bundle = bundle.for_origin(origin);
+            }
+
+            if let Some(lineage) = list_matches.get_one::<String>("lineage") {
+                bundle = bundle.for_lineage(lineage);
+            }
+
+            let kind = list_matches.get_one::<String>("kind").map(String::as_str);
+
+            for entry in bundle.list(kind) {
+                let line = serde_json::to_string(&entry)
+                    .map_err(|e| Errors::UnexpectedError(format!("Could not serialize entry: {}", e)))?;
+                println!("{}", line);
+            }
+        }
+        Some(("export", export_matches)) => {
+            let mut bundle = provider.export_bundle().await?;
+
+            if let Some(origin) = export_matches.get_one::<String>("origin") {
+                bundle = bundle.for_origin(origin);
+            }
+
+            let data = serde_json::to_string_pretty(&bundle)
+                .map_err(|e| Errors::UnexpectedError(format!("Could not serialize bundle: {}", e)))?;
+
+            match export_matches.get_one::<String>("output") {
+                Some(path) => fs::write(path, data).map_err(|_| Errors::FileOutputError)?,
+                None => println!("{}", data),
+            }
+
+            eprintln!("Exported {} entries", bundle.len());
+        }
+        Some(("import", import_matches)) => {
+            let path = import_matches.get_one::<String>("bundle").expect("bundle is required");
+
+            let data = if path == "-" {
+                load_stdin().map_err(|_| Errors::DocumentNotProvided)?
+            } else {
+                get_file_as_text(path)?
+            };
+
+            let bundle: ProviderBundle = serde_json::from_str(&data)
+                .map_err(|e| Errors::JsonParseError(format!("Invalid bundle: {}", e)))?;
+
+            if bundle.format_version > BUNDLE_FORMAT_VERSION {
+                return Err(Errors::UnexpectedParameter(format!(
+                    "Bundle format version {} is newer than the supported version {}",
+                    bundle.format_version,
+                    BUNDLE_FORMAT_VERSION,
+                )));
+            }
+
+            let count = bundle.len();
+            provider.import_bundle(bundle).await?;
+
+            eprintln!("Imported {} entries", count);
+        }
+        Some(("prune", prune_matches)) => {
+            let origin = prune_matches.get_one::<String>("origin").expect("origin is required");
+
+            let bundle = provider.export_bundle().await?.exclusive_to_origin(origin);
+
+            if bundle.is_empty() {
+                log::warn!("No inferences recorded for origin {}", origin);
+            }
+
+            provider.delete_bundle(&bundle).await?;
+
+            eprintln!("Deleted {} entries for origin {}", bundle.len(), origin);
+        }
+        _ => unreachable!("a provider subcommand is required"),
+    }
+
+    Ok(())
+}
+
+// Provider and reasoner are initialized once and shared by every request
+#[cfg(feature = "server")]
+async fn run_server(matches: &ArgMatches) -> Result<(), Errors> {
+    let address = matches
+        .get_one::<String>("address")
+        .cloned()
+        .unwrap_or_else(|| read_lock!(CONFIG).server.address.clone());
+
+    let provider = init_provider().await?;
+    let reasoner = init_reasoner().await?;
+
+    report_transformation_runtimes();
+
+    crate::server::serve(provider, reasoner, &address).await
+}
+
+fn get_document_format(matches: &ArgMatches) -> Result<document_format::DocumentFormat, Errors> {
+    parse_document_format(
+        matches.get_one::<String>("output-format").map(String::as_str),
+        matches.get_one::<String>("flatten").map(String::as_str),
+    )
+}
+
+pub(crate) fn parse_document_format(
+    output_format: Option<&str>,
+    flatten: Option<&str>,
+) -> Result<document_format::DocumentFormat, Errors> {
+    let mut document_format = document_format::DocumentFormat::default();
+
+    if let Some(output_format) = output_format {
+        match output_format {
+            "csv" => document_format.format_type = DocumentType::Csv,
+            "tsv" => document_format.format_type = DocumentType::Tsv,
+            "json" | "xml" | "html" => {
+                document_format.format_type = parse_document_type(output_format)?;
+            }
+            _ => return Err(Errors::UnexpectedDocumentType),
+        }
+    }
+
+    if let Some(flatten) = flatten {
+        let rule = document_format::FlattenRule::from_str(flatten)
+            .map_err(|e| Errors::UnexpectedParameter(e))?;
+        document_format.flatten = Some(rule);
+    }
+
+    Ok(document_format)
 }
 
 fn get_options(matches: &clap::ArgMatches) -> Result<Options, Errors> {
     Ok(Options {
-        regenerate: matches.get_flag("regenerate"),
+        regenerate: matches
+            .get_many::<String>("regenerate")
+            .map(|values| parse_regeneration_stages(&values.collect::<Vec<_>>()))
+            .transpose()
+            .map_err(Errors::UnexpectedParameter)?
+            .unwrap_or_default(),
+        offline: matches.get_flag("offline"),
+        drift_policy: matches
+            .get_one::<String>("on-drift")
+            .map(|s| DriftPolicy::from_str(s))
+            .transpose()
+            .map_err(Errors::UnexpectedParameter)?,
+        drift_threshold: matches.get_one::<f64>("drift-threshold").copied(),
+        embedded_data: matches
+            .get_one::<String>("embedded-data")
+            .map(|s| EmbeddedDataPolicy::from_str(s))
+            .transpose()
+            .map_err(Errors::UnexpectedParameter)?
+            .unwrap_or_default(),
         ..Options::default()
     })
 }
@@ -359,17 +774,18 @@ async fn parse_document(raw_document: &str) -> Result<(String, MetadataPartial),
     Ok((text, partial))
 }
 
-fn parse_document_type(s: &str) -> Result<DocumentType, Errors> {
+pub(crate) fn parse_document_type(s: &str) -> Result<DocumentType, Errors> {
     match s {
         "js" => Ok(DocumentType::JavaScript),
         "json" => Ok(DocumentType::Json),
         "html" => Ok(DocumentType::Html),
         "xml" => Ok(DocumentType::Xml),
+        "text" | "txt" => Ok(DocumentType::PlainText),
         _ => Err(Errors::UnexpectedDocumentType),
     }
 }
 
-async fn determine_documents<P: Provider + ?Sized, R: Reasoner>(
+async fn determine_documents<P: Provider, R: Reasoner>(
     provider: Arc<P>,
     reasoner: Arc<R>,
     documents: Vec<(String, Metadata)>,
@@ -380,58 +796,273 @@ async fn determine_documents<P: Provider + ?Sized, R: Reasoner>(
 ) -> Result<Package, Errors> {
     log::debug!("options: {:?}", options);
 
-    let Some((document, metadata)) = documents.into_iter().next() else {
-        unimplemented!();
+    let Some(document) = documents.into_iter().next() else {
+        return Err(Errors::DocumentNotProvided);
     };
 
-    log::debug!("metadata: {:?}", metadata);
+    batch::process_document(
+        provider,
+        reasoner,
+        document,
+        translation.as_ref(),
+        &options,
+        document_format,
+        execution_context,
+    ).await
+}
 
-    if let Some((translation, translation_metadata)) = translation {
-        let translated_document = translation::translate_text_to_document(
-            provider.clone(),
-            reasoner.clone(),
-            (document, &metadata),
-            (translation, &translation_metadata),
-            &options,
-            document_format,
-            execution_context.clone(),
-        )
-        .await?;
+// Every document gets an output of its own, either a file in the output directory or a
+// line of JSON on stdout carrying its index in the batch
+async fn run_batch<P: Provider, R: Reasoner>(
+    provider: Arc<P>,
+    reasoner: Arc<R>,
+    documents: Vec<(String, Metadata)>,
+    translation: Option<(String, Metadata)>,
+    options: Options,
+    document_format: &document_format::DocumentFormat,
+    batch_options: &BatchOptions,
+    output_directory: Option<&Path>,
+    metadata_only: bool,
+    execution_context: Arc<ExecutionContext>,
+) -> Result<(), Errors> {
+    if let Some(output_directory) = output_directory {
+        fs::create_dir_all(output_directory).map_err(|e| {
+            log::error!("Could not create output directory {}: {}", output_directory.display(), e);
+            Errors::FileOutputError
+        })?;
+    }
+
+    let total = documents.len();
+    let extension = if metadata_only { "json" } else { get_file_extension(document_format) };
 
-        Ok(Package {
-            document: translated_document,
-            mutations: Vec::new(),
-        })
+    let summary = batch::process_batch(
+        provider,
+        reasoner,
+        documents,
+        translation,
+        &options,
+        document_format,
+        batch_options,
+        execution_context,
+        |index, result| match (output_directory, result) {
+            (Some(output_directory), Ok(package)) => {
+                let path = output_directory.join(format!("{:05}.{}", index, extension));
+
+                fs::write(&path, render_package(&package, metadata_only)).map_err(|e| {
+                    log::error!("Could not write output {}: {}", path.display(), e);
+                    Errors::FileOutputError
+                })
+            }
+            (Some(_), Err(e)) => {
+                eprintln!("Document {} failed: {:?}", index, e);
+                Ok(())
+            }
+            (None, Ok(package)) => {
+                let line = if metadata_only {
+                    serde_json::json!({ "index": index, "metadata": package.document.metadata })
+                } else {
+                    serde_json::json!({ "index": index, "document": package.document })
+                };
+                println!("{}", line);
+                Ok(())
+            }
+            (None, Err(e)) => {
+                println!("{}", serde_json::json!({ "index": index, "error": format!("{:?}", e) }));
+                Ok(())
+            }
+        },
+    ).await?;
+
+    if !summary.needs_inference.is_empty() {
+        eprintln!("{}", serde_json::json!({ "needs_inference": summary.needs_inference }));
+    }
+
+    if summary.failed > 0 {
+        return Err(Errors::UnexpectedError(format!(
+            "{} of {} documents could not be processed",
+            summary.failed,
+            total
+        )));
+    }
+
+    Ok(())
+}
+
+fn render_package(package: &Package, metadata_only: bool) -> String {
+    if metadata_only {
+        serde_json::to_string(&package.document.metadata).expect("Failed to serialize document metadata")
     } else {
-        let normalized_document = normalization::normalize_text_to_document(
-            provider.clone(),
-            reasoner.clone(),
-            document,
-            &options,
-            &metadata,
-            document_format,
-            execution_context.clone(),
-        )
-        .await?;
+        package.to_string()
+    }
+}
 
-        Ok(Package {
-            document: normalized_document,
-            mutations: Vec::new(),
-        })
+fn get_file_extension(document_format: &document_format::DocumentFormat) -> &'static str {
+    match document_format.format_type {
+        DocumentType::Csv => "csv",
+        DocumentType::Tsv => "tsv",
+        DocumentType::PlainText => "txt",
+        DocumentType::Json => "json",
+        DocumentType::Xml => "xml",
+        DocumentType::Html => "html",
+        DocumentType::JavaScript => "js",
     }
 }
 
-fn init_execution_context() -> Arc<ExecutionContext> {
-    let (tx, mut rx) = mpsc::unbounded_channel();
+fn get_batch_options(matches: &ArgMatches, document_count: usize) -> Option<BatchOptions> {
+    if !matches.get_flag("batch") && document_count < 2 {
+        return None;
+    }
+
+    let defaults = BatchOptions::default();
+
+    Some(BatchOptions {
+        sample_size: matches.get_one::<usize>("batch-sample").copied().unwrap_or(defaults.sample_size),
+        concurrency: matches.get_one::<usize>("batch-concurrency").copied().unwrap_or(defaults.concurrency),
+    })
+}
+
+fn report_missing_inferences(execution_context: &ExecutionContext) {
+    let missing_inferences = execution_context.missing_inferences();
+
+    if missing_inferences.is_empty() {
+        log::info!("Every inference was found in the provider");
+        return;
+    }
+
+    match serde_json::to_string_pretty(&serde_json::json!({ "missing_inferences": missing_inferences })) {
+        Ok(report) => eprintln!("{}", report),
+        Err(e) => log::warn!("Could not serialize missing inferences: {}", e),
+    }
+}
+
+fn report_drift(execution_context: &ExecutionContext) {
+    let drift_reports = execution_context.drift_reports();
+
+    if drift_reports.is_empty() {
+        log::info!("No structure recorded before for the origins of the documents");
+        return;
+    }
+
+    match serde_json::to_string_pretty(&serde_json::json!({ "drift": drift_reports })) {
+        Ok(report) => eprintln!("{}", report),
+        Err(e) => log::warn!("Could not serialize drift reports: {}", e),
+    }
+}
+
+fn report_translation_warnings(execution_context: &ExecutionContext) {
+    let translation_warnings = execution_context.translation_warnings();
+
+    if translation_warnings.is_empty() {
+        return;
+    }
+
+    match serde_json::to_string_pretty(&serde_json::json!({ "translation_warnings": translation_warnings })) {
+        Ok(report) => eprintln!("{}", report),
+        Err(e) => log::warn!("Could not serialize translation warnings: {}", e),
+    }
+}
+
+fn report_coverage(execution_context: &ExecutionContext, coverage_format: &CoverageFormat) {
+    let coverage_reports = execution_context.coverage_reports();
+
+    match coverage_format {
+        CoverageFormat::Table => {
+            for coverage_report in coverage_reports {
+                eprintln!("{}", coverage_report.to_table());
+            }
+        }
+        CoverageFormat::Json => {
+            match serde_json::to_string_pretty(&serde_json::json!({ "coverage": coverage_reports })) {
+                Ok(report) => eprintln!("{}", report),
+                Err(e) => log::warn!("Could not serialize coverage reports: {}", e),
+            }
+        }
+    }
+}
+
+fn report_schema_violations(violations: &[SchemaViolation]) {
+    match serde_json::to_string_pretty(&serde_json::json!({ "schema_violations": violations })) {
+        Ok(report) => eprintln!("{}", report),
+        Err(e) => log::warn!("Could not serialize schema violations: {}", e),
+    }
+}
+
+// Only translations run transformation code, which fails for a missing runtime once a
+// transformation needs it
+fn report_transformation_runtimes() {
+    for (runtime, availability) in transformation_runtime::detect_runtimes() {
+        match availability {
+            Ok(version) => log::info!("Transformation runtime {} available: {}", runtime, version),
+            Err(reason) => {
+                log::warn!("Transformation runtime {} not available: {}", runtime, reason);
+                eprintln!("Warning: transformation runtime {} not available: {}", runtime, reason);
+            }
+        }
+    }
+}
+
+fn report_usage(execution_context: &ExecutionContext) {
+    let report = execution_context.usage_report();
+
+    match serde_json::to_string_pretty(&report) {
+        Ok(report) => eprintln!("{}", report),
+        Err(e) => log::warn!("Could not serialize usage report: {}", e),
+    }
+}
+
+// Progress never goes to stdout, which is reserved for the output document
+fn init_execution_context(
+    matches: &ArgMatches
+) -> Result<(Arc<ExecutionContext>, Option<tokio::task::JoinHandle<()>>), Errors> {
+    let mode = matches
+        .get_one::<String>("progress")
+        .map(|s| ProgressMode::from_str(s))
+        .transpose()
+        .map_err(Errors::UnexpectedParameter)?
+        .unwrap_or(ProgressMode::Human);
+
+    if mode == ProgressMode::None {
+        return Ok((ExecutionContext::new(), None));
+    }
+
+    let (mut writer, is_terminal): (Box<dyn Write + Send>, bool) = match matches.get_one::<i32>("progress-fd") {
+        Some(fd) => {
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .open(format!("/dev/fd/{}", fd))
+                .map_err(|e| {
+                    Errors::UnexpectedParameter(format!("Could not open progress file descriptor {}: {}", fd, e))
+                })?;
+
+            (Box::new(file), false)
+        }
+        None => (Box::new(io::stderr()), atty::is(Stream::Stderr)),
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<ProgressEvent>();
     let execution_context = ExecutionContext::with_progress(tx);
 
-    tokio::spawn(async move {
+    let handle = tokio::spawn(async move {
         while let Some(event) = rx.recv().await {
-            println!("\x1b[38;2;255;0;255m{:?}\x1b[0m", event); // fuchsia
+            let line = match mode {
+                ProgressMode::Json => match serde_json::to_string(&event) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        log::warn!("Could not serialize progress event: {}", e);
+                        continue;
+                    }
+                },
+                _ if is_terminal => format!("\x1b[38;2;255;0;255m{}\x1b[0m", event.to_human()), // fuchsia
+                _ => event.to_human(),
+            };
+
+            if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+                break;
+            }
         }
     });
 
-    execution_context
+    Ok((execution_context, Some(handle)))
 }
 
 async fn init_reasoner() -> Result<Arc<impl Reasoner>, Errors> {
@@ -443,9 +1074,41 @@ async fn init_reasoner() -> Result<Arc<impl Reasoner>, Errors> {
     let prompt_registry = PromptRegistry::load(prompts_location)?;
 
     cfg_if::cfg_if! {
-        if #[cfg(feature = "openrouter-reasoner")] {
-            log::info!("Using OpenRouter reasoner");
-            Ok(Arc::new(OpenRouterReasoner::new(prompt_registry)))
+        if #[cfg(feature = "fixture-reasoner")] {
+            let fixtures_location = read_lock!(CONFIG).reasoner.fixtures_location.clone();
+            log::info!("Using fixture reasoner with fixtures at: {}", fixtures_location);
+            Ok(Arc::new(FixtureReasoner::new(prompt_registry, fixtures_location.into())))
+        } else if #[cfg(any(feature = "openrouter-reasoner", feature = "openai-compatible-reasoner"))] {
+            let (backend, recording_mode, recordings_location) = {
+                let config = read_lock!(CONFIG);
+                (
+                    config.reasoner.backend.clone(),
+                    config.reasoner.recording_mode.clone(),
+                    config.reasoner.recordings_location.clone(),
+                )
+            };
+            let recording_mode: RecordingMode = recording_mode.parse()
+                .map_err(Errors::UnexpectedParameter)?;
+
+            // Replaying needs no backend, so none is set up at all
+            if recording_mode == RecordingMode::Replay {
+                log::info!("Replaying reasoner responses from: {}", recordings_location);
+                return Ok(Arc::new(RecordingReasoner::<ReasonerBackend>::replay(
+                    prompt_registry,
+                    recordings_location.into(),
+                )));
+            }
+
+            log::info!("Using {} reasoner backend", backend);
+            if recording_mode == RecordingMode::Record {
+                log::info!("Recording reasoner responses to: {}", recordings_location);
+            }
+
+            Ok(Arc::new(RecordingReasoner::new(
+                ReasonerBackend::new(&backend, prompt_registry)?,
+                recording_mode,
+                recordings_location.into(),
+            )))
         } else {
             Err(Errors::ReasonerNotConfigured)
         }
@@ -5,3 +5,9 @@ pub fn get_env_variable(key: &str) -> String {
 
     std::env::var(key).unwrap().to_string()
 }
+
+pub fn get_optional_env_variable(key: &str) -> Option<String> {
+    dotenv().ok();
+
+    std::env::var(key).ok()
+}
@@ -1,40 +1,242 @@
-use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock, atomic::{AtomicU64, Ordering}};
+use std::time::Instant;
 use tokio::sync::mpsc;
 
-#[derive(Debug)]
-pub enum ProgressEvent {
-    StageStart(&'static str),
-    StageDone(&'static str),
-    Event {
-        stage: &'static str,
-        event_name: &'static str,
-        tokens: u64,
-    },
+use crate::prelude::*;
+use crate::config::{CONFIG, Budget, BudgetConfig, ModelPrice};
+use crate::drift_analysis::DriftReport;
+use crate::translation_context::TranslationWarning;
+use crate::translation_coverage::CoverageReport;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressMode {
+    Json,
+    Human,
+    None,
+}
+
+impl FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ProgressMode::Json),
+            "human" => Ok(ProgressMode::Human),
+            "none" => Ok(ProgressMode::None),
+            other => Err(format!("Invalid progress mode: {}", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressKind {
+    StageStarted,
+    StageDone,
+    TaskStarted,
+    TaskDone,
+    Usage,
+}
+
+// Task counts are those of the stage so far. The total grows as tasks are discovered,
+// so it is only final once the stage is done.
+#[derive(Clone, Debug, Serialize)]
+pub struct ProgressEvent {
+    #[serde(rename = "type")]
+    pub kind: ProgressKind,
+    pub stage: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<&'static str>,
+    pub tokens: u64,
+    pub elapsed_ms: u64,
+    pub completed_tasks: u64,
+    pub total_tasks: u64,
+}
+
+impl ProgressEvent {
+    pub fn to_human(&self) -> String {
+        let elapsed = format!("[{:>8.1}s]", self.elapsed_ms as f64 / 1000.0);
+
+        match self.kind {
+            ProgressKind::StageStarted => format!("{} {} started", elapsed, self.stage),
+            ProgressKind::StageDone => format!(
+                "{} {} done, {} tasks",
+                elapsed, self.stage, self.completed_tasks
+            ),
+            ProgressKind::Usage => format!(
+                "{} {}: {}/{} tasks, {} tokens",
+                elapsed, self.event.unwrap_or(self.stage), self.completed_tasks, self.total_tasks, self.tokens
+            ),
+            ProgressKind::TaskStarted | ProgressKind::TaskDone => format!(
+                "{} {}: {}/{} tasks",
+                elapsed, self.event.unwrap_or(self.stage), self.completed_tasks, self.total_tasks
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Usage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    fn add(&mut self, other: &Usage) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost += other.cost;
+    }
+
+    fn exceeds(&self, budget: &Budget) -> Option<String> {
+        if let Some(max_tokens) = budget.max_tokens {
+            if self.total_tokens() > max_tokens {
+                return Some(format!("{} tokens used, budget is {}", self.total_tokens(), max_tokens));
+            }
+        }
+
+        if let Some(max_cost) = budget.max_cost {
+            if self.cost > max_cost {
+                return Some(format!("{:.4} spent, budget is {:.4}", self.cost, max_cost));
+            }
+        }
+
+        None
+    }
+
+    fn exhausts(&self, budget: &Budget) -> bool {
+        budget.max_tokens.map_or(false, |max_tokens| self.total_tokens() >= max_tokens) ||
+            budget.max_cost.map_or(false, |max_cost| self.cost >= max_cost)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UsageReport {
+    pub currency: String,
+    pub total: Usage,
+    pub stages: BTreeMap<String, Usage>,
+    pub models: BTreeMap<String, Usage>,
+    // Models without a price, whose cost is counted as zero
+    pub unpriced_models: BTreeSet<String>,
+}
+
+// An inference an offline run found neither in the provider nor was allowed to ask the
+// reasoner for, with the contexts that went without it
+#[derive(Clone, Debug, Serialize)]
+pub struct MissingInference {
+    pub stage: &'static str,
+    pub inference: &'static str,
+    pub key: String,
+    pub contexts: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct TaskCounts {
+    total: AtomicU64,
+    completed: AtomicU64,
 }
 
 #[derive(Clone)]
 pub struct StageContext {
     parent: Arc<ExecutionContext>,
     stage: &'static str,
+    tasks: Arc<TaskCounts>,
+}
+
+// Counts as done when dropped, so that every way out of a task is accounted for
+pub struct StageTask {
+    stage_context: StageContext,
+    event_name: &'static str,
+}
+
+impl Drop for StageTask {
+    fn drop(&mut self) {
+        self.stage_context.tasks.completed.fetch_add(1, Ordering::Relaxed);
+        self.stage_context.send(ProgressKind::TaskDone, Some(self.event_name), 0);
+    }
 }
 
 impl StageContext {
+    pub fn start_task(&self, event_name: &'static str) -> StageTask {
+        self.tasks.total.fetch_add(1, Ordering::Relaxed);
+        self.send(ProgressKind::TaskStarted, Some(event_name), 0);
+
+        StageTask {
+            stage_context: self.clone(),
+            event_name,
+        }
+    }
+
     pub fn record_events(&self, event_name: &'static str, tokens: u64) {
         self.parent.total_tokens.fetch_add(tokens, Ordering::Relaxed);
+        self.send(ProgressKind::Usage, Some(event_name), tokens);
+    }
 
+    fn send(&self, kind: ProgressKind, event: Option<&'static str>, tokens: u64) {
         if let Some(tx) = &self.parent.progress_tx {
-            let _ = tx.send(ProgressEvent::Event {
+            let _ = tx.send(ProgressEvent {
+                kind,
                 stage: self.stage,
-                event_name,
+                event,
                 tokens,
+                elapsed_ms: self.parent.start.elapsed().as_millis() as u64,
+                completed_tasks: self.tasks.completed.load(Ordering::Relaxed),
+                total_tasks: self.tasks.total.load(Ordering::Relaxed),
             });
         }
     }
 
+    // Accounts for a completion and fails once the run or this stage has gone over budget
+    pub fn record_usage(
+        &self,
+        event_name: &'static str,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> Result<(), Errors> {
+        self.parent.record_usage(self.stage, model, input_tokens, output_tokens);
+        self.record_events(event_name, input_tokens + output_tokens);
+
+        self.parent.check_budget(self.stage, Usage::exceeds)
+    }
+
+    // Fails before a request is made when there is nothing left to spend on it
+    pub fn check_budget(&self) -> Result<(), Errors> {
+        self.parent.check_budget(self.stage, |usage, budget| {
+            usage.exhausts(budget).then(|| "budget is used up".to_string())
+        })
+    }
+
+    pub fn record_missing_inference(&self, inference: &'static str, key: String, contexts: &[Arc<Context>]) {
+        log::warn!("No {} in provider for {}, {} contexts affected", inference, key, contexts.len());
+
+        write_lock!(self.parent.missing_inferences).push(MissingInference {
+            stage: self.stage,
+            inference,
+            key,
+            contexts: contexts
+                .iter()
+                .map(|context| describe_context(context))
+                .collect(),
+        });
+    }
+
+    pub fn record_drift_report(&self, drift_report: DriftReport) {
+        write_lock!(self.parent.drift_reports).push(drift_report);
+    }
+
     pub fn finish(self) {
-        if let Some(tx) = &self.parent.progress_tx {
-            let _ = tx.send(ProgressEvent::StageDone(self.stage));
-        }
+        self.send(ProgressKind::StageDone, None, 0);
     }
 }
 
@@ -42,6 +244,12 @@ impl StageContext {
 pub struct ExecutionContext {
     pub total_tokens: AtomicU64,
     pub progress_tx: Option<mpsc::UnboundedSender<ProgressEvent>>,
+    start: Instant,
+    usage: RwLock<UsageReport>,
+    missing_inferences: RwLock<Vec<MissingInference>>,
+    drift_reports: RwLock<Vec<DriftReport>>,
+    translation_warnings: RwLock<Vec<TranslationWarning>>,
+    coverage_reports: RwLock<Vec<CoverageReport>>,
 }
 
 impl ExecutionContext {
@@ -49,24 +257,186 @@ impl ExecutionContext {
         Arc::new(Self {
             total_tokens: AtomicU64::new(0),
             progress_tx: None,
+            start: Instant::now(),
+            usage: RwLock::new(Self::init_usage_report()),
+            missing_inferences: RwLock::new(Vec::new()),
+            drift_reports: RwLock::new(Vec::new()),
+            translation_warnings: RwLock::new(Vec::new()),
+            coverage_reports: RwLock::new(Vec::new()),
         })
     }
 
     pub fn enter_stage(self: &Arc<Self>, stage: &'static str) -> StageContext {
-        if let Some(tx) = &self.progress_tx {
-            let _ = tx.send(ProgressEvent::StageStart(stage));
-        }
-
-        StageContext {
+        let stage_context = StageContext {
             parent: Arc::clone(self),
             stage,
-        }
+            tasks: Arc::new(TaskCounts::default()),
+        };
+
+        stage_context.send(ProgressKind::StageStarted, None, 0);
+
+        stage_context
     }
 
     pub fn with_progress(tx: mpsc::UnboundedSender<ProgressEvent>) -> Arc<Self> {
         Arc::new(Self {
             total_tokens: AtomicU64::new(0),
             progress_tx: Some(tx),
+            start: Instant::now(),
+            usage: RwLock::new(Self::init_usage_report()),
+            missing_inferences: RwLock::new(Vec::new()),
+            drift_reports: RwLock::new(Vec::new()),
+            translation_warnings: RwLock::new(Vec::new()),
+            coverage_reports: RwLock::new(Vec::new()),
         })
     }
+
+    // Keeps what a single document records apart, until it is known to belong to the run
+    pub fn child(&self) -> Arc<Self> {
+        Arc::new(Self {
+            total_tokens: AtomicU64::new(0),
+            progress_tx: self.progress_tx.clone(),
+            start: self.start,
+            usage: RwLock::new(Self::init_usage_report()),
+            missing_inferences: RwLock::new(Vec::new()),
+            drift_reports: RwLock::new(Vec::new()),
+            translation_warnings: RwLock::new(Vec::new()),
+            coverage_reports: RwLock::new(Vec::new()),
+        })
+    }
+
+    pub fn absorb(&self, child: &ExecutionContext) {
+        self.total_tokens.fetch_add(child.total_tokens.load(Ordering::Relaxed), Ordering::Relaxed);
+
+        {
+            let child_usage = child.usage_report();
+            let mut report = write_lock!(self.usage);
+
+            report.total.add(&child_usage.total);
+            for (stage, usage) in &child_usage.stages {
+                report.stages.entry(stage.clone()).or_default().add(usage);
+            }
+            for (model, usage) in &child_usage.models {
+                report.models.entry(model.clone()).or_default().add(usage);
+            }
+            report.unpriced_models.extend(child_usage.unpriced_models);
+        }
+
+        write_lock!(self.missing_inferences).extend(child.missing_inferences());
+        write_lock!(self.drift_reports).extend(child.drift_reports());
+        self.record_translation_warnings(child.translation_warnings());
+        write_lock!(self.coverage_reports).extend(child.coverage_reports());
+    }
+
+    pub fn usage_report(&self) -> UsageReport {
+        read_lock!(self.usage).clone()
+    }
+
+    pub fn missing_inferences(&self) -> Vec<MissingInference> {
+        read_lock!(self.missing_inferences).clone()
+    }
+
+    pub fn drift_reports(&self) -> Vec<DriftReport> {
+        read_lock!(self.drift_reports).clone()
+    }
+
+    pub fn record_translation_warnings(&self, warnings: Vec<TranslationWarning>) {
+        write_lock!(self.translation_warnings).extend(warnings);
+    }
+
+    pub fn translation_warnings(&self) -> Vec<TranslationWarning> {
+        read_lock!(self.translation_warnings).clone()
+    }
+
+    pub fn record_coverage_report(&self, coverage_report: CoverageReport) {
+        write_lock!(self.coverage_reports).push(coverage_report);
+    }
+
+    pub fn coverage_reports(&self) -> Vec<CoverageReport> {
+        read_lock!(self.coverage_reports).clone()
+    }
+
+    fn init_usage_report() -> UsageReport {
+        UsageReport {
+            currency: read_lock!(CONFIG).budget.currency.clone(),
+            ..UsageReport::default()
+        }
+    }
+
+    fn record_usage(&self, stage: &str, model: &str, input_tokens: u64, output_tokens: u64) {
+        let price = get_model_price(&read_lock!(CONFIG).budget, model);
+
+        let usage = Usage {
+            requests: 1,
+            input_tokens,
+            output_tokens,
+            cost: price.as_ref().map_or(0.0, |price| {
+                (input_tokens as f64 * price.input + output_tokens as f64 * price.output) / 1_000_000.0
+            }),
+        };
+
+        let mut report = write_lock!(self.usage);
+
+        if price.is_none() && report.unpriced_models.insert(model.to_string()) {
+            log::warn!("No price configured for model {}, its cost is not counted", model);
+        }
+
+        report.total.add(&usage);
+        report.stages.entry(stage.to_string()).or_default().add(&usage);
+        report.models.entry(model.to_string()).or_default().add(&usage);
+    }
+
+    fn check_budget<F>(&self, stage: &str, is_over: F) -> Result<(), Errors>
+    where
+        F: Fn(&Usage, &Budget) -> Option<String>,
+    {
+        let config = read_lock!(CONFIG);
+        let report = read_lock!(self.usage);
+
+        if let Some(reason) = is_over(&report.total, &config.budget.run) {
+            log::error!("Run budget exceeded: {}", reason);
+            return Err(Errors::BudgetExceeded(format!("Run: {}", reason)));
+        }
+
+        let stage_budget = config.budget.stages
+            .iter()
+            .find(|(name, _)| is_stage_name(name, stage))
+            .map(|(_, budget)| budget);
+
+        if let (Some(budget), Some(usage)) = (stage_budget, report.stages.get(stage)) {
+            if let Some(reason) = is_over(usage, budget) {
+                log::error!("Budget for stage {} exceeded: {}", stage, reason);
+                return Err(Errors::BudgetExceeded(format!("{}: {}", stage, reason)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Descriptions can hold a whole element, the start of it is enough to find it again
+fn describe_context(context: &Context) -> String {
+    let description: String = context.data_node.description.chars().take(120).collect();
+
+    format!("{} {}", context.lineage, description)
 }
+
+// Stages can be named as displayed, e.g. "Field analysis", or as a key, e.g. field_analysis
+fn is_stage_name(name: &str, stage: &str) -> bool {
+    name.replace('_', " ").eq_ignore_ascii_case(stage)
+}
+
+// Model names may or may not carry the provider, e.g. "openai/gpt-5" or "gpt-5"
+fn get_model_price(budget: &BudgetConfig, model: &str) -> Option<ModelPrice> {
+    let unqualified = |name: &str| name.rsplit('/').next().unwrap_or(name).to_string();
+
+    budget.prices.get(model)
+        .or_else(|| {
+            budget.prices
+                .iter()
+                .find(|(name, _)| unqualified(name) == unqualified(model))
+                .map(|(_, price)| price)
+        })
+        .cloned()
+}
+
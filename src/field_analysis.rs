@@ -1,6 +1,6 @@
 use tokio::task::{self, JoinError};
 use std::sync::{Arc, RwLock};
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet};
 
 use crate::prelude::*;
 use crate::basis_field::{BasisField, BasisFieldMetadata};
@@ -14,14 +14,14 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
 ) -> Result<HashMap<ID, Arc<BasisField>>, Errors> {
     log::trace!("In generate_basis_fields");
 
-    stage_context.record_events("Field analysis", 0);
+    let _task = stage_context.start_task("Field analysis");
 
     let meta_context = {
         let lock = read_lock!(normalization_context);
         lock.meta_context.clone().ok_or(Errors::DeficientNormalizationContextError("Meta context not provided in normalization context".to_string()))?
     };
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Fields) {
         let basis_fields: Vec<BasisField> = provider
             .get_basis_fields_by_acyclic_subgraph_hash(&meta_context.acyclic_subgraph_hash).await?
             .into_iter()
@@ -40,13 +40,48 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
         }
     }
 
+    let (mut basis_fields, known_field_names) = if options.regenerates(InferenceStage::Fields) {
+        (Vec::new(), HashSet::new())
+    } else {
+        get_previous_basis_fields(&provider, &normalization_context, &meta_context).await?
+    };
+
+    // Without the fields inferred before every field is kept, the basis nodes found for the
+    // contexts then decide which of them make it into the output
+    if options.offline {
+        let field_names: HashSet<String> = meta_context.contexts
+            .values()
+            .flat_map(|context| context.data_node.fields.keys().cloned())
+            .filter(|name| name != "text" && !known_field_names.contains(name))
+            .collect();
+
+        if !field_names.is_empty() {
+            stage_context.record_missing_inference(
+                "basis fields",
+                format!("{}", meta_context.acyclic_subgraph_hash),
+                &[],
+            );
+        }
+
+        let field_map: HashMap<ID, Arc<BasisField>> = basis_fields.into_iter()
+            .chain(field_names.into_iter().map(|name| new_basis_field(&meta_context.acyclic_subgraph_hash, name)))
+            .chain(std::iter::once(new_basis_field(&meta_context.acyclic_subgraph_hash, "text".to_string())))
+            .map(|basis_field| {
+                let basis_field = Arc::new(basis_field);
+                (basis_field.id.clone(), basis_field)
+            })
+            .collect();
+
+        return Ok(field_map);
+    }
+
     let contexts: Vec<Arc<Context>> = meta_context.contexts.values().cloned().collect();
     log::info!("Number of contexts: {}", contexts.len());
 
     let mut contexts_by_field: HashMap<String, Vec<Arc<Context>>> = HashMap::new();
     for context in contexts {
         for field_name in context.data_node.fields.keys() {
-            if field_name == "text" {
+            if field_name == "text" || known_field_names.contains(field_name) {
                 continue;
             }
 
@@ -66,6 +101,8 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
         let cloned_stage_context = stage_context.clone();
 
         let handle = task::spawn(async move {
+            cloned_stage_context.check_budget()?;
+
             let result = cloned_reasoner.basis_field(
                 cloned_normalization_context,
                 contexts_in_group,
@@ -74,7 +111,12 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
 
             match result {
                 Ok((maybe_basis_field, metadata)) => {
-                    cloned_stage_context.record_events("Field analysis", metadata.tokens.into());
+                    cloned_stage_context.record_usage(
+                        "Field analysis",
+                        &metadata.model,
+                        metadata.input_tokens.into(),
+                        metadata.output_tokens.into(),
+                    )?;
                     Ok(maybe_basis_field)
                 }
                 Err(e) => Err(e),
@@ -86,7 +128,7 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
 
     let results: Vec<Result<Result<Option<BasisField>, Errors>, JoinError>> = futures::future::join_all(handles).await;
 
-    let mut basis_fields: Vec<BasisField> = results.into_iter()
+    let inferred_basis_fields: Vec<BasisField> = results.into_iter()
         .enumerate()
         .filter_map(|(idx, res)| {
             match res {
@@ -104,15 +146,10 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
         })
         .collect::<Result<Vec<BasisField>, Errors>>()?;
 
+    basis_fields.extend(inferred_basis_fields);
+
     // Always ensure 'text' is a basis field
-    basis_fields.push(BasisField {
-        id: ID::new(),
-        acyclic_subgraph_hash: meta_context.acyclic_subgraph_hash.clone(),
-        name: "text".to_string(),
-        metadata: BasisFieldMetadata {
-            prompts: Vec::new()
-        }
-    });
+    basis_fields.push(new_basis_field(&meta_context.acyclic_subgraph_hash, "text".to_string()));
 
     provider.save_basis_fields(
         &meta_context.acyclic_subgraph_hash,
@@ -129,3 +166,52 @@ pub async fn generate_basis_fields<P: Provider, R: Reasoner>(
 
     Ok(field_map)
 }
+
+// Fields the document shares with the last one normalized for its origin keep the basis
+// fields inferred then, even though the markup around them changed the subgraph hash
+async fn get_previous_basis_fields<P: Provider>(
+    provider: &Arc<P>,
+    normalization_context: &Arc<RwLock<NormalizationContext>>,
+    meta_context: &MetaContext,
+) -> Result<(Vec<BasisField>, HashSet<String>), Errors> {
+    let Some(drift_report) = read_lock!(normalization_context).drift_report.clone() else {
+        return Ok((Vec::new(), HashSet::new()));
+    };
+
+    let field_names: HashSet<String> = meta_context.contexts
+        .values()
+        .flat_map(|context| context.data_node.fields.keys().cloned())
+        .filter(|name| name != "text")
+        .collect();
+
+    let basis_fields: Vec<BasisField> = provider
+        .get_basis_fields_by_acyclic_subgraph_hash(&drift_report.previous_acyclic_subgraph_hash).await?
+        .into_iter()
+        .filter(|basis_field| field_names.contains(&basis_field.name))
+        .map(|basis_field| BasisField {
+            acyclic_subgraph_hash: meta_context.acyclic_subgraph_hash.clone(),
+            ..basis_field
+        })
+        .collect();
+
+    let known_field_names: HashSet<String> = drift_report.previous_field_names
+        .iter()
+        .filter(|name| field_names.contains(*name))
+        .cloned()
+        .collect();
+
+    log::info!("Reusing {} basis fields, {} fields known", basis_fields.len(), known_field_names.len());
+
+    Ok((basis_fields, known_field_names))
+}
+
+fn new_basis_field(acyclic_subgraph_hash: &Hash, name: String) -> BasisField {
+    BasisField {
+        id: ID::new(),
+        acyclic_subgraph_hash: acyclic_subgraph_hash.clone(),
+        name,
+        metadata: BasisFieldMetadata {
+            prompts: Vec::new()
+        }
+    }
+}
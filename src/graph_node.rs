@@ -6,6 +6,7 @@ use crate::data_node::DataNode;
 use crate::prelude::*;
 use crate::xpath::{XPath, XPathAxis, XPathSegment, XPathPredicate};
 use crate::basis_node::BasisNode;
+use crate::document::DOCUMENT_ROOT;
 
 pub type Graph = Arc<RwLock<GraphNode>>;
 pub type GraphNodeID = ID;
@@ -577,11 +578,16 @@ impl GraphNode {
             ancestors
         };
 
-        let segments: Vec<XPathSegment> = ancestors.iter().map(|graph| {
+        let segments: Vec<XPathSegment> = ancestors.iter().filter_map(|graph| {
             let lock = read_lock!(graph);
             let context = meta_context.contexts_lookup.get(&lock.id).unwrap();
             let document_node = read_lock!(context.document_node);
 
+            // The leading slash of an xpath already refers to the document node
+            if document_node.get_element_name() == DOCUMENT_ROOT {
+                return None;
+            }
+
             let predicate = {
                 if lock.parents.len() > 0 {
                     let position = lock.index_in_parent_by_type(meta_context).unwrap();
@@ -596,11 +602,11 @@ impl GraphNode {
                 }
             };
 
-            XPathSegment {
+            Some(XPathSegment {
                 axis: XPathAxis::Child,
                 node_test: document_node.get_element_name(),
                 predicates: predicate.into_iter().collect()
-            }
+            })
         }).collect();
 
         let final_context = meta_context.contexts_lookup.get(&self.id).unwrap();
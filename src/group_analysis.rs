@@ -79,12 +79,36 @@ pub fn resolve_context_groups(
         }
     });
 
-    let context_groups = read_lock!(context_groups).clone();
+    let mut context_groups = read_lock!(context_groups).clone();
     let context_to_group = read_lock!(context_to_group).clone();
 
+    for group in context_groups.values_mut() {
+        Context::sort_contexts(group);
+    }
+
     Ok((context_groups, context_to_group))
 }
 
+// Contexts that ended up in no basis group, by acyclic lineage. Only an offline run, which
+// cannot have the reasoner split up a group it has not seen before, leaves any.
+pub fn get_unmatched_contexts(
+    normalization_context: Arc<RwLock<NormalizationContext>>,
+    context_to_group: &HashMap<ContextID, Arc<BasisGroup>>,
+) -> Result<HashMap<Lineage, Vec<Arc<Context>>>, Errors> {
+    let mut unmatched_contexts: HashMap<Lineage, Vec<Arc<Context>>> = HashMap::new();
+
+    for context in get_non_empty_contexts(normalization_context)? {
+        if !context_to_group.contains_key(&context.id) {
+            unmatched_contexts
+                .entry(context.acyclic_lineage.clone())
+                .or_default()
+                .push(context);
+        }
+    }
+
+    Ok(unmatched_contexts)
+}
+
 pub async fn generate_basis_groups<P: Provider, R: Reasoner>(
     provider: Arc<P>,
     reasoner: Arc<R>,
@@ -157,9 +181,9 @@ async fn generate_acyclic_basis_groups<P: Provider, R: Reasoner>(
     options: Options,
     stage_context: StageContext,
 ) -> Result<Vec<BasisGroup>, Errors> {
-    stage_context.record_events("Group analysis", 0);
+    let _task = stage_context.start_task("Group analysis");
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Groups) {
         let basis_groups: Vec<BasisGroup> = provider
             .get_basis_groups_by_acyclic_lineage(&acyclic_lineage).await?
             .into_iter()
@@ -184,15 +208,30 @@ async fn generate_acyclic_basis_groups<P: Provider, R: Reasoner>(
         return Ok(vec![basis_group]);
     }
 
-    let (maybe_basis_group, metadata) = reasoner.basis_group(
-        Arc::clone(&normalization_context),
-        candidate_group.clone(),
-        acyclic_lineage.clone(),
-        None,
-        None,
-    ).await?;
+    // Offline, a group that is not in the provider is treated as not matching, so that the
+    // subgroups it was split into before are looked up instead
+    let (maybe_basis_group, prompt_hash) = if options.offline {
+        (None, None)
+    } else {
+        stage_context.check_budget()?;
+
+        let (maybe_basis_group, metadata) = reasoner.basis_group(
+            Arc::clone(&normalization_context),
+            candidate_group.clone(),
+            acyclic_lineage.clone(),
+            None,
+            None,
+        ).await?;
 
-    stage_context.record_events("Group analysis", metadata.tokens.into());
+        stage_context.record_usage(
+            "Group analysis",
+            &metadata.model,
+            metadata.input_tokens.into(),
+            metadata.output_tokens.into(),
+        )?;
+
+        (maybe_basis_group, Some(metadata.prompt_hash))
+    };
 
     if let Some(basis_group) = maybe_basis_group {
         provider.save_basis_group(&acyclic_lineage, None, None, basis_group.clone()).await?;
@@ -245,7 +284,7 @@ async fn generate_acyclic_basis_groups<P: Provider, R: Reasoner>(
         .into_iter()
         .flatten()
         .map(|mut basis_group| {
-            basis_group.metadata.prompts.push(metadata.prompt_hash.clone());
+            basis_group.metadata.prompts.extend(prompt_hash.clone());
             basis_group
         })
         .collect();
@@ -263,9 +302,9 @@ async fn generate_cyclic_basis_groups<P: Provider, R: Reasoner>(
     options: Options,
     stage_context: StageContext
 ) -> Result<Vec<BasisGroup>, Errors> {
-    stage_context.record_events("Group analysis", 0);
+    let _task = stage_context.start_task("Group analysis");
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Groups) {
         let cached: Vec<BasisGroup> = provider
             .get_basis_groups_by_lineage(&acyclic_lineage, &lineage).await?
             .into_iter()
@@ -290,15 +329,28 @@ async fn generate_cyclic_basis_groups<P: Provider, R: Reasoner>(
         return Ok(vec![basis_group]);
     }
 
-    let (maybe_basis_group, metadata) = reasoner.basis_group(
-        Arc::clone(&normalization_context),
-        candidate_group.clone(),
-        acyclic_lineage.clone(),
-        Some(lineage.clone()),
-        None,
-    ).await?;
+    let (maybe_basis_group, prompt_hash) = if options.offline {
+        (None, None)
+    } else {
+        stage_context.check_budget()?;
+
+        let (maybe_basis_group, metadata) = reasoner.basis_group(
+            Arc::clone(&normalization_context),
+            candidate_group.clone(),
+            acyclic_lineage.clone(),
+            Some(lineage.clone()),
+            None,
+        ).await?;
 
-    stage_context.record_events("Group analysis", metadata.tokens.into());
+        stage_context.record_usage(
+            "Group analysis",
+            &metadata.model,
+            metadata.input_tokens.into(),
+            metadata.output_tokens.into(),
+        )?;
+
+        (maybe_basis_group, Some(metadata.prompt_hash))
+    };
 
     if let Some(basis_group) = maybe_basis_group {
         provider.save_basis_group(
@@ -358,7 +410,7 @@ async fn generate_cyclic_basis_groups<P: Provider, R: Reasoner>(
         .into_iter()
         .flatten()
         .map(|mut basis_group| {
-            basis_group.metadata.prompts.push(metadata.prompt_hash.clone());
+            basis_group.metadata.prompts.extend(prompt_hash.clone());
             basis_group
         })
         .collect();
@@ -379,9 +431,9 @@ async fn generate_indexed_basis_groups<P: Provider, R: Reasoner>(
     options: Options,
     stage_context: StageContext,
 ) -> Result<Vec<BasisGroup>, Errors> {
-    stage_context.record_events("Group analysis", 0);
+    let _task = stage_context.start_task("Group analysis");
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Groups) {
         let cached = provider
             .get_basis_groups_by_indexed_lineage(&acyclic_lineage, &lineage, &indexed_lineage).await?;
         if !cached.is_empty() {
@@ -404,15 +456,28 @@ async fn generate_indexed_basis_groups<P: Provider, R: Reasoner>(
         return Ok(vec![basis_group]);
     }
 
-    let (maybe_basis_group, metadata) = reasoner.basis_group(
-        Arc::clone(&normalization_context),
-        candidate_group.clone(),
-        acyclic_lineage.clone(),
-        Some(lineage.clone()),
-        Some(indexed_lineage.clone())
-    ).await?;
+    let (maybe_basis_group, prompt_hash) = if options.offline {
+        (None, None)
+    } else {
+        stage_context.check_budget()?;
+
+        let (maybe_basis_group, metadata) = reasoner.basis_group(
+            Arc::clone(&normalization_context),
+            candidate_group.clone(),
+            acyclic_lineage.clone(),
+            Some(lineage.clone()),
+            Some(indexed_lineage.clone())
+        ).await?;
+
+        stage_context.record_usage(
+            "Group analysis",
+            &metadata.model,
+            metadata.input_tokens.into(),
+            metadata.output_tokens.into(),
+        )?;
 
-    stage_context.record_events("Group analysis", metadata.tokens.into());
+        (maybe_basis_group, Some(metadata.prompt_hash))
+    };
 
     if let Some(basis_group) = maybe_basis_group {
         provider.save_basis_group(
@@ -472,7 +537,7 @@ async fn generate_indexed_basis_groups<P: Provider, R: Reasoner>(
         .into_iter()
         .flatten()
         .map(|mut basis_group| {
-            basis_group.metadata.prompts.push(metadata.prompt_hash.clone());
+            basis_group.metadata.prompts.extend(prompt_hash.clone());
             basis_group
         })
         .collect();
@@ -0,0 +1,713 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Deep enough for any schema written by hand, and a bound for recursive references
+const MAX_DEPTH: usize = 32;
+
+// A violation of the schema, at the JSON pointer of the offending value
+#[derive(Clone, Debug, Serialize)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+// A JSON Schema (draft 2020-12), given as translation target or describing what a reasoner
+// is to respond with. Only local references are followed, and the keywords about
+// annotations and vocabularies are ignored.
+#[derive(Clone, Debug)]
+pub struct JsonSchema {
+    root: Value,
+}
+
+impl JsonSchema {
+    pub fn parse(value: &str) -> Option<Self> {
+        let root: Value = serde_json::from_str(value).ok()?;
+
+        let is_schema = root.as_object().map_or(false, |map| {
+            ["$schema", "$defs", "properties", "items", "type"]
+                .iter()
+                .any(|keyword| map.contains_key(*keyword))
+        });
+
+        is_schema.then(|| JsonSchema { root })
+    }
+
+    pub fn from_value(root: Value) -> Self {
+        JsonSchema { root }
+    }
+
+    // A document shaped like the schema, whose values are examples where the schema gives
+    // them and otherwise describe the type, format, allowed values and meaning of the value
+    pub fn to_instance(&self) -> Value {
+        self.instance(&self.root, 0)
+    }
+
+    // Translated values are text unless transformation code typed them, so strings are made
+    // into the numbers and booleans the schema asks for where they parse as such
+    pub fn coerce(&self, value: &mut Value) {
+        self.coerce_value(&self.root, value, 0);
+    }
+
+    pub fn validate(&self, value: &Value) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        self.validate_value(&self.root, value, "", &mut violations, 0);
+        violations
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        let mut schema = schema;
+
+        for _ in 0..MAX_DEPTH {
+            match schema.get("$ref").and_then(Value::as_str).and_then(|reference| self.lookup(reference)) {
+                Some(target) => schema = target,
+                None => break,
+            }
+        }
+
+        schema
+    }
+
+    fn lookup(&self, reference: &str) -> Option<&Value> {
+        let pointer = reference.strip_prefix('#')?;
+
+        if pointer.is_empty() {
+            Some(&self.root)
+        } else {
+            self.root.pointer(pointer)
+        }
+    }
+
+    fn instance(&self, schema: &Value, depth: usize) -> Value {
+        let schema = self.resolve(schema);
+
+        if depth > MAX_DEPTH {
+            return Value::Null;
+        }
+
+        if let Some(value) = schema.get("const").or_else(|| first(schema, "examples")).or_else(|| schema.get("default")) {
+            return value.clone();
+        }
+
+        if let Some(subschemas) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for subschema in subschemas {
+                if let Value::Object(map) = self.instance(subschema, depth + 1) {
+                    merged.extend(map);
+                }
+            }
+            if let Value::Object(map) = self.instance_of_type(schema, depth) {
+                merged.extend(map);
+            }
+            return Value::Object(merged);
+        }
+
+        let alternative = ["anyOf", "oneOf"]
+            .iter()
+            .filter_map(|keyword| schema.get(*keyword).and_then(Value::as_array))
+            .flatten()
+            .find(|subschema| get_types(self.resolve(subschema)) != vec!["null"]);
+
+        if let Some(alternative) = alternative {
+            return self.instance(alternative, depth + 1);
+        }
+
+        self.instance_of_type(schema, depth)
+    }
+
+    fn instance_of_type(&self, schema: &Value, depth: usize) -> Value {
+        let types = get_types(schema);
+        let primary = types.iter().find(|name| **name != "null").copied();
+
+        match primary.or_else(|| infer_type(schema)) {
+            Some("object") => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+
+                Value::Object(
+                    properties
+                        .into_iter()
+                        .flatten()
+                        .map(|(name, property)| (name.clone(), self.instance(property, depth + 1)))
+                        .collect()
+                )
+            }
+            Some("array") => {
+                let item = schema
+                    .get("prefixItems")
+                    .and_then(Value::as_array)
+                    .and_then(|items| items.first())
+                    .or_else(|| schema.get("items"));
+
+                Value::Array(item.map(|item| self.instance(item, depth + 1)).into_iter().collect())
+            }
+            other => Value::String(describe(schema, other.unwrap_or("value"))),
+        }
+    }
+
+    fn coerce_value(&self, schema: &Value, value: &mut Value, depth: usize) {
+        let schema = self.resolve(schema);
+
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        for subschema in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.coerce_value(subschema, value, depth + 1);
+        }
+
+        let types = get_types(schema);
+
+        match value {
+            Value::String(text) if !types.is_empty() && !types.contains(&"string") => {
+                let trimmed = text.trim();
+                let coerced = types.iter().find_map(|name| match *name {
+                    "integer" => trimmed.parse::<i64>().ok().map(Value::from),
+                    "number" => trimmed.parse::<f64>().ok().filter(|number| number.is_finite()).map(Value::from),
+                    "boolean" => trimmed.parse::<bool>().ok().map(Value::Bool),
+                    _ => None,
+                });
+
+                if let Some(coerced) = coerced {
+                    *value = coerced;
+                }
+            }
+            Value::Object(map) => {
+                if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                    for (name, property) in properties {
+                        if let Some(value) = map.get_mut(name) {
+                            self.coerce_value(property, value, depth + 1);
+                        }
+                    }
+                }
+            }
+            Value::Array(items) => {
+                let prefix_items = schema.get("prefixItems").and_then(Value::as_array);
+
+                for (index, item) in items.iter_mut().enumerate() {
+                    let item_schema = prefix_items
+                        .and_then(|prefix_items| prefix_items.get(index))
+                        .or_else(|| schema.get("items"));
+
+                    if let Some(item_schema) = item_schema {
+                        self.coerce_value(item_schema, item, depth + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_value(
+        &self,
+        schema: &Value,
+        value: &Value,
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+        depth: usize,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        let mut violate = |message: String| {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message,
+            });
+        };
+
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return violate("no value is allowed here".to_string()),
+            schema => schema,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.lookup(reference) {
+                Some(target) => self.validate_value(target, value, path, violations, depth + 1),
+                None => violations.push(SchemaViolation {
+                    path: display_path(path),
+                    message: format!("unresolvable reference {}", reference),
+                }),
+            }
+        }
+
+        let mut violate = |message: String| {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message,
+            });
+        };
+
+        let types = get_types(schema);
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return violate(format!("expected {}, found {}", types.join(" or "), type_name(value)));
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                violate(format!("{} is not one of {}", value, Value::Array(allowed.clone())));
+            }
+        }
+
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                violate(format!("expected {}, found {}", constant, value));
+            }
+        }
+
+        match value {
+            Value::String(text) => validate_string(schema, text, &mut violate),
+            Value::Number(_) => validate_number(schema, value.as_f64().unwrap_or_default(), &mut violate),
+            _ => {}
+        }
+
+        if let Value::Object(map) = value {
+            self.validate_object(schema, map, path, violations, depth);
+        }
+
+        if let Value::Array(items) = value {
+            self.validate_array(schema, items, path, violations, depth);
+        }
+
+        self.validate_combinations(schema, value, path, violations, depth);
+    }
+
+    fn validate_object(
+        &self,
+        schema: &Value,
+        map: &Map<String, Value>,
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+        depth: usize,
+    ) {
+        for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(name) = required.as_str().filter(|name| !map.contains_key(*name)) {
+                violations.push(SchemaViolation {
+                    path: format!("{}/{}", path, escape(name)),
+                    message: "required property is missing".to_string(),
+                });
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let pattern_properties: Vec<(Regex, &Value)> = schema
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(pattern, subschema)| Regex::new(pattern).ok().map(|regex| (regex, subschema)))
+            .collect();
+
+        for (name, property_value) in map {
+            let property_path = format!("{}/{}", path, escape(name));
+            let mut is_known = false;
+
+            if let Some(property) = properties.and_then(|properties| properties.get(name)) {
+                is_known = true;
+                self.validate_value(property, property_value, &property_path, violations, depth + 1);
+            }
+
+            for (regex, subschema) in &pattern_properties {
+                if regex.is_match(name) {
+                    is_known = true;
+                    self.validate_value(subschema, property_value, &property_path, violations, depth + 1);
+                }
+            }
+
+            if !is_known {
+                match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => violations.push(SchemaViolation {
+                        path: property_path,
+                        message: "property is not allowed".to_string(),
+                    }),
+                    Some(additional) => {
+                        self.validate_value(additional, property_value, &property_path, violations, depth + 1)
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        let count = map.len() as u64;
+        if let Some(minimum) = schema.get("minProperties").and_then(Value::as_u64).filter(|minimum| count < *minimum) {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message: format!("expected at least {} properties, found {}", minimum, count),
+            });
+        }
+        if let Some(maximum) = schema.get("maxProperties").and_then(Value::as_u64).filter(|maximum| count > *maximum) {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message: format!("expected at most {} properties, found {}", maximum, count),
+            });
+        }
+    }
+
+    fn validate_array(
+        &self,
+        schema: &Value,
+        items: &[Value],
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+        depth: usize,
+    ) {
+        let prefix_items = schema.get("prefixItems").and_then(Value::as_array);
+        let prefix_count = prefix_items.map_or(0, Vec::len);
+
+        for (index, item) in items.iter().enumerate() {
+            let item_schema = prefix_items
+                .and_then(|prefix_items| prefix_items.get(index))
+                .or_else(|| schema.get("items").filter(|_| index >= prefix_count));
+
+            if let Some(item_schema) = item_schema {
+                self.validate_value(item_schema, item, &format!("{}/{}", path, index), violations, depth + 1);
+            }
+        }
+
+        let mut violate = |message: String| {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message,
+            });
+        };
+
+        let count = items.len() as u64;
+        if let Some(minimum) = schema.get("minItems").and_then(Value::as_u64).filter(|minimum| count < *minimum) {
+            violate(format!("expected at least {} items, found {}", minimum, count));
+        }
+        if let Some(maximum) = schema.get("maxItems").and_then(Value::as_u64).filter(|maximum| count > *maximum) {
+            violate(format!("expected at most {} items, found {}", maximum, count));
+        }
+
+        if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+            let has_duplicates = items
+                .iter()
+                .enumerate()
+                .any(|(index, item)| items[..index].contains(item));
+
+            if has_duplicates {
+                violate("items are not unique".to_string());
+            }
+        }
+
+        if let Some(contains) = schema.get("contains") {
+            if !items.iter().any(|item| self.is_valid(contains, item, depth)) {
+                violate("no item matches the schema it must contain".to_string());
+            }
+        }
+    }
+
+    fn validate_combinations(
+        &self,
+        schema: &Value,
+        value: &Value,
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+        depth: usize,
+    ) {
+        for subschema in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.validate_value(subschema, value, path, violations, depth + 1);
+        }
+
+        let mut violate = |message: &str| {
+            violations.push(SchemaViolation {
+                path: display_path(path),
+                message: message.to_string(),
+            });
+        };
+
+        if let Some(subschemas) = schema.get("anyOf").and_then(Value::as_array) {
+            if !subschemas.iter().any(|subschema| self.is_valid(subschema, value, depth)) {
+                violate("does not match any of the schemas in anyOf");
+            }
+        }
+
+        if let Some(subschemas) = schema.get("oneOf").and_then(Value::as_array) {
+            let matches = subschemas.iter().filter(|subschema| self.is_valid(subschema, value, depth)).count();
+            if matches != 1 {
+                violate(&format!("matches {} of the schemas in oneOf instead of exactly one", matches));
+            }
+        }
+
+        if let Some(subschema) = schema.get("not") {
+            if self.is_valid(subschema, value, depth) {
+                violate("matches the schema in not");
+            }
+        }
+    }
+
+    fn is_valid(&self, schema: &Value, value: &Value, depth: usize) -> bool {
+        let mut violations = Vec::new();
+        self.validate_value(schema, value, "", &mut violations, depth + 1);
+        violations.is_empty()
+    }
+}
+
+fn validate_string<F: FnMut(String)>(schema: &Value, text: &str, violate: &mut F) {
+    let length = text.chars().count() as u64;
+
+    if let Some(minimum) = schema.get("minLength").and_then(Value::as_u64).filter(|minimum| length < *minimum) {
+        violate(format!("expected at least {} characters, found {}", minimum, length));
+    }
+    if let Some(maximum) = schema.get("maxLength").and_then(Value::as_u64).filter(|maximum| length > *maximum) {
+        violate(format!("expected at most {} characters, found {}", maximum, length));
+    }
+
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        if let Ok(regex) = Regex::new(pattern) {
+            if !regex.is_match(text) {
+                violate(format!("{:?} does not match the pattern {}", text, pattern));
+            }
+        }
+    }
+
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        if !has_format(text, format) {
+            violate(format!("{:?} is not a valid {}", text, format));
+        }
+    }
+}
+
+fn validate_number<F: FnMut(String)>(schema: &Value, number: f64, violate: &mut F) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+    if let Some(minimum) = bound("minimum").filter(|minimum| number < *minimum) {
+        violate(format!("expected at least {}, found {}", minimum, number));
+    }
+    if let Some(maximum) = bound("maximum").filter(|maximum| number > *maximum) {
+        violate(format!("expected at most {}, found {}", maximum, number));
+    }
+    if let Some(minimum) = bound("exclusiveMinimum").filter(|minimum| number <= *minimum) {
+        violate(format!("expected more than {}, found {}", minimum, number));
+    }
+    if let Some(maximum) = bound("exclusiveMaximum").filter(|maximum| number >= *maximum) {
+        violate(format!("expected less than {}, found {}", maximum, number));
+    }
+    if let Some(divisor) = bound("multipleOf").filter(|divisor| *divisor > 0.0) {
+        let quotient = number / divisor;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            violate(format!("{} is not a multiple of {}", number, divisor));
+        }
+    }
+}
+
+// Formats not listed here are taken as annotations only
+fn has_format(text: &str, format: &str) -> bool {
+    match format {
+        "date-time" => chrono::DateTime::parse_from_rfc3339(text).is_ok(),
+        "date" => chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+        "time" => chrono::NaiveTime::parse_from_str(text.trim_end_matches('Z'), "%H:%M:%S%.f").is_ok(),
+        "email" => text.split_once('@').map_or(false, |(local, domain)| !local.is_empty() && domain.contains('.')),
+        "uri" | "iri" => url::Url::parse(text).is_ok(),
+        "uuid" => uuid::Uuid::parse_str(text).is_ok(),
+        "ipv4" => text.parse::<std::net::Ipv4Addr>().is_ok(),
+        "ipv6" => text.parse::<std::net::Ipv6Addr>().is_ok(),
+        "regex" => Regex::new(text).is_ok(),
+        _ => true,
+    }
+}
+
+fn get_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn infer_type(schema: &Value) -> Option<&'static str> {
+    if schema.get("properties").is_some() {
+        Some("object")
+    } else if schema.get("items").is_some() || schema.get("prefixItems").is_some() {
+        Some("array")
+    } else {
+        None
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().map_or(false, |number| number.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn first<'a>(schema: &'a Value, keyword: &str) -> Option<&'a Value> {
+    schema.get(keyword).and_then(Value::as_array).and_then(|values| values.first())
+}
+
+// E.g. "<string, format: date-time> When the entry was published"
+fn describe(schema: &Value, type_name: &str) -> String {
+    let mut details = vec![type_name.to_string()];
+
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        details.push(format!("format: {}", format));
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        let allowed: Vec<String> = allowed
+            .iter()
+            .map(|value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            })
+            .collect();
+        details.push(format!("one of: {}", allowed.join(" | ")));
+    }
+
+    let description = schema
+        .get("description")
+        .or_else(|| schema.get("title"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    format!("<{}> {}", details.join(", "), description).trim().to_string()
+}
+
+// Paths are JSON pointers, except that the root is shown as a slash rather than nothing
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn violations(schema: Value, value: Value) -> Vec<(String, String)> {
+        JsonSchema::from_value(schema)
+            .validate(&value)
+            .into_iter()
+            .map(|violation| (violation.path, violation.message))
+            .collect()
+    }
+
+    fn paths(schema: Value, value: Value) -> Vec<String> {
+        violations(schema, value).into_iter().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn follows_references_into_defs() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "author": { "$ref": "#/$defs/Person" } },
+            "$defs": {
+                "Person": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } },
+                    "required": ["name"]
+                }
+            }
+        });
+
+        assert!(paths(schema.clone(), json!({ "author": { "name": "Ada" } })).is_empty());
+        assert_eq!(paths(schema.clone(), json!({ "author": { "name": 1 } })), vec!["/author/name"]);
+        assert_eq!(paths(schema, json!({ "author": {} })), vec!["/author/name"]);
+    }
+
+    #[test]
+    fn reports_unresolvable_references() {
+        let found = violations(json!({ "$ref": "#/$defs/Missing" }), json!(1));
+
+        assert_eq!(found, vec![("/".to_string(), "unresolvable reference #/$defs/Missing".to_string())]);
+    }
+
+    #[test]
+    fn requires_every_schema_in_all_of() {
+        let schema = json!({
+            "allOf": [
+                { "type": "object", "required": ["id"] },
+                { "type": "object", "required": ["title"] }
+            ]
+        });
+
+        assert!(paths(schema.clone(), json!({ "id": 1, "title": "Notes" })).is_empty());
+        assert_eq!(paths(schema, json!({ "id": 1 })), vec!["/title"]);
+    }
+
+    #[test]
+    fn requires_exactly_one_schema_in_one_of() {
+        let schema = json!({
+            "oneOf": [
+                { "type": "integer" },
+                { "type": "number" }
+            ]
+        });
+
+        assert!(paths(schema.clone(), json!(1.5)).is_empty());
+        assert_eq!(
+            violations(schema, json!(2)),
+            vec![("/".to_string(), "matches 2 of the schemas in oneOf instead of exactly one".to_string())],
+        );
+    }
+
+    #[test]
+    fn requires_some_schema_in_any_of() {
+        let schema = json!({ "anyOf": [{ "type": "integer" }, { "type": "number" }] });
+
+        assert!(paths(schema.clone(), json!(2)).is_empty());
+        assert_eq!(paths(schema, json!("two")), vec!["/"]);
+    }
+
+    #[test]
+    fn validates_prefix_items_before_items() {
+        let schema = json!({
+            "type": "array",
+            "prefixItems": [{ "type": "string" }, { "type": "integer" }],
+            "items": { "type": "boolean" }
+        });
+
+        assert!(paths(schema.clone(), json!(["a", 1, true, false])).is_empty());
+        assert_eq!(paths(schema, json!([1, "a", "b"])), vec!["/0", "/1", "/2"]);
+    }
+
+    #[test]
+    fn points_required_violations_at_the_missing_property() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": { "type": "object", "required": ["a/b", "c~d"] }
+                }
+            },
+            "required": ["items", "total"]
+        });
+
+        assert_eq!(
+            violations(schema, json!({ "items": [{ "a/b": 1 }, {}] })),
+            vec![
+                ("/total".to_string(), "required property is missing".to_string()),
+                ("/items/0/c~0d".to_string(), "required property is missing".to_string()),
+                ("/items/1/a~1b".to_string(), "required property is missing".to_string()),
+                ("/items/1/c~0d".to_string(), "required property is missing".to_string()),
+            ],
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod classification;
 pub mod basis_network;
+pub mod batch;
 pub mod basis_node;
 #[cfg(feature = "caching")]
 pub mod cache;
@@ -15,8 +16,8 @@ pub mod graph_node;
 pub mod hash;
 pub mod id;
 pub mod json_node;
+pub mod json_schema;
 pub mod lineage;
-pub mod llm;
 pub mod macros;
 pub mod normalization_context;
 pub mod metadata;
@@ -31,6 +32,7 @@ pub mod prelude;
 pub mod provider;
 pub mod query;
 pub mod transformation;
+pub mod transformation_runtime;
 pub mod translation;
 pub mod types;
 #[allow(dead_code)]
@@ -44,11 +46,15 @@ pub mod basis_group;
 pub mod reports;
 pub mod basis_field;
 pub mod translation_context;
+pub mod translation_coverage;
 pub mod translation_node;
 pub mod meta_context;
 pub mod translation_network;
 pub mod reasoner;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod prompt_registry;
 pub mod field_analysis;
 pub mod group_analysis;
+pub mod drift_analysis;
 pub mod normal_meta_context;
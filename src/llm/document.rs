@@ -1,188 +0,0 @@
-use openrouter_rs::{
-    api::chat::*,
-    types::{ResponseFormat, Role},
-    OpenRouterClient,
-};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-
-use crate::prelude::*;
-use crate::environment::get_env_variable;
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SchemaToInstanceResponseMetadata {
-    pub tokens: u64,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SchemaToInstanceResponse {
-    pub instance_document: String,
-}
-
-pub struct Document;
-
-impl Document {
-    pub async fn schema_to_instance(document: String) -> Result<(SchemaToInstanceResponse, SchemaToInstanceResponseMetadata), Errors> {
-        log::trace!("In schema_to_instance");
-
-        let system_prompt = r##"
-Your task is to convert a schema to an instance of that schema with several examples capturing everything the schema permits.
-        "##;
-
-        let user_prompt = format!(r##"
-[Schema]
-{}
-"##, document);
-
-        log::debug!("╔═══════════════════════════════════════════════════════════════╗");
-        log::debug!("║                                                               ║");
-        log::debug!("║              SCHEMA TO INSTANCE - LLM REQUEST                   ║");
-        log::debug!("║                                                               ║");
-        log::debug!("╚═══════════════════════════════════════════════════════════════╝");
-        log::debug!("");
-        log::debug!("┌─── SYSTEM PROMPT ─────────────────────────────────────────────┐");
-        log::debug!("{}", system_prompt);
-        log::debug!("└───────────────────────────────────────────────────────────────┘");
-        log::debug!("");
-        log::debug!("┌─── USER PROMPT ───────────────────────────────────────────────┐");
-        log::debug!("{}", user_prompt);
-        log::debug!("└───────────────────────────────────────────────────────────────┘");
-        log::debug!("");
-
-        let client = Self::build_client();
-
-        let response_format = ResponseFormat::json_schema(
-            "schema_to_instance",
-            true,
-            json!({
-                "type": "object",
-                "properties": {
-                    "instance_document": {
-                        "type": "string",
-                        "description": "A document that is an instance of a schema"
-                    }
-                },
-                "required": ["instance_document"],
-                "additionalProperties": false
-            }),
-        );
-
-        let request = ChatCompletionRequest::builder()
-            .model("gpt-5-mini")
-            .messages(vec![
-                Message::new(Role::System, system_prompt),
-                Message::new(Role::User, user_prompt),
-            ])
-            .response_format(response_format)
-            .build()
-            .expect("Could not create llm request");
-
-        match client.send_chat_completion(&request).await {
-            Ok(response) => {
-                log::debug!("┌─── RAW LLM RESPONSE ──────────────────────────────────────────┐");
-                log::debug!("{:?}", response);
-                log::debug!("└───────────────────────────────────────────────────────────────┘");
-                log::debug!("");
-
-                if let Some(content) = response.choices[0].content() {
-                    log::debug!(
-                        "┌─── LLM RESPONSE CONTENT ──────────────────────────────────────┐"
-                    );
-                    log::debug!("{}", content);
-                    log::debug!(
-                        "└───────────────────────────────────────────────────────────────┘"
-                    );
-                    log::debug!("");
-
-                    let instance_to_schema_response = {
-                        match serde_json::from_str::<SchemaToInstanceResponse>(content) {
-                            Ok(parsed_response) => {
-                                log::debug!(
-                                    "┌─── PARSED RESPONSE ───────────────────────────────────────────┐"
-                                );
-                                log::debug!("{:?}", parsed_response);
-                                log::debug!(
-                                    "└───────────────────────────────────────────────────────────────┘"
-                                );
-                                log::debug!("");
-                                log::debug!(
-                                    "╔═══════════════════════════════════════════════════════════════╗"
-                                );
-                                log::debug!(
-                                    "║                                                               ║"
-                                );
-                                log::debug!(
-                                    "║            CATEGORIZE DOCUMENT - REQUEST COMPLETE             ║"
-                                );
-                                log::debug!(
-                                    "║                                                               ║"
-                                );
-                                log::debug!(
-                                    "╚═══════════════════════════════════════════════════════════════╝"
-                                );
-
-                                Ok(parsed_response)
-                            }
-                            Err(e) => {
-                                log::error!(
-                                    "╔═══════════════════════════════════════════════════════════════╗"
-                                );
-                                log::error!(
-                                    "║                    PARSE ERROR                                ║"
-                                );
-                                log::error!(
-                                    "╚═══════════════════════════════════════════════════════════════╝"
-                                );
-                                log::error!("Failed to parse LLM response: {}", e);
-                                Err(Errors::UnexpectedError("Failed to parse LLM response".to_string()))
-
-                            }
-                        }
-                    }?;
-
-                    let metadata = {
-                        if let Some(usage) = response.usage {
-                            SchemaToInstanceResponseMetadata {
-                                tokens: usage.total_tokens.clone() as u64,
-                            }
-                        } else {
-                            SchemaToInstanceResponseMetadata {
-                                tokens: 0,
-                            }
-                        }
-                    };
-
-                    Ok((instance_to_schema_response, metadata))
-                } else {
-                    log::error!(
-                        "╔═══════════════════════════════════════════════════════════════╗"
-                    );
-                    log::error!(
-                        "║                    NO CONTENT ERROR                           ║"
-                    );
-                    log::error!(
-                        "╚═══════════════════════════════════════════════════════════════╝"
-                    );
-                    log::error!("No content in LLM response");
-                    Err(Errors::UnexpectedError("No content in LLM response".to_string()))
-
-                }
-            }
-            Err(e) => {
-                log::error!("╔═══════════════════════════════════════════════════════════════╗");
-                log::error!("║                    REQUEST ERROR                              ║");
-                log::error!("╚═══════════════════════════════════════════════════════════════╝");
-                log::error!("Failed to get response from OpenRouter: {}", e);
-                Err(Errors::UnexpectedError("Failed to get response from OpenRouter".to_string()))
-            }
-        }
-    }
-
-    fn build_client() -> OpenRouterClient {
-        let api_key = get_env_variable("OPENROUTER_API_KEY");
-        OpenRouterClient::builder()
-            .api_key(api_key)
-            .build()
-            .expect("Could not build open router client")
-    }
-}
@@ -1,5 +1,6 @@
 mod classification;
 mod basis_network;
+mod batch;
 mod basis_node;
 #[cfg(feature = "caching")]
 mod cache;
@@ -15,8 +16,8 @@ mod graph_node;
 mod hash;
 mod id;
 mod json_node;
+mod json_schema;
 mod lineage;
-mod llm;
 mod macros;
 mod normalization_context;
 mod metadata;
@@ -31,6 +32,7 @@ mod prelude;
 mod provider;
 mod query;
 mod transformation;
+mod transformation_runtime;
 mod translation;
 mod types;
 #[allow(dead_code)]
@@ -44,13 +46,17 @@ mod basis_group;
 mod reports;
 mod basis_field;
 mod translation_context;
+mod translation_coverage;
 mod translation_node;
 mod meta_context;
 mod translation_network;
 mod reasoner;
+#[cfg(feature = "server")]
+mod server;
 mod prompt_registry;
 mod field_analysis;
 mod group_analysis;
+mod drift_analysis;
 mod normal_meta_context;
 
 use crate::entrypoint::run;
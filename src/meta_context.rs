@@ -23,6 +23,23 @@ impl MetaContext {
         Ok(spatial_context)
     }
 
+    pub fn get_document_order(&self) -> HashMap<GraphNodeID, usize> {
+        let mut document_order: HashMap<GraphNodeID, usize> = HashMap::new();
+        let mut stack: Vec<Graph> = vec![Arc::clone(&self.graph_root)];
+
+        while let Some(node) = stack.pop() {
+            let lock = read_lock!(node);
+            let position = document_order.len();
+            document_order.insert(lock.id.clone(), position);
+
+            for child in lock.children.iter().rev() {
+                stack.push(Arc::clone(child));
+            }
+        }
+
+        document_order
+    }
+
     fn generate_spatial_context(&self) -> Result<String, Errors> {
         let max_lineages: usize = 1;
         let render_ids = get_render_ids(
@@ -41,6 +58,7 @@ impl MetaContext {
                 wrap_text: None,
                 exclude_nulls: None,
                 custom_delimiter: None,
+                flatten: None,
             },
             Some(&render_ids),
         )?;
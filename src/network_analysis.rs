@@ -9,7 +9,6 @@ use crate::basis_network::{BasisNetwork, BasisNetworkMetadata, NodeRelationship,
 use crate::basis_graph::BasisGraph;
 use crate::config::CONFIG;
 use crate::graph_node::Graph;
-use crate::llm::LLM;
 use crate::normalization_context::NormalizationContext;
 use crate::prelude::*;
 use crate::provider::Provider;
@@ -142,6 +141,12 @@ fn resolve_basis_networks(
 
         let mut basis_network_nodes: Vec<Arc<BasisNode>> = Vec::new();
 
+        // A basis node without relationships still forms a network on its own
+        if current_relationships.is_empty() {
+            basis_network_nodes.push(basis_node.clone());
+            placed.insert(basis_node.lineage.clone());
+        }
+
         for relationship in &current_relationships {
             let lineages = vec![relationship.left_basis_lineage.clone(), relationship.right_basis_lineage.clone()];
 
@@ -187,7 +192,7 @@ async fn generate_node_relationship<P: Provider, R: Reasoner>(
     right: Arc<BasisNode>,
 ) -> Result<NodeRelationship, Errors> {
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Relationships) {
         if let Some(node_relationship) = provider.get_node_relationship(
             &left.lineage,
             &right.lineage,
@@ -196,7 +201,25 @@ async fn generate_node_relationship<P: Provider, R: Reasoner>(
         }
     }
 
-    stage_context.record_events("Node relationship", 0);
+    // Nodes without a known relationship form networks of their own
+    if options.offline {
+        stage_context.record_missing_inference(
+            "node relationship",
+            format!("{} <-> {}", left.lineage, right.lineage),
+            &[],
+        );
+
+        return Ok(NodeRelationship {
+            id: ID::new(),
+            left_basis_lineage: left.lineage.clone(),
+            right_basis_lineage: right.lineage.clone(),
+            relationship_type: NodeRelationshipType::NoRelationship,
+        });
+    }
+
+    let _task = stage_context.start_task("Node relationship");
+
+    stage_context.check_budget()?;
 
     let (relationship, metadata) = reasoner.node_relationship(
         Arc::clone(&normalization_context),
@@ -204,7 +227,12 @@ async fn generate_node_relationship<P: Provider, R: Reasoner>(
         right.clone(),
     ).await?;
 
-    stage_context.record_events("Node relationship", metadata.tokens.into());
+    stage_context.record_usage(
+        "Node relationship",
+        &metadata.model,
+        metadata.input_tokens.into(),
+        metadata.output_tokens.into(),
+    )?;
 
     provider
         .save_node_relationship(
@@ -339,9 +367,11 @@ async fn get_translation_network<P: Provider, R: Reasoner>(
     options: &Options,
     stage_context: &StageContext
 ) -> Result<Option<TranslationNetwork>, Errors> {
+    let _task = stage_context.start_task("Network translation");
+
     let (input_context, target_context) = context_pair;
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Translations) {
         if let Some(maybe_translation_network) = provider.get_translation_network_by_lineages(
             &input_context.lineage,
             &target_context.lineage,
@@ -350,12 +380,30 @@ async fn get_translation_network<P: Provider, R: Reasoner>(
         }
     }
 
-    let (transformation, (tokens,)) = LLM::get_network_translation(
+    if options.offline {
+        stage_context.record_missing_inference(
+            "translation network",
+            format!("{} -> {}", input_context.lineage, target_context.lineage),
+            &[input_context],
+        );
+        return Ok(None);
+    }
+
+    stage_context.check_budget()?;
+
+    let (transformation, metadata) = reasoner.network_translation(
         Arc::clone(&translation_context),
         Arc::clone(&input_context),
         Arc::clone(&target_context),
     ).await?;
 
+    stage_context.record_usage(
+        "Network translation",
+        &metadata.model,
+        metadata.input_tokens.into(),
+        metadata.output_tokens.into(),
+    )?;
+
     if let Some(transformation) = transformation {
         let translation_network = TranslationNetwork {
             id: ID::new(),
@@ -389,7 +437,7 @@ pub async fn get_classification<P: Provider, R: Reasoner>(
 ) -> Result<Arc<Classification>, Errors> {
     log::trace!("In get_classification");
 
-    stage_context.record_events("Document classification", 0);
+    let _task = stage_context.start_task("Document classification");
 
     let meta_context = {
         let lock = read_lock!(normalization_context);
@@ -397,7 +445,7 @@ pub async fn get_classification<P: Provider, R: Reasoner>(
     };
     let lineage = read_lock!(meta_context.graph_root).lineage.clone();
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Classification) {
         if let Some(classification) = provider.get_classification_by_lineage(&lineage).await? {
             log::info!("Provider has supplied classification");
 
@@ -405,6 +453,15 @@ pub async fn get_classification<P: Provider, R: Reasoner>(
         };
     }
 
+    // The normalized graph is named and described by the classification, there is no
+    // sensible default for it
+    if options.offline {
+        stage_context.record_missing_inference("classification", lineage.to_string(), &[]);
+        return Err(Errors::InferenceNotAvailable(format!("No classification for document lineage {}", lineage)));
+    }
+
+    stage_context.check_budget()?;
+
     let (classification, metadata) = reasoner.classify(
         Arc::clone(&meta_context)
     ).await?;
@@ -413,7 +470,12 @@ pub async fn get_classification<P: Provider, R: Reasoner>(
         .save_classification(&lineage, classification.clone())
         .await?;
 
-    stage_context.record_events("Document classification", metadata.tokens.into());
+    stage_context.record_usage(
+        "Document classification",
+        &metadata.model,
+        metadata.input_tokens.into(),
+        metadata.output_tokens.into(),
+    )?;
 
     Ok(Arc::new(classification))
 }
@@ -9,7 +9,6 @@ use crate::basis_field::BasisField;
 use crate::basis_group::BasisGroup;
 use crate::basis_node::BasisNode;
 use crate::config::CONFIG;
-use crate::llm::{LLM};
 use crate::normalization_context::NormalizationContext;
 use crate::translation_context::TranslationContext;
 use crate::prelude::*;
@@ -139,9 +138,11 @@ async fn get_translation_node<P: Provider, R: Reasoner>(
     options: &Options,
     stage_context: &StageContext,
 ) -> Result<Option<TranslationNode>, Errors> {
+    let _task = stage_context.start_task("Node translation");
+
     let (input_context, target_context) = context_pair;
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Translations) {
         if let Some(maybe_translation_node) = provider.get_translation_node_by_lineages(
             &input_context.lineage,
             &target_context.lineage,
@@ -150,12 +151,30 @@ async fn get_translation_node<P: Provider, R: Reasoner>(
         }
     }
 
-    let (transformations, (tokens,)) = LLM::get_node_translation(
+    if options.offline {
+        stage_context.record_missing_inference(
+            "translation node",
+            format!("{} -> {}", input_context.lineage, target_context.lineage),
+            &[input_context],
+        );
+        return Ok(None);
+    }
+
+    stage_context.check_budget()?;
+
+    let (transformations, metadata) = reasoner.node_translation(
         Arc::clone(&translation_context),
         Arc::clone(&input_context),
         Arc::clone(&target_context)
     ).await?;
 
+    stage_context.record_usage(
+        "Node translation",
+        &metadata.model,
+        metadata.input_tokens.into(),
+        metadata.output_tokens.into(),
+    )?;
+
     if transformations.is_empty() {
         provider.save_translation_node(
             (input_context.lineage.clone(), target_context.lineage.clone()),
@@ -217,7 +236,7 @@ pub async fn generate_basis_nodes<P: Provider, R: Reasoner>(
         let cloned_options = options.clone();
 
         let handle = task::spawn(async move {
-            let basis_node = generate_basis_node(
+            let maybe_basis_node = generate_basis_node(
                 cloned_provider,
                 cloned_reasoner,
                 cloned_normalization_context,
@@ -228,11 +247,11 @@ pub async fn generate_basis_nodes<P: Provider, R: Reasoner>(
             )
             .await?;
 
-            Ok::<_, Errors>((
+            Ok::<_, Errors>(maybe_basis_node.map(|basis_node| (
                 basis_node.id.clone(),
                 Arc::new(basis_node),
                 context_group.clone(),
-            ))
+            )))
         });
 
         handles.push(handle);
@@ -244,7 +263,9 @@ pub async fn generate_basis_nodes<P: Provider, R: Reasoner>(
     let mut basis_node_to_context_group = HashMap::new();
 
     for result in results {
-        let (id, basis_node, ctx_group) = result?;
+        let Some((id, basis_node, ctx_group)) = result? else {
+            continue;
+        };
         basis_nodes.insert(id.clone(), basis_node);
         basis_node_to_context_group.insert(id, ctx_group);
     }
@@ -260,28 +281,41 @@ async fn generate_basis_node<P: Provider, R: Reasoner>(
     context_group: Vec<Arc<Context>>,
     options: &Options,
     stage_context: &StageContext,
-) -> Result<BasisNode, Errors> {
-    stage_context.record_events("Node analysis", 0);
+) -> Result<Option<BasisNode>, Errors> {
+    let _task = stage_context.start_task("Node analysis");
 
     let basis_lineage: BasisLineage = basis_group.get_basis_lineage();
 
-    if !options.regenerate {
+    if !options.regenerates(InferenceStage::Nodes) {
         if let Some(basis_node) = provider.get_basis_node_by_lineage(&basis_lineage).await? {
-            return Ok(basis_node);
+            return Ok(Some(basis_node));
         }
     }
 
+    // The contexts of the group are left out of the normalized graph
+    if options.offline {
+        stage_context.record_missing_inference("basis node", basis_lineage.to_string(), &context_group);
+        return Ok(None);
+    }
+
+    stage_context.check_budget()?;
+
     let (basis_node, metadata) = reasoner.basis_node(
         Arc::clone(&normalization_context),
         basis_group,
         context_group,
     ).await?;
 
-    stage_context.record_events("Node analysis", metadata.tokens.into());
+    stage_context.record_usage(
+        "Node analysis",
+        &metadata.model,
+        metadata.input_tokens.into(),
+        metadata.output_tokens.into(),
+    )?;
 
     provider
         .save_basis_node(&basis_lineage, basis_node.clone())
         .await?;
     
-    Ok(basis_node)
+    Ok(Some(basis_node))
 }
@@ -5,8 +5,10 @@ use std::time::Instant;
 use crate::document::{Document, DocumentType};
 use crate::document_format::DocumentFormat;
 use crate::normalization_context::NormalizationContext;
+use crate::options::EmbeddedDataPolicy;
+use crate::drift_analysis::{detect_drift, record_structure, index_origin};
 use crate::field_analysis::generate_basis_fields;
-use crate::group_analysis::{generate_basis_groups, resolve_context_groups};
+use crate::group_analysis::{generate_basis_groups, resolve_context_groups, get_unmatched_contexts};
 use crate::node_analysis::{generate_basis_nodes};
 use crate::network_analysis::{
     get_classification,
@@ -42,17 +44,26 @@ pub async fn normalize<P: Provider, R: Reasoner>(
     let start = Instant::now();
     let stage = execution_context.enter_stage("Initialization");
 
-    let normalization_context = init_normalization_context(
+    let normalization_context = init_normalization_context(document)?;
+
+    stage.finish();
+    let elapsed = start.elapsed();
+    log::info!("init_normalization_context: {:.2?}", elapsed);
+
+    let start = Instant::now();
+    let stage = execution_context.enter_stage("Drift analysis");
+
+    let options = &detect_drift(
         Arc::clone(&provider),
-        Arc::clone(&reasoner),
-        document,
+        Arc::clone(&normalization_context),
         options,
+        &stage,
     )
     .await?;
 
     stage.finish();
     let elapsed = start.elapsed();
-    log::info!("init_normalization_context: {:.2?}", elapsed);
+    log::info!("detect_drift: {:.2?}", elapsed);
 
     let start = Instant::now();
     let stage = execution_context.enter_stage("Document classification");
@@ -126,6 +137,14 @@ pub async fn normalize<P: Provider, R: Reasoner>(
         Arc::clone(&normalization_context)
     )?;
 
+    if options.offline {
+        let unmatched_contexts = get_unmatched_contexts(Arc::clone(&normalization_context), &context_to_group)?;
+
+        for (acyclic_lineage, contexts) in unmatched_contexts {
+            stage.record_missing_inference("basis group", acyclic_lineage.to_string(), &contexts);
+        }
+    }
+
     {
         let mut lock = write_lock!(normalization_context);
         lock.update_context_groups(context_groups, context_to_group);
@@ -198,7 +217,6 @@ pub async fn normalize<P: Provider, R: Reasoner>(
     }
 
     stage.finish();
-    panic!();
 
     let start = Instant::now();
     let stage = execution_context.enter_stage("Building normalized graph");
@@ -219,24 +237,103 @@ pub async fn normalize<P: Provider, R: Reasoner>(
 
     stage.finish();
 
+    record_structure(Arc::clone(&provider), Arc::clone(&normalization_context), options).await?;
+    index_origin(Arc::clone(&provider), Arc::clone(&normalization_context), options).await?;
+
+    let extracted_documents: Vec<(DocumentVersion, Arc<Document>)> = {
+        let lock = read_lock!(normalization_context);
+        let mut documents: Vec<(DocumentVersion, Arc<Document>)> = lock.document_versions
+            .iter()
+            .filter(|(version, _)| matches!(version, DocumentVersion::ExtractedDocument(_)))
+            .map(|(version, document)| (version.clone(), Arc::clone(document)))
+            .collect();
+        documents.sort_by_key(|(version, _)| match version {
+            DocumentVersion::ExtractedDocument(index) => *index,
+            _ => 0,
+        });
+        documents
+    };
+
+    // Embedded documents share the origin of the page, so their structure is left out of
+    // the one recorded for it
+    let extracted_options = Options {
+        drift_policy: None,
+        ..options.clone()
+    };
+
+    let mut replaced_records = false;
+
+    for (version, document) in extracted_documents {
+        log::info!("Normalizing embedded document {:?}", version);
+
+        let start = Instant::now();
+
+        // Structured data is a bonus on top of the page, so failing to normalize it is not fatal
+        let extracted_context = match Box::pin(normalize(
+            Arc::clone(&provider),
+            Arc::clone(&reasoner),
+            (*document).clone(),
+            &extracted_options,
+            Arc::clone(&execution_context),
+        )).await {
+            Ok(extracted_context) => extracted_context,
+            Err(e @ Errors::BudgetExceeded(_)) => return Err(e),
+            Err(e) => {
+                log::warn!("Could not normalize embedded document {:?}: {:?}", version, e);
+                continue;
+            }
+        };
+
+        {
+            let mut lock = write_lock!(normalization_context);
+
+            if options.embedded_data == EmbeddedDataPolicy::Prefer && !replaced_records {
+                log::info!("Replacing records of the markup with the ones of the structured data");
+                lock.clear_normalized_records()?;
+                replaced_records = true;
+            }
+
+            lock.merge_normalized_graph(&read_lock!(extracted_context))?;
+        }
+
+        let elapsed = start.elapsed();
+        log::info!("normalize embedded document: {:.2?}", elapsed);
+    }
+
     Ok(normalization_context)
 }
 
-async fn normalize_html<P: Provider, R: Reasoner>(
-    provider: Arc<P>,
-    reasoner: Arc<R>,
+// Every document type but HTML is traversed into a meta context and nothing else
+fn load_meta_context(
     document: Document,
-    options: &Options,
-    normalization_context: Arc<RwLock<NormalizationContext>>,
+    normalization_context: &Arc<RwLock<NormalizationContext>>,
 ) -> Result<(), Errors> {
-    let mut document = document;
+    log::info!("Traversing {} document", document.document_type);
+    let meta_context = document.to_meta_context()?;
+
+    {
+        let mut lock = write_lock!(normalization_context);
+        lock.update_meta_context(meta_context);
+    }
+
+    Ok(())
+}
 
+// Pages also carry documents of their own, e.g. JSON-LD, which are normalized after the page
+fn normalize_html(
+    document: Document,
+    normalization_context: &Arc<RwLock<NormalizationContext>>,
+) -> Result<(), Errors> {
     log::info!("Traversing document");
-    let meta_context = document.to_meta_context()?;
+    let (meta_context, extracted_documents) = document.to_meta_context_with_documents()?;
 
     {
         let mut lock = write_lock!(normalization_context);
         lock.update_meta_context(meta_context);
+
+        for (index, extracted_document) in extracted_documents.into_iter().enumerate() {
+            lock.add_document_version(DocumentVersion::ExtractedDocument(index), extracted_document);
+        }
     }
 
     Ok(())
@@ -416,12 +513,7 @@ pub async fn normalize_file_to_string<P: Provider, R: Reasoner>(
     Ok(document.to_string())
 }
 
-async fn init_normalization_context<P: Provider, R: Reasoner>(
-    provider: Arc<P>,
-    reasoner: Arc<R>,
-    document: Document,
-    options: &Options,
-) -> Result<Arc<RwLock<NormalizationContext>>, Errors> {
+fn init_normalization_context(document: Document) -> Result<Arc<RwLock<NormalizationContext>>, Errors> {
     log::trace!("In init_normalization_context");
 
     let normalization_context = Arc::new(RwLock::new(NormalizationContext::new()));
@@ -432,37 +524,21 @@ async fn init_normalization_context<P: Provider, R: Reasoner>(
     }
 
     match document.document_type {
-        DocumentType::Html => {
-            normalize_html(
-                Arc::clone(&provider),
-                Arc::clone(&reasoner),
-                document,
-                options,
-                normalization_context.clone(),
-            )
-            .await?;
-        }
-        DocumentType::Json => {
-            unimplemented!();
-        }
-        DocumentType::PlainText => {
-            unimplemented!();
-        }
-        DocumentType::JavaScript => {
-            unimplemented!();
-        }
-        DocumentType::Xml => {
-            unimplemented!();
-        }
+        DocumentType::Html => normalize_html(document, &normalization_context)?,
+        DocumentType::Json
+        | DocumentType::PlainText
+        | DocumentType::JavaScript
+        | DocumentType::Xml => load_meta_context(document, &normalization_context)?,
+        DocumentType::Csv | DocumentType::Tsv => return Err(Errors::UnexpectedDocumentType),
     }
 
     Ok(normalization_context)
 }
 
 fn build_normalized_graph<P: Provider>(
-    provider: Arc<P>,
+    _provider: Arc<P>,
     normalization_context: Arc<RwLock<NormalizationContext>>,
-    options: &Options
+    _options: &Options
 ) -> Result<
     (
         HashMap<ID, Arc<NormalContext>>,
@@ -472,396 +548,75 @@ fn build_normalized_graph<P: Provider>(
 > {
     log::trace!("In build_normalized_graph");
 
-    unimplemented!()
-
-    //let classification: Arc<Classification> = {
-    //    let lock = read_lock!(normalization_context);
-    //    lock.classification.clone().ok_or(Errors::ClassificationNotFound)?
-    //};
-    //let normalized = Arc::new(RwLock::new(GraphNode {
-    //    id: ID::new(),
-    //    parents: Vec::new(),
-    //    description: String::from("placeholder description"),
-    //    hash: Hash::new(),
-    //    subgraph_hash: Hash::new(),
-    //    lineage: Lineage::new(),
-    //    children: Vec::new(),
-    //}));
-
-    //let mut contexts: HashMap<ID, Arc<NormalContext>> = HashMap::new();
-    //let mut visited: HashSet<ID> = HashSet::new();
-
-    //let data_node = Arc::new(DataNode {
-    //    id: ID::new(),
-    //    hash: Hash::new(),
-    //    lineage: Lineage::new(),
-    //    fields: HashMap::new(),
-    //    description: "placeholder".to_string()
-    //});
-
-    //let root_context = Arc::new(NormalContext {
-    //    id: ID::new(),
-    //    network_name: Some(classification.name.clone()),
-    //    network_description: Some(classification.description.clone()),
-    //    graph_node: Arc::clone(&normalized),
-    //    data_node: Arc::clone(&data_node),
-    //});
-    //contexts.insert(
-    //    data_node.id.clone(),
-    //    Arc::clone(&root_context)
-    //);
-    //contexts.insert(
-    //    read_lock!(normalized).id.clone(),
-    //    Arc::clone(&root_context)
-    //);
-
-    //let basis_graph: BasisGraph = read_lock!(normalization_context).basis_graph.clone().unwrap();
-    //let canonicalization: CanonicalizationTransformation = basis_graph.canonicalization;
-    //let graph_root = read_lock!(normalization_context).meta_context.as_ref().unwrap().graph_root.clone();
-
-    //let mut queue = VecDeque::new();
-    //queue.push_back(graph_root);
-
-    //while let Some(current) = queue.pop_front() {
-    //    let subgraph_hash = read_lock!(current).subgraph_hash.clone();
-
-    //    let is_canonical = {
-    //        if let Some(basis_network) = read_lock!(normalization_context).get_basis_network_by_lineage_and_subgraph_hash(&subgraph_hash)? {
-    //            if let Some(basis_network) = canonicalization.transform(vec![basis_network])?.first() {
-    //                process_canonical_network(
-    //                    Arc::clone(&normalization_context),
-    //                    Arc::clone(&normalized),
-    //                    Arc::clone(&current),
-    //                    &mut contexts,
-    //                    &mut visited,
-    //                )?;
-
-    //                true
-    //            } else {
-    //                false
-    //            }
-    //        } else {
-    //            false
-    //        }
-    //    };
-
-    //    if !is_canonical {
-    //        for child in read_lock!(current).children.iter() {
-    //            queue.push_back(Arc::clone(child));
-    //        }
-    //    }
-    //}
-
-    //Ok((contexts, normalized))
-}
-
-fn process_canonical_network(
-    normalization_context: Arc<RwLock<NormalizationContext>>,
-    normalized_parent_node: Graph,
-    current_node: Graph,
-    contexts: &mut HashMap<ID, Arc<NormalContext>>,
-    visited: &mut HashSet<ID>,
-) -> Result<(), Errors> {
-    unimplemented!()
-    //let basis_graph: BasisGraph = read_lock!(normalization_context).basis_graph.clone().unwrap();
-    //let relationships: Vec<RelationshipTransformation> = basis_graph.relationships.unwrap();
-    //let subgraph_hash: Hash = read_lock!(current_node).subgraph_hash.clone();
-    //let basis_network: Arc<BasisNetwork> = {
-    //    let lock = read_lock!(normalization_context);
-    //    lock.get_basis_network_by_lineage_and_subgraph_hash(&subgraph_hash)?.unwrap()
-    //};
-
-    //let current_relationships: Vec<&RelationshipTransformation> = relationships
-    //    .iter()
-    //    .filter(|item| item.from == basis_network.id || item.to == basis_network.id)
-    //    .collect();
-
-    //// No relationships, but still a canonical network so we include it
-    //if current_relationships.is_empty() {
-    //    process_network(
-    //        Arc::clone(&normalization_context),
-    //        Arc::clone(&normalized_parent_node),
-    //        Arc::clone(&current_node),
-    //        contexts,
-    //        visited,
-    //    )?;
-    //}
-
-    //for relationship in current_relationships.iter() {
-    //    match relationship.relationship_type {
-    //        NetworkRelationshipType::Composition => {
-    //            if relationship.from == basis_network.id {
-    //                let normalized_data_node = Arc::new(DataNode {
-    //                    id: ID::new(),
-    //                    hash: Hash::new(),
-    //                    lineage: Lineage::new(),
-    //                    fields: HashMap::new(),
-    //                    description: "Merged network".to_string()
-    //                });
-    //                let normalized_graph_node = Arc::new(RwLock::new(GraphNode::from_data_node(
-    //                    Arc::clone(&normalized_data_node),
-    //                    vec![normalized_parent_node.clone()]
-    //                )));
-
-    //                write_lock!(normalized_parent_node).children.push(Arc::clone(&normalized_graph_node));
-
-    //                let normal_context = Arc::new(NormalContext {
-    //                    id: ID::new(),
-    //                    network_name: Some(basis_network.transformation.image.clone()),
-    //                    network_description: Some(basis_network.description.clone()),
-    //                    graph_node: Arc::clone(&normalized_graph_node),
-    //                    data_node: Arc::clone(&normalized_data_node),
-    //                });
-
-    //                contexts.insert(
-    //                    normalized_data_node.id.clone(),
-    //                    Arc::clone(&normal_context)
-    //                );
-    //                contexts.insert(
-    //                    read_lock!(normalized_graph_node).id.clone(),
-    //                    Arc::clone(&normal_context)
-    //                );
-
-    //                process_network(
-    //                    Arc::clone(&normalization_context),
-    //                    Arc::clone(&normalized_graph_node),
-    //                    Arc::clone(&current_node),
-    //                    contexts,
-    //                    visited,
-    //                )?;
-
-    //                process_composition_relationship(
-    //                    Arc::clone(&normalization_context),
-    //                    Arc::clone(&normalized_graph_node),
-    //                    Arc::clone(&current_node),
-    //                    *relationship,
-    //                    contexts,
-    //                    visited,
-    //                )?;
-    //            }
-    //        }
-    //        NetworkRelationshipType::ParentChild => {
-    //            todo!();
-    //        }
-    //    }
-    //}
-
-    //Ok(())
-}
-
-//fn process_composition_relationship(
-//    normalization_context: Arc<RwLock<NormalizationContext>>,
-//    normalized_parent_node: Graph,
-//    current_node: Graph,
-//    relationship: &RelationshipTransformation,
-//    contexts: &mut HashMap<ID, Arc<NormalContext>>,
-//    visited: &mut HashSet<ID>,
-//) -> Result<(), Errors> {
-//    let basis_graph: BasisGraph = read_lock!(normalization_context).basis_graph.clone().unwrap();
-//    let traversals: Option<Vec<TraversalTransformation>> = basis_graph.traversals;
-//
-//    let Some(traversals) = traversals else {
-//        panic!("Processing composition relationship, but no traversals available on basis graph");
-//    };
-//
-//    let Some(traversal) = traversals.iter().find(|item| item.relationship_id == relationship.id) else {
-//        panic!("Processing composition relationship, but no traversal is available for this relationship");
-//    };
-//
-//    if let Some(target_network) = traversal.transform(
-//        Arc::clone(&normalization_context),
-//        Arc::clone(&current_node),
-//    )? {
-//        let target_id = read_lock!(target_network).id.clone();
-//        if visited.contains(&target_id) {
-//            log::info!("Composition target is already being processed — skipping to prevent cycle");
-//        } else {
-//            process_network(
-//                Arc::clone(&normalization_context),
-//                Arc::clone(&normalized_parent_node),
-//                Arc::clone(&target_network),
-//                contexts,
-//                visited,
-//            )?;
-//        }
-//    } else {
-//        log::warn!("Traversal could not be applied to find target network");
-//    }
-//
-//    Ok(())
-//}
-
-fn process_network(
-    normalization_context: Arc<RwLock<NormalizationContext>>,
-    normalized_parent_node: Graph,
-    current_node: Graph,
-    contexts: &mut HashMap<ID, Arc<NormalContext>>,
-    visited: &mut HashSet<ID>,
-) -> Result<(), Errors> {
-    unimplemented!()
-    //visited.insert(read_lock!(current_node).id.clone());
-
-    //fn recurse(
-    //    normalization_context: Arc<RwLock<NormalizationContext>>,
-    //    current_node: Graph,
-    //    parent_normalized_node: Graph,
-    //    contexts: &mut HashMap<ID, Arc<NormalContext>>,
-    //    visited: &mut HashSet<ID>,
-    //) -> Result<(), Errors> {
-    //    let basis_graph: BasisGraph = read_lock!(normalization_context).basis_graph.clone().unwrap();
-    //    let canonicalization: CanonicalizationTransformation = basis_graph.canonicalization;
-    //    
-    //    let normalized_data_node = process_node(
-    //        Arc::clone(&normalization_context),
-    //        Arc::clone(&current_node)
-    //    )?;
-
-    //    let normalized_data_node = Arc::new(normalized_data_node);
-
-    //    let normalized_graph_node = Arc::new(RwLock::new(GraphNode::from_data_node(
-    //        Arc::clone(&normalized_data_node),
-    //        vec![parent_normalized_node.clone()]
-    //    )));
-
-    //    write_lock!(parent_normalized_node).children.push(Arc::clone(&normalized_graph_node));
-
-    //    let normal_context = {
-    //        let subgraph_hash: Hash = read_lock!(current_node).subgraph_hash.clone();
-
-    //        let basis_network = {
-    //            let lock = read_lock!(normalization_context);
-    //            lock.get_basis_network_by_lineage_and_subgraph_hash(&subgraph_hash)?
-    //        };
-
-    //        if let Some(basis_network) = basis_network {
-    //            Arc::new(NormalContext {
-    //                id: ID::new(),
-    //                network_name: Some(basis_network.transformation.image.clone()),
-    //                network_description: Some(basis_network.description.clone()),
-    //                graph_node: Arc::clone(&normalized_graph_node),
-    //                data_node: Arc::clone(&normalized_data_node),
-    //            })
-    //        } else {
-    //            Arc::new(NormalContext {
-    //                id: ID::new(),
-    //                network_name: None,
-    //                network_description: None,
-    //                graph_node: Arc::clone(&normalized_graph_node),
-    //                data_node: Arc::clone(&normalized_data_node),
-    //            })
-    //        }
-    //    };
-
-    //    contexts.insert(
-    //        normalized_data_node.id.clone(),
-    //        Arc::clone(&normal_context)
-    //    );
-    //    contexts.insert(
-    //        read_lock!(normalized_graph_node).id.clone(),
-    //        Arc::clone(&normal_context)
-    //    );
-
-    //    for child in &read_lock!(current_node).children {
-    //        let subgraph_hash: Hash = read_lock!(child).subgraph_hash.clone();
-
-    //        let basis_network = {
-    //            let lock = read_lock!(normalization_context);
-    //            lock.get_basis_network_by_lineage_and_subgraph_hash(&subgraph_hash)?
-    //        };
-
-    //       if let Some(basis_network) = basis_network {
-    //           if let Some(canonical) = canonicalization
-    //               .transform(vec![Arc::clone(&basis_network)])?
-    //               .first()
-    //           {
-    //               process_canonical_network(
-    //                   Arc::clone(&normalization_context),
-    //                   Arc::clone(&normalized_graph_node),
-    //                   Arc::clone(&child),
-    //                   contexts,
-    //                   visited,
-    //               )?;
-    //               continue;
-    //           }
-    //       }
-
-    //       recurse(
-    //           Arc::clone(&normalization_context),
-    //           Arc::clone(&child),
-    //           Arc::clone(&normalized_graph_node),
-    //           contexts,
-    //           visited,
-    //       )?;
-    //    }
-
-    //    Ok(())
-    //}
-
-    //recurse(
-    //    Arc::clone(&normalization_context),
-    //    Arc::clone(&current_node),
-    //    Arc::clone(&normalized_parent_node),
-    //    contexts,
-    //    visited,
-    //)?;
-
-    //Ok(())
-}
-
-fn process_node(
-    normalization_context: Arc<RwLock<NormalizationContext>>,
-    node: Graph,
-) -> Result<DataNode, Errors> {
-    let context = {
+    let classification: Arc<Classification> = {
         let lock = read_lock!(normalization_context);
-        lock.meta_context.as_ref().unwrap().contexts_lookup
-            .get(&read_lock!(node).id)
-            .cloned()
-            .unwrap()
+        lock.classification.clone().ok_or(Errors::ClassificationNotFound)?
     };
-    let context_to_group = {
+
+    let mut basis_networks: Vec<Arc<BasisNetwork>> = {
         let lock = read_lock!(normalization_context);
-        lock.context_to_group.clone().unwrap()
-    };
-    let data_node = &context.data_node;
-    let maybe_basis_group: Option<Arc<BasisGroup>> = context_to_group.get(&context.id).cloned();
-
-    let basis_lineage: Option<Lineage> = {
-        if let Some(basis_group) = maybe_basis_group {
-            Some(basis_group.get_basis_lineage())
-        } else {
-            None
-        }
+        lock.basis_networks
+            .as_ref()
+            .ok_or_else(|| {
+                Errors::DeficientNormalizationContextError("Basis networks not provided in normalization context".to_string())
+            })?
+            .values()
+            .cloned()
+            .collect()
     };
 
-    if let Some(basis_lineage) = basis_lineage {
-        let basis_node = {
-            let lock = read_lock!(normalization_context);
-            lock.get_basis_node_by_lineage(&basis_lineage)
-                .expect("Could not get basis node by lineage")
-                .unwrap()
-        };
+    // Networks are applied in a stable order so repeated runs produce the same graph
+    basis_networks.sort_by_key(|basis_network| {
+        basis_network.basis_nodes
+            .iter()
+            .map(|basis_node| basis_node.lineage.to_string())
+            .min()
+            .unwrap_or_default()
+    });
+
+    let root_data_node = Arc::new(DataNode::new(
+        Hash::new(),
+        Lineage::new(),
+        DataNodeFields::new(),
+        classification.description.clone(),
+    ));
+
+    let normalized_graph_root = Arc::new(RwLock::new(GraphNode::from_data_node(
+        Arc::clone(&root_data_node),
+        Vec::new(),
+    )));
+    let root_id = read_lock!(normalized_graph_root).id.clone();
+
+    let root_context = Arc::new(NormalContext {
+        id: ID::new(),
+        network_name: Some(classification.name.clone()),
+        network_description: Some(classification.description.clone()),
+        graph_node: Arc::clone(&normalized_graph_root),
+        data_node: Arc::clone(&root_data_node),
+        contexts: Vec::new(),
+    });
+
+    let mut contexts: HashMap<ID, Arc<NormalContext>> = HashMap::new();
+    contexts.insert(root_id.clone(), Arc::clone(&root_context));
+    contexts.insert(root_data_node.id.clone(), Arc::clone(&root_context));
+
+    for basis_network in basis_networks {
+        let normal_meta_context = basis_network.apply(
+            Arc::clone(&normalization_context),
+            Arc::clone(&normalized_graph_root),
+        )?;
 
-        let data_nodes: Vec<DataNode> = basis_node.transformations
-            .clone()
-            .into_iter()
-            .map(|transformation| {
-                transformation
-                    .transform(Arc::clone(&data_node))
-                    .expect("Could not transform data node")
-            })
-            .collect();
+        for (graph_node_id, normal_context) in normal_meta_context.contexts_lookup {
+            if graph_node_id == root_id {
+                continue;
+            }
 
-        let normalized_data_node = DataNode::from_data_nodes(data_nodes);
-
-        Ok(normalized_data_node)
-    } else {
-        Ok(DataNode {
-            id: ID::new(),
-            hash: Hash::new(),
-            lineage: Lineage::new(),
-            fields: DataNodeFields::new(),
-            description: "placeholder".to_string()
-        })
+            contexts.insert(normal_context.data_node.id.clone(), Arc::clone(&normal_context));
+            contexts.insert(graph_node_id, normal_context);
+        }
     }
+
+    log::info!("Number of normalized records: {}", read_lock!(normalized_graph_root).children.len());
+
+    Ok((contexts, normalized_graph_root))
 }
@@ -9,6 +9,7 @@ use crate::basis_node::BasisNode;
 use crate::basis_graph::BasisGraph;
 use crate::context::Context;
 use crate::document::Document;
+use crate::drift_analysis::DriftReport;
 use crate::graph_node::Graph;
 use crate::meta_context::MetaContext;
 use crate::prelude::*;
@@ -28,6 +29,7 @@ pub struct NormalizationContext {
     pub normal_graph_root: Option<Graph>,
     pub context_groups: Option<HashMap<ID, Vec<Arc<Context>>>>,
     pub context_to_group: Option<HashMap<ID, Arc<BasisGroup>>>,
+    pub drift_report: Option<Arc<DriftReport>>,
 }
 
 impl NormalizationContext {
@@ -46,6 +48,7 @@ impl NormalizationContext {
             normal_graph_root: None,
             context_groups: None,
             context_to_group: None,
+            drift_report: None,
         }
     }
 
@@ -73,6 +76,10 @@ impl NormalizationContext {
         Ok(None)
     }
 
+    pub fn update_drift_report(&mut self, drift_report: DriftReport) {
+        self.drift_report = Some(Arc::new(drift_report));
+    }
+
     pub fn update_meta_context(&mut self, meta_context: MetaContext) {
         self.meta_context = Some(Arc::new(meta_context));
     }
@@ -86,6 +93,58 @@ impl NormalizationContext {
         self.normal_graph_root = Some(graph_root);
     }
 
+    // Records of another normalized graph, e.g. of a document embedded in this one, are
+    // added as records of this graph
+    pub fn merge_normalized_graph(&mut self, other: &NormalizationContext) -> Result<(), Errors> {
+        let (Some(graph_root), Some(contexts)) = (&self.normal_graph_root, &mut self.normal_contexts) else {
+            return Err(Errors::DeficientNormalizationContextError("Normalized graph not provided in normalization context".to_string()));
+        };
+
+        let (Some(other_root), Some(other_contexts)) = (&other.normal_graph_root, &other.normal_contexts) else {
+            return Err(Errors::DeficientNormalizationContextError("Normalized graph not provided in merged normalization context".to_string()));
+        };
+
+        let (other_root_id, records) = {
+            let lock = read_lock!(other_root);
+            (lock.id.clone(), lock.children.clone())
+        };
+        let other_root_data_node_id = other_contexts
+            .get(&other_root_id)
+            .map(|context| context.data_node.id.clone());
+
+        for (id, context) in other_contexts {
+            if *id == other_root_id || Some(id) == other_root_data_node_id.as_ref() {
+                continue;
+            }
+
+            contexts.insert(id.clone(), Arc::clone(context));
+        }
+
+        for record in records {
+            write_lock!(record).parents = vec![Arc::clone(graph_root)];
+            write_lock!(graph_root).children.push(record);
+        }
+
+        Ok(())
+    }
+
+    // Leaves the normalized graph with its root only
+    pub fn clear_normalized_records(&mut self) -> Result<(), Errors> {
+        let (Some(graph_root), Some(contexts)) = (&self.normal_graph_root, &mut self.normal_contexts) else {
+            return Err(Errors::DeficientNormalizationContextError("Normalized graph not provided in normalization context".to_string()));
+        };
+
+        let root_id = read_lock!(graph_root).id.clone();
+        let root_data_node_id = contexts
+            .get(&root_id)
+            .map(|context| context.data_node.id.clone());
+
+        contexts.retain(|id, _| *id == root_id || Some(id) == root_data_node_id.as_ref());
+        write_lock!(graph_root).children.clear();
+
+        Ok(())
+    }
+
     pub fn update_classification(&mut self, classification: Arc<Classification>) {
         self.classification = Some(classification);
     }
@@ -1,8 +1,134 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use crate::drift_analysis::DriftPolicy;
+
+// Stages whose inferences are stored in the provider and can be regenerated on their own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InferenceStage {
+    Classification,
+    Fields,
+    Groups,
+    Nodes,
+    Relationships,
+    Schema,
+    Translations,
+}
+
+impl InferenceStage {
+    pub const ALL: [InferenceStage; 7] = [
+        InferenceStage::Classification,
+        InferenceStage::Fields,
+        InferenceStage::Groups,
+        InferenceStage::Nodes,
+        InferenceStage::Relationships,
+        InferenceStage::Schema,
+        InferenceStage::Translations,
+    ];
+
+    // Stages whose inferences were made from the ones of this stage, and so go stale with them
+    fn downstream(&self) -> &'static [InferenceStage] {
+        match self {
+            InferenceStage::Classification => &[InferenceStage::Fields],
+            InferenceStage::Fields => &[InferenceStage::Groups],
+            InferenceStage::Groups => &[InferenceStage::Nodes],
+            InferenceStage::Nodes => &[InferenceStage::Relationships],
+            InferenceStage::Relationships => &[InferenceStage::Translations],
+            InferenceStage::Schema => &[InferenceStage::Translations],
+            InferenceStage::Translations => &[],
+        }
+    }
+
+    // The given stages along with every stage downstream of them
+    pub fn with_downstream(stages: impl IntoIterator<Item = InferenceStage>) -> BTreeSet<InferenceStage> {
+        let mut result = BTreeSet::new();
+        let mut pending: Vec<InferenceStage> = stages.into_iter().collect();
+
+        while let Some(stage) = pending.pop() {
+            if result.insert(stage) {
+                pending.extend_from_slice(stage.downstream());
+            }
+        }
+
+        result
+    }
+}
+
+impl FromStr for InferenceStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "classification" => Ok(InferenceStage::Classification),
+            "fields" => Ok(InferenceStage::Fields),
+            "groups" => Ok(InferenceStage::Groups),
+            "nodes" => Ok(InferenceStage::Nodes),
+            "relationships" => Ok(InferenceStage::Relationships),
+            "schema" => Ok(InferenceStage::Schema),
+            "translations" => Ok(InferenceStage::Translations),
+            other => Err(format!("Invalid stage: {}", other)),
+        }
+    }
+}
+
+// What becomes of the records read from the markup of a page that embeds structured data
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EmbeddedDataPolicy {
+    // Records of the structured data are added to the ones of the markup
+    #[default]
+    Merge,
+    // Records of the structured data replace the ones of the markup, which are only kept
+    // when none of the structured data could be normalized
+    Prefer,
+}
+
+impl FromStr for EmbeddedDataPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "merge" => Ok(EmbeddedDataPolicy::Merge),
+            "prefer" => Ok(EmbeddedDataPolicy::Prefer),
+            other => Err(format!("Invalid embedded data policy: {}", other)),
+        }
+    }
+}
+
+// Stage names as given on the command line, where "all" stands for every stage
+pub fn parse_regeneration_stages<S: AsRef<str>>(values: &[S]) -> Result<BTreeSet<InferenceStage>, String> {
+    let mut stages = Vec::new();
+
+    for value in values {
+        if value.as_ref().trim().eq_ignore_ascii_case("all") {
+            stages.extend(InferenceStage::ALL);
+        } else {
+            stages.push(InferenceStage::from_str(value.as_ref())?);
+        }
+    }
+
+    Ok(InferenceStage::with_downstream(stages))
+}
+
 #[derive(Clone, Debug)]
 pub struct Options {
     pub origin: Option<String>,
     pub date: Option<String>,
-    pub regenerate: bool,
+    // Stages that bypass the inferences stored in the provider, including everything
+    // downstream of them
+    pub regenerate: BTreeSet<InferenceStage>,
+    // Only apply inferences already in the provider, never ask the reasoner
+    pub offline: bool,
+    // Compare documents against the structure recorded for their origin, and what to do
+    // when too little of it is known
+    pub drift_policy: Option<DriftPolicy>,
+    pub drift_threshold: Option<f64>,
+    pub embedded_data: EmbeddedDataPolicy,
+}
+
+impl Options {
+    pub fn regenerates(&self, stage: InferenceStage) -> bool {
+        self.regenerate.contains(&stage)
+    }
 }
 
 impl Default for Options {
@@ -10,7 +136,11 @@ impl Default for Options {
         Options {
             origin: None,
             date: None,
-            regenerate: false,
+            regenerate: BTreeSet::new(),
+            offline: false,
+            drift_policy: None,
+            drift_threshold: None,
+            embedded_data: EmbeddedDataPolicy::default(),
         }
     }
 }
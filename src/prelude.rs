@@ -18,7 +18,7 @@ pub use crate::normalization_context::NormalizationContext;
 pub use crate::meta_context::MetaContext;
 pub use crate::translation_context::TranslationContext;
 pub use crate::metadata::Metadata;
-pub use crate::options::Options;
+pub use crate::options::{Options, InferenceStage};
 pub use crate::types::*;
 pub use crate::utility::*;
 pub use crate::execution_context::*;
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::basis_field::BasisField;
+use crate::basis_graph::BasisGraph;
+use crate::basis_group::BasisGroup;
+use crate::basis_network::NodeRelationship;
+use crate::basis_node::BasisNode;
+use crate::classification::Classification;
+use crate::document::Document;
+use crate::drift_analysis::StructureSnapshot;
+use crate::translation_network::TranslationNetwork;
+use crate::translation_node::TranslationNode;
+
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+// Everything normalized for an origin, so that stored inferences can be traced back to the
+// origins they were made for
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OriginIndex {
+    pub acyclic_subgraph_hashes: BTreeSet<String>,
+    pub acyclic_lineages: BTreeSet<String>,
+    // Translation nodes and networks are stored by full lineage. Indexes written before
+    // these were recorded have none, so the translations of such an origin are only found
+    // once a document of it is translated again.
+    #[serde(default)]
+    pub lineages: BTreeSet<String>,
+}
+
+impl OriginIndex {
+    pub fn merge(&mut self, other: OriginIndex) {
+        self.acyclic_subgraph_hashes.extend(other.acyclic_subgraph_hashes);
+        self.acyclic_lineages.extend(other.acyclic_lineages);
+        self.lineages.extend(other.lineages);
+    }
+}
+
+// A stored inference along with the keys the provider looks it up by
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleEntry<T> {
+    pub key: Vec<String>,
+    pub data: T,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ListedEntry {
+    pub kind: &'static str,
+    pub key: Vec<String>,
+    pub data: serde_json::Value,
+}
+
+// Portable copy of what a provider holds, e.g. to move tuned inferences from one provider
+// to another
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProviderBundle {
+    pub format_version: u32,
+    #[serde(default)]
+    pub classifications: Vec<BundleEntry<Classification>>,
+    #[serde(default)]
+    pub basis_fields: Vec<BundleEntry<Vec<BasisField>>>,
+    #[serde(default)]
+    pub basis_groups: Vec<BundleEntry<BasisGroup>>,
+    #[serde(default)]
+    pub basis_nodes: Vec<BundleEntry<BasisNode>>,
+    #[serde(default)]
+    pub node_relationships: Vec<BundleEntry<NodeRelationship>>,
+    #[serde(default)]
+    pub basis_graphs: Vec<BundleEntry<BasisGraph>>,
+    #[serde(default)]
+    pub schema_instances: Vec<BundleEntry<Document>>,
+    #[serde(default)]
+    pub translation_nodes: Vec<BundleEntry<Option<TranslationNode>>>,
+    #[serde(default)]
+    pub translation_networks: Vec<BundleEntry<Option<TranslationNetwork>>>,
+    #[serde(default)]
+    pub structure_snapshots: Vec<BundleEntry<StructureSnapshot>>,
+    #[serde(default)]
+    pub origins: Vec<BundleEntry<OriginIndex>>,
+}
+
+impl Default for ProviderBundle {
+    fn default() -> Self {
+        ProviderBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            classifications: Vec::new(),
+            basis_fields: Vec::new(),
+            basis_groups: Vec::new(),
+            basis_nodes: Vec::new(),
+            node_relationships: Vec::new(),
+            basis_graphs: Vec::new(),
+            schema_instances: Vec::new(),
+            translation_nodes: Vec::new(),
+            translation_networks: Vec::new(),
+            structure_snapshots: Vec::new(),
+            origins: Vec::new(),
+        }
+    }
+}
+
+impl ProviderBundle {
+    pub const KINDS: [&'static str; 11] = [
+        "classifications",
+        "basis_fields",
+        "basis_groups",
+        "basis_nodes",
+        "node_relationships",
+        "basis_graphs",
+        "schema_instances",
+        "translation_nodes",
+        "translation_networks",
+        "structure_snapshots",
+        "origins",
+    ];
+
+    pub fn len(&self) -> usize {
+        self.classifications.len() +
+            self.basis_fields.len() +
+            self.basis_groups.len() +
+            self.basis_nodes.len() +
+            self.node_relationships.len() +
+            self.basis_graphs.len() +
+            self.schema_instances.len() +
+            self.translation_nodes.len() +
+            self.translation_networks.len() +
+            self.structure_snapshots.len() +
+            self.origins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Inferences are stored by lineage and hash rather than by origin, so the ones of an
+    // origin are those reachable from the lineages and subgraphs recorded for it. Most are
+    // stored by acyclic lineage, translations by the full lineage of the normalized document.
+    // Documents and basis graphs are keyed by content alone and never belong to an origin.
+    pub fn for_origin(&self, origin: &str) -> ProviderBundle {
+        let Some(index) = self.origins.iter().find(|entry| entry.key.first().map(String::as_str) == Some(origin)) else {
+            return ProviderBundle::default();
+        };
+
+        let acyclic_lineages = &index.data.acyclic_lineages;
+        let lineages = &index.data.lineages;
+        let subgraph_hashes = &index.data.acyclic_subgraph_hashes;
+
+        let basis_groups = filter_entries(&self.basis_groups, |entry| is_first_key_in(entry, acyclic_lineages));
+        let basis_lineages: HashSet<String> = basis_groups
+            .iter()
+            .map(|entry| entry.data.get_basis_lineage().to_string())
+            .collect();
+
+        ProviderBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            classifications: filter_entries(&self.classifications, |entry| is_first_key_in(entry, acyclic_lineages)),
+            basis_fields: filter_entries(&self.basis_fields, |entry| is_first_key_in(entry, subgraph_hashes)),
+            basis_groups,
+            basis_nodes: filter_entries(&self.basis_nodes, |entry| {
+                entry.key.iter().any(|key| basis_lineages.contains(key))
+            }),
+            node_relationships: filter_entries(&self.node_relationships, |entry| {
+                entry.key.iter().any(|key| basis_lineages.contains(key))
+            }),
+            basis_graphs: Vec::new(),
+            schema_instances: Vec::new(),
+            translation_nodes: filter_entries(&self.translation_nodes, |entry| is_first_key_in(entry, lineages)),
+            translation_networks: filter_entries(&self.translation_networks, |entry| is_first_key_in(entry, lineages)),
+            structure_snapshots: filter_entries(&self.structure_snapshots, |entry| entry.key.iter().any(|key| key == origin)),
+            origins: filter_entries(&self.origins, |entry| entry.key.iter().any(|key| key == origin)),
+        }
+    }
+
+    // Entries of the origin that no other origin reaches as well
+    pub fn exclusive_to_origin(&self, origin: &str) -> ProviderBundle {
+        let mut shared = ProviderBundle::default();
+        for entry in &self.origins {
+            if let Some(other) = entry.key.first().filter(|other| other.as_str() != origin) {
+                shared.extend(self.for_origin(other));
+            }
+        }
+
+        self.for_origin(origin).without(&shared)
+    }
+
+    pub fn for_lineage(&self, lineage: &str) -> ProviderBundle {
+        let has_key = |key: &Vec<String>| key.iter().any(|part| part == lineage);
+
+        ProviderBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            classifications: filter_entries(&self.classifications, |entry| has_key(&entry.key)),
+            basis_fields: filter_entries(&self.basis_fields, |entry| has_key(&entry.key)),
+            basis_groups: filter_entries(&self.basis_groups, |entry| has_key(&entry.key)),
+            basis_nodes: filter_entries(&self.basis_nodes, |entry| has_key(&entry.key)),
+            node_relationships: filter_entries(&self.node_relationships, |entry| has_key(&entry.key)),
+            basis_graphs: filter_entries(&self.basis_graphs, |entry| has_key(&entry.key)),
+            schema_instances: filter_entries(&self.schema_instances, |entry| has_key(&entry.key)),
+            translation_nodes: filter_entries(&self.translation_nodes, |entry| has_key(&entry.key)),
+            translation_networks: filter_entries(&self.translation_networks, |entry| has_key(&entry.key)),
+            structure_snapshots: Vec::new(),
+            origins: Vec::new(),
+        }
+    }
+
+    pub fn extend(&mut self, other: ProviderBundle) {
+        self.classifications.extend(other.classifications);
+        self.basis_fields.extend(other.basis_fields);
+        self.basis_groups.extend(other.basis_groups);
+        self.basis_nodes.extend(other.basis_nodes);
+        self.node_relationships.extend(other.node_relationships);
+        self.basis_graphs.extend(other.basis_graphs);
+        self.schema_instances.extend(other.schema_instances);
+        self.translation_nodes.extend(other.translation_nodes);
+        self.translation_networks.extend(other.translation_networks);
+        self.structure_snapshots.extend(other.structure_snapshots);
+        self.origins.extend(other.origins);
+    }
+
+    pub fn list(&self, kind: Option<&str>) -> Vec<ListedEntry> {
+        let mut listed = Vec::new();
+
+        list_entries("classifications", &self.classifications, &mut listed);
+        list_entries("basis_fields", &self.basis_fields, &mut listed);
+        list_entries("basis_groups", &self.basis_groups, &mut listed);
+        list_entries("basis_nodes", &self.basis_nodes, &mut listed);
+        list_entries("node_relationships", &self.node_relationships, &mut listed);
+        list_entries("basis_graphs", &self.basis_graphs, &mut listed);
+        list_entries("schema_instances", &self.schema_instances, &mut listed);
+        list_entries("translation_nodes", &self.translation_nodes, &mut listed);
+        list_entries("translation_networks", &self.translation_networks, &mut listed);
+        list_entries("structure_snapshots", &self.structure_snapshots, &mut listed);
+        list_entries("origins", &self.origins, &mut listed);
+
+        listed.retain(|entry| kind.map_or(true, |kind| entry.kind == kind));
+
+        listed
+    }
+
+    fn without(self, other: &ProviderBundle) -> ProviderBundle {
+        ProviderBundle {
+            format_version: self.format_version,
+            classifications: exclude_keys(self.classifications, &other.classifications),
+            basis_fields: exclude_keys(self.basis_fields, &other.basis_fields),
+            basis_groups: exclude_keys(self.basis_groups, &other.basis_groups),
+            basis_nodes: exclude_keys(self.basis_nodes, &other.basis_nodes),
+            node_relationships: exclude_keys(self.node_relationships, &other.node_relationships),
+            basis_graphs: exclude_keys(self.basis_graphs, &other.basis_graphs),
+            schema_instances: exclude_keys(self.schema_instances, &other.schema_instances),
+            translation_nodes: exclude_keys(self.translation_nodes, &other.translation_nodes),
+            translation_networks: exclude_keys(self.translation_networks, &other.translation_networks),
+            structure_snapshots: exclude_keys(self.structure_snapshots, &other.structure_snapshots),
+            origins: exclude_keys(self.origins, &other.origins),
+        }
+    }
+}
+
+fn is_first_key_in<T>(entry: &BundleEntry<T>, keys: &BTreeSet<String>) -> bool {
+    entry.key.first().map_or(false, |key| keys.contains(key))
+}
+
+fn filter_entries<T: Clone, F>(entries: &[BundleEntry<T>], predicate: F) -> Vec<BundleEntry<T>>
+where
+    F: Fn(&BundleEntry<T>) -> bool,
+{
+    entries.iter().filter(|entry| predicate(entry)).cloned().collect()
+}
+
+fn exclude_keys<T>(entries: Vec<BundleEntry<T>>, excluded: &[BundleEntry<T>]) -> Vec<BundleEntry<T>> {
+    let excluded: HashSet<&Vec<String>> = excluded.iter().map(|entry| &entry.key).collect();
+
+    entries.into_iter().filter(|entry| !excluded.contains(&entry.key)).collect()
+}
+
+fn list_entries<T: Serialize>(kind: &'static str, entries: &[BundleEntry<T>], listed: &mut Vec<ListedEntry>) {
+    for entry in entries {
+        listed.push(ListedEntry {
+            kind,
+            key: entry.key.clone(),
+            data: serde_json::to_value(&entry.data).unwrap_or(serde_json::Value::Null),
+        });
+    }
+}
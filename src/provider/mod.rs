@@ -12,6 +12,11 @@ use crate::basis_field::BasisField;
 use crate::operation::Operation;
 use crate::translation_node::TranslationNode;
 use crate::translation_network::TranslationNetwork;
+use crate::drift_analysis::StructureSnapshot;
+
+pub mod bundle;
+
+use bundle::{OriginIndex, ProviderBundle};
 
 #[cfg(feature = "yaml-provider")]
 pub mod yaml;
@@ -119,6 +124,26 @@ pub trait Provider: Send + Sync + Sized + 'static {
         right_basis_lineage: Lineage,
         node_relationship: NodeRelationship,
     ) -> Result<(), Errors>;
+    async fn get_structure_snapshot(
+        &self,
+        origin: &str,
+    ) -> Result<Option<StructureSnapshot>, Errors>;
+    async fn save_structure_snapshot(
+        &self,
+        origin: &str,
+        structure_snapshot: StructureSnapshot,
+    ) -> Result<(), Errors>;
+    // Adds to what is already recorded for the origin
+    async fn update_origin_index(
+        &self,
+        origin: &str,
+        origin_index: OriginIndex,
+    ) -> Result<(), Errors>;
+    async fn export_bundle(&self) -> Result<ProviderBundle, Errors>;
+    // Entries already in the provider are replaced by the ones of the bundle
+    async fn import_bundle(&self, bundle: ProviderBundle) -> Result<(), Errors>;
+    // Removes the entries with the keys of those in the bundle
+    async fn delete_bundle(&self, bundle: &ProviderBundle) -> Result<(), Errors>;
 }
 
 pub struct VoidProvider;
@@ -275,4 +300,39 @@ impl Provider for VoidProvider {
     ) -> Result<(), Errors> {
         Ok(())
     }
+
+    async fn get_structure_snapshot(
+        &self,
+        _origin: &str,
+    ) -> Result<Option<StructureSnapshot>, Errors> {
+        Ok(None)
+    }
+
+    async fn save_structure_snapshot(
+        &self,
+        _origin: &str,
+        _structure_snapshot: StructureSnapshot,
+    ) -> Result<(), Errors> {
+        Ok(())
+    }
+
+    async fn update_origin_index(
+        &self,
+        _origin: &str,
+        _origin_index: OriginIndex,
+    ) -> Result<(), Errors> {
+        Ok(())
+    }
+
+    async fn export_bundle(&self) -> Result<ProviderBundle, Errors> {
+        Ok(ProviderBundle::default())
+    }
+
+    async fn import_bundle(&self, _bundle: ProviderBundle) -> Result<(), Errors> {
+        Ok(())
+    }
+
+    async fn delete_bundle(&self, _bundle: &ProviderBundle) -> Result<(), Errors> {
+        Ok(())
+    }
 }
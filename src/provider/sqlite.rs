@@ -15,6 +15,8 @@ use crate::operation::Operation;
 use crate::prelude::*;
 use crate::provider::Provider;
 use crate::document::Document;
+use crate::drift_analysis::StructureSnapshot;
+use crate::provider::bundle::{BundleEntry, OriginIndex, ProviderBundle};
 
 #[cfg(feature = "sqlite-provider")]
 pub struct SqliteProvider {
@@ -74,6 +76,14 @@ impl SqliteProvider {
                  data               TEXT,
                  PRIMARY KEY (lineage_from, lineage_to)
              );
+             CREATE TABLE IF NOT EXISTS structure_snapshots (
+                 origin  TEXT PRIMARY KEY,
+                 data    TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS origins (
+                 origin  TEXT PRIMARY KEY,
+                 data    TEXT NOT NULL
+             );
              ",
         )
         .map_err(|e| Errors::ProviderError(e.to_string()))?;
@@ -106,6 +116,109 @@ fn deserialize<T: serde::de::DeserializeOwned>(data: String) -> Result<T, Errors
     serde_json::from_str(&data).map_err(|e| db_err(e))
 }
 
+// Rows of a table as bundle entries, with the key columns in the order given
+fn export_table<T: serde::de::DeserializeOwned>(
+    conn: &rusqlite::Connection,
+    table: &str,
+    key_columns: &[&str],
+) -> Result<Vec<BundleEntry<T>>, Errors> {
+    let sql = format!("SELECT {}, data FROM {}", key_columns.join(", "), table);
+    let mut statement = conn.prepare(&sql).map_err(|e| db_err(e))?;
+
+    let rows = statement
+        .query_map([], |row| {
+            let key = (0..key_columns.len())
+                .map(|index| row.get::<_, String>(index))
+                .collect::<Result<Vec<String>, _>>()?;
+            let data = row.get::<_, Option<String>>(key_columns.len())?;
+            Ok((key, data))
+        })
+        .map_err(|e| db_err(e))?;
+
+    rows.map(|row| {
+        let (key, data) = row.map_err(|e| db_err(e))?;
+        Ok(BundleEntry {
+            key,
+            data: deserialize(data.unwrap_or_else(|| "null".to_string()))?,
+        })
+    })
+    .collect()
+}
+
+fn import_table<T: serde::Serialize>(
+    conn: &rusqlite::Connection,
+    table: &str,
+    key_columns: &[&str],
+    entries: &[BundleEntry<T>],
+) -> Result<(), Errors> {
+    let placeholders: Vec<String> = (1..=key_columns.len() + 1).map(|index| format!("?{}", index)).collect();
+    let sql = format!(
+        "INSERT OR REPLACE INTO {} ({}, data) VALUES ({})",
+        table,
+        key_columns.join(", "),
+        placeholders.join(", "),
+    );
+
+    for entry in entries {
+        let mut values = get_key_values(table, key_columns, &entry.key)?;
+
+        // Translations found not to exist are stored without data
+        let data = serde_json::to_value(&entry.data).map_err(|e| db_err(e))?;
+        values.push(if data.is_null() {
+            rusqlite::types::Value::Null
+        } else {
+            rusqlite::types::Value::Text(data.to_string())
+        });
+
+        conn.execute(&sql, rusqlite::params_from_iter(values)).map_err(|e| db_err(e))?;
+    }
+
+    Ok(())
+}
+
+fn delete_table<T>(
+    conn: &rusqlite::Connection,
+    table: &str,
+    key_columns: &[&str],
+    entries: &[BundleEntry<T>],
+) -> Result<(), Errors> {
+    let conditions: Vec<String> = key_columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| format!("{} = ?{}", column, index + 1))
+        .collect();
+    let sql = format!("DELETE FROM {} WHERE {}", table, conditions.join(" AND "));
+
+    for entry in entries {
+        let values = get_key_values(table, key_columns, &entry.key)?;
+        conn.execute(&sql, rusqlite::params_from_iter(values)).map_err(|e| db_err(e))?;
+    }
+
+    Ok(())
+}
+
+fn get_key_values(table: &str, key_columns: &[&str], key: &[String]) -> Result<Vec<rusqlite::types::Value>, Errors> {
+    if key.len() != key_columns.len() {
+        return Err(Errors::ProviderError(format!(
+            "Entry of {} has {} keys, expected {}",
+            table,
+            key.len(),
+            key_columns.len(),
+        )));
+    }
+
+    Ok(key.iter().cloned().map(rusqlite::types::Value::Text).collect())
+}
+
+const CLASSIFICATION_KEYS: &[&str] = &["lineage_hash"];
+const BASIS_FIELD_KEYS: &[&str] = &["acyclic_subgraph_hash"];
+const BASIS_GROUP_KEYS: &[&str] = &["acyclic_lineage_hash", "lineage_hash", "indexed_lineage_hash"];
+const BASIS_NODE_KEYS: &[&str] = &["lineage_hash"];
+const NODE_RELATIONSHIP_KEYS: &[&str] = &["left_basis_lineage", "right_basis_lineage"];
+const HASH_KEYS: &[&str] = &["hash"];
+const TRANSLATION_KEYS: &[&str] = &["lineage_from", "lineage_to"];
+const ORIGIN_KEYS: &[&str] = &["origin"];
+
 #[cfg(feature = "sqlite-provider")]
 #[async_trait]
 impl Provider for SqliteProvider {
@@ -619,4 +732,158 @@ impl Provider for SqliteProvider {
         .await
             .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
     }
+
+    async fn get_structure_snapshot(
+        &self,
+        origin: &str,
+    ) -> Result<Option<StructureSnapshot>, Errors> {
+        let conn = self.connection.clone();
+        let key = origin.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|_| lock_err())?;
+            match conn.query_row(
+                "SELECT data FROM structure_snapshots WHERE origin = ?1",
+                params![key],
+                |row| row.get::<_, String>(0),
+            ) {
+                Ok(data) => deserialize(data).map(Some),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(db_err(e)),
+            }
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
+
+    async fn save_structure_snapshot(
+        &self,
+        origin: &str,
+        structure_snapshot: StructureSnapshot,
+    ) -> Result<(), Errors> {
+        let conn = self.connection.clone();
+        let key = origin.to_string();
+        let data = serialize(&structure_snapshot)?;
+
+        task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|_| lock_err())?;
+            conn.execute(
+                "INSERT OR REPLACE INTO structure_snapshots (origin, data) VALUES (?1, ?2)",
+                params![key, data],
+            )
+            .map_err(|e| db_err(e))?;
+            Ok(())
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
+
+    async fn update_origin_index(
+        &self,
+        origin: &str,
+        origin_index: OriginIndex,
+    ) -> Result<(), Errors> {
+        let conn = self.connection.clone();
+        let key = origin.to_string();
+
+        task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|_| lock_err())?;
+
+            let mut merged: OriginIndex = match conn.query_row(
+                "SELECT data FROM origins WHERE origin = ?1",
+                params![key],
+                |row| row.get::<_, String>(0),
+            ) {
+                Ok(data) => deserialize(data)?,
+                Err(rusqlite::Error::QueryReturnedNoRows) => OriginIndex::default(),
+                Err(e) => return Err(db_err(e)),
+            };
+            merged.merge(origin_index);
+
+            conn.execute(
+                "INSERT OR REPLACE INTO origins (origin, data) VALUES (?1, ?2)",
+                params![key, serialize(&merged)?],
+            )
+            .map_err(|e| db_err(e))?;
+            Ok(())
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
+
+    async fn export_bundle(&self) -> Result<ProviderBundle, Errors> {
+        let conn = self.connection.clone();
+
+        task::spawn_blocking(move || {
+            let conn = conn.lock().map_err(|_| lock_err())?;
+
+            Ok(ProviderBundle {
+                classifications: export_table(&conn, "classifications", CLASSIFICATION_KEYS)?,
+                basis_fields: export_table(&conn, "basis_fields", BASIS_FIELD_KEYS)?,
+                basis_groups: export_table(&conn, "basis_groups", BASIS_GROUP_KEYS)?,
+                basis_nodes: export_table(&conn, "basis_nodes", BASIS_NODE_KEYS)?,
+                node_relationships: export_table(&conn, "node_relationships", NODE_RELATIONSHIP_KEYS)?,
+                basis_graphs: export_table(&conn, "basis_graphs", HASH_KEYS)?,
+                schema_instances: export_table(&conn, "documents", HASH_KEYS)?,
+                translation_nodes: export_table(&conn, "translation_nodes", TRANSLATION_KEYS)?,
+                translation_networks: export_table(&conn, "translation_networks", TRANSLATION_KEYS)?,
+                structure_snapshots: export_table(&conn, "structure_snapshots", ORIGIN_KEYS)?,
+                origins: export_table(&conn, "origins", ORIGIN_KEYS)?,
+                ..ProviderBundle::default()
+            })
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
+
+    async fn import_bundle(&self, bundle: ProviderBundle) -> Result<(), Errors> {
+        let conn = self.connection.clone();
+
+        task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| lock_err())?;
+            let transaction = conn.transaction().map_err(|e| db_err(e))?;
+
+            import_table(&transaction, "classifications", CLASSIFICATION_KEYS, &bundle.classifications)?;
+            import_table(&transaction, "basis_fields", BASIS_FIELD_KEYS, &bundle.basis_fields)?;
+            import_table(&transaction, "basis_groups", BASIS_GROUP_KEYS, &bundle.basis_groups)?;
+            import_table(&transaction, "basis_nodes", BASIS_NODE_KEYS, &bundle.basis_nodes)?;
+            import_table(&transaction, "node_relationships", NODE_RELATIONSHIP_KEYS, &bundle.node_relationships)?;
+            import_table(&transaction, "basis_graphs", HASH_KEYS, &bundle.basis_graphs)?;
+            import_table(&transaction, "documents", HASH_KEYS, &bundle.schema_instances)?;
+            import_table(&transaction, "translation_nodes", TRANSLATION_KEYS, &bundle.translation_nodes)?;
+            import_table(&transaction, "translation_networks", TRANSLATION_KEYS, &bundle.translation_networks)?;
+            import_table(&transaction, "structure_snapshots", ORIGIN_KEYS, &bundle.structure_snapshots)?;
+            import_table(&transaction, "origins", ORIGIN_KEYS, &bundle.origins)?;
+
+            transaction.commit().map_err(|e| db_err(e))
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
+
+    async fn delete_bundle(&self, bundle: &ProviderBundle) -> Result<(), Errors> {
+        let conn = self.connection.clone();
+        let bundle = bundle.clone();
+
+        task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| lock_err())?;
+            let transaction = conn.transaction().map_err(|e| db_err(e))?;
+
+            delete_table(&transaction, "classifications", CLASSIFICATION_KEYS, &bundle.classifications)?;
+            delete_table(&transaction, "basis_fields", BASIS_FIELD_KEYS, &bundle.basis_fields)?;
+            delete_table(&transaction, "basis_groups", BASIS_GROUP_KEYS, &bundle.basis_groups)?;
+            delete_table(&transaction, "basis_nodes", BASIS_NODE_KEYS, &bundle.basis_nodes)?;
+            delete_table(&transaction, "node_relationships", NODE_RELATIONSHIP_KEYS, &bundle.node_relationships)?;
+            delete_table(&transaction, "basis_graphs", HASH_KEYS, &bundle.basis_graphs)?;
+            delete_table(&transaction, "documents", HASH_KEYS, &bundle.schema_instances)?;
+            delete_table(&transaction, "translation_nodes", TRANSLATION_KEYS, &bundle.translation_nodes)?;
+            delete_table(&transaction, "translation_networks", TRANSLATION_KEYS, &bundle.translation_networks)?;
+            delete_table(&transaction, "structure_snapshots", ORIGIN_KEYS, &bundle.structure_snapshots)?;
+            delete_table(&transaction, "origins", ORIGIN_KEYS, &bundle.origins)?;
+
+            transaction.commit().map_err(|e| db_err(e))
+        })
+        .await
+        .map_err(|_| Errors::UnexpectedError("Database operation failed".to_string()))?
+    }
 }
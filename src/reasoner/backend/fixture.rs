@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, CompletionMetadata, Capability, ReasonerOperation, EmbeddingMetadata, get_prompt_hash};
+use crate::reasoner::recording::{
+    CompletionRecording,
+    EmbeddingRecording,
+    get_completion_path,
+    get_embedding_path,
+    read_recording,
+};
+use crate::prompt_registry::PromptRegistry;
+
+const FIXTURE_MODEL: &str = "fixture";
+
+// Answers from a directory of recorded responses so that the pipeline runs deterministically
+// and without network access. Fixtures share their format and layout with recordings, so a
+// recording can be checked in as is. Any request without a fixture is an error.
+pub struct FixtureReasoner {
+    prompts: PromptRegistry,
+    location: PathBuf,
+}
+
+impl FixtureReasoner {
+    pub fn new(prompts: PromptRegistry, location: PathBuf) -> Self {
+        FixtureReasoner {
+            prompts,
+            location,
+        }
+    }
+}
+
+fn to_fixture_error(error: Errors) -> Errors {
+    match error {
+        Errors::RecordingNotFound(message) => Errors::FixtureNotFound(message),
+        other => other,
+    }
+}
+
+#[async_trait]
+impl Reasoner for FixtureReasoner {
+    fn prompts(&self) -> &PromptRegistry { &self.prompts }
+
+    async fn complete(
+        &self,
+        capability: &Capability,
+        operation: &ReasonerOperation,
+        system_prompt: &str,
+        user_prompt: &str,
+        _schema: serde_json::Value,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        let prompt_hash = get_prompt_hash(system_prompt, user_prompt);
+        let path = get_completion_path(&self.location, &prompt_hash);
+
+        log::debug!("Reading {:?} {:?} completion fixture {}", capability, operation, path.display());
+
+        let fixture: CompletionRecording = read_recording(&path).map_err(|e| {
+            log::error!("╔═══════════════════════════════════════════════════════════════╗");
+            log::error!("║                    MISSING FIXTURE                            ║");
+            log::error!("╚═══════════════════════════════════════════════════════════════╝");
+            log::error!("No recorded completion for prompt hash {}", prompt_hash);
+            log::error!("Expected at: {}", path.display());
+            log::error!("User prompt: {}", user_prompt);
+            to_fixture_error(e)
+        })?;
+
+        Ok(fixture.into_completion(prompt_hash, FIXTURE_MODEL))
+    }
+
+    async fn embed(
+        &self,
+        inputs: Vec<String>
+    ) -> Result<(Vec<Vec<f32>>, EmbeddingMetadata), Errors> {
+        let mut vectors: Vec<Vec<f32>> = Vec::new();
+        let mut input_tokens = 0;
+
+        for input in &inputs {
+            let path = get_embedding_path(&self.location, input);
+
+            let fixture: EmbeddingRecording = read_recording(&path).map_err(|e| {
+                log::error!("No recorded embedding for input: {}", input);
+                log::error!("Expected at: {}", path.display());
+                to_fixture_error(e)
+            })?;
+
+            input_tokens += fixture.input_tokens;
+            vectors.push(fixture.embedding);
+        }
+
+        Ok((vectors, EmbeddingMetadata { input_tokens }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::document::{Document, DocumentType, DocumentRole};
+    use crate::metadata::Metadata;
+    use crate::normalization::normalize;
+    use crate::provider::VoidProvider;
+
+    const DOCUMENT: &str = r#"{
+        "title": "Release notes",
+        "authors": [
+            { "name": "Ada Lovelace", "url": "https://example.com/ada" },
+            { "name": "Grace Hopper", "url": "https://example.com/grace" }
+        ]
+    }"#;
+
+    fn get_reasoner(location: &str) -> FixtureReasoner {
+        let prompts = PromptRegistry::load(format!("file://{}/prompts", env!("CARGO_MANIFEST_DIR")))
+            .expect("Could not load prompts");
+
+        FixtureReasoner::new(prompts, PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(location))
+    }
+
+    fn get_document(data: &str) -> Document {
+        let metadata = Metadata {
+            document_type: Some(DocumentType::Json),
+            origin: "https://example.com/releases".to_string(),
+            role: DocumentRole::Instance,
+        };
+
+        Document::from_string(data.to_string(), &Options::default(), &metadata)
+            .expect("Could not build document")
+    }
+
+    #[tokio::test]
+    async fn normalizes_document_from_fixtures() {
+        let normalization_context = normalize(
+            Arc::new(VoidProvider),
+            Arc::new(get_reasoner("fixtures")),
+            get_document(DOCUMENT),
+            &Options::default(),
+            ExecutionContext::new(),
+        ).await.expect("Normalization should be answered from fixtures");
+
+        let lock = read_lock!(normalization_context);
+
+        let classification = lock.classification.as_ref().expect("Document should be classified");
+        assert_eq!(classification.name, "release notes");
+
+        let basis_fields = lock.basis_fields.as_ref().expect("Basis fields should be generated");
+        assert!(!basis_fields.is_empty());
+
+        let basis_nodes = lock.basis_nodes.as_ref().expect("Basis nodes should be generated");
+        let mut images: Vec<&str> = basis_nodes
+            .values()
+            .flat_map(|basis_node| basis_node.transformations.iter().map(|t| t.image.as_str()))
+            .collect();
+        images.sort();
+        assert_eq!(images, vec!["author_name", "author_profile_url", "release_title"]);
+
+        let basis_networks = lock.basis_networks.as_ref().expect("Basis networks should be generated");
+        assert!(!basis_networks.is_empty());
+        assert!(lock.normal_graph_root.is_some());
+    }
+
+    #[tokio::test]
+    async fn fails_on_missing_fixture() {
+        let result = normalize(
+            Arc::new(VoidProvider),
+            Arc::new(get_reasoner("fixtures")),
+            get_document(r#"{ "unrecorded": true }"#),
+            &Options::default(),
+            ExecutionContext::new(),
+        ).await;
+
+        assert!(matches!(result, Err(Errors::FixtureNotFound(_))));
+    }
+}
@@ -1,2 +1,154 @@
+use async_trait::async_trait;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, CompletionMetadata, Capability, ReasonerOperation, EmbeddingMetadata};
+use crate::prompt_registry::PromptRegistry;
+#[cfg(debug_assertions)]
+use crate::config::CONFIG;
+#[cfg(debug_assertions)]
+use crate::hash::Hash;
+#[cfg(debug_assertions)]
+use std::path::PathBuf;
+
 #[cfg(feature = "openrouter-reasoner")]
 pub mod openrouter;
+#[cfg(feature = "openai-compatible-reasoner")]
+pub mod openai_compatible;
+#[cfg(feature = "fixture-reasoner")]
+pub mod fixture;
+
+// The remote backend named by `reasoner.backend` in settings.toml, out of those compiled in
+pub enum ReasonerBackend {
+    #[cfg(feature = "openrouter-reasoner")]
+    OpenRouter(openrouter::OpenRouterReasoner),
+    #[cfg(feature = "openai-compatible-reasoner")]
+    OpenAICompatible(openai_compatible::OpenAICompatibleReasoner),
+}
+
+impl ReasonerBackend {
+    pub fn new(backend: &str, prompts: PromptRegistry) -> Result<Self, Errors> {
+        match backend {
+            #[cfg(feature = "openrouter-reasoner")]
+            "openrouter" => Ok(ReasonerBackend::OpenRouter(openrouter::OpenRouterReasoner::new(prompts))),
+            #[cfg(feature = "openai-compatible-reasoner")]
+            "openai-compatible" => Ok(ReasonerBackend::OpenAICompatible(
+                openai_compatible::OpenAICompatibleReasoner::new(prompts)?
+            )),
+            other => {
+                log::error!("Reasoner backend '{}' is unknown or was not compiled in", other);
+                Err(Errors::ReasonerNotConfigured)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Reasoner for ReasonerBackend {
+    fn prompts(&self) -> &PromptRegistry {
+        match *self {
+            #[cfg(feature = "openrouter-reasoner")]
+            ReasonerBackend::OpenRouter(ref reasoner) => reasoner.prompts(),
+            #[cfg(feature = "openai-compatible-reasoner")]
+            ReasonerBackend::OpenAICompatible(ref reasoner) => reasoner.prompts(),
+        }
+    }
+
+    async fn complete(
+        &self,
+        capability: &Capability,
+        operation: &ReasonerOperation,
+        system_prompt: &str,
+        user_prompt: &str,
+        schema: serde_json::Value,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        match *self {
+            #[cfg(feature = "openrouter-reasoner")]
+            ReasonerBackend::OpenRouter(ref reasoner) => {
+                reasoner.complete(capability, operation, system_prompt, user_prompt, schema).await
+            }
+            #[cfg(feature = "openai-compatible-reasoner")]
+            ReasonerBackend::OpenAICompatible(ref reasoner) => {
+                reasoner.complete(capability, operation, system_prompt, user_prompt, schema).await
+            }
+        }
+    }
+
+    async fn embed(
+        &self,
+        inputs: Vec<String>
+    ) -> Result<(Vec<Vec<f32>>, EmbeddingMetadata), Errors> {
+        match *self {
+            #[cfg(feature = "openrouter-reasoner")]
+            ReasonerBackend::OpenRouter(ref reasoner) => reasoner.embed(inputs).await,
+            #[cfg(feature = "openai-compatible-reasoner")]
+            ReasonerBackend::OpenAICompatible(ref reasoner) => reasoner.embed(inputs).await,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn write_debug_log<T: std::fmt::Debug>(system_prompt: &str, user_prompt: &str, response: &T, hash: &Hash) {
+    use std::fs;
+
+    let debug_dir = {
+        let config = CONFIG.read().unwrap();
+        PathBuf::from(&config.dev.debug_dir)
+    };
+
+    let file_path = debug_dir.join(format!("{}.txt", hash));
+
+    let content = format!(
+        "=== SYSTEM PROMPT ===\n{}\n\n=== USER PROMPT ===\n{}\n\n=== LLM RESPONSE ===\n{:#?}",
+        system_prompt,
+        user_prompt,
+        response
+    );
+
+    if let Err(e) = fs::write(&file_path, content) {
+        log::warn!("Failed to write debug log to {:?}: {}", file_path, e);
+    }
+}
+
+fn ensure_valid_json_schema(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(obj) => {
+            // Add additionalProperties: false
+            if obj.get("type").map(|t| t.as_str()) == Some(Some("object")) {
+                if !obj.contains_key("additionalProperties") {
+                    obj.insert("additionalProperties".to_string(), serde_json::json!(false));
+                }
+
+                // Ensure ALL properties are in required array
+                if let Some(serde_json::Value::Object(props)) = obj.get("properties") {
+                    let prop_keys: Vec<String> = props.keys().cloned().collect();
+                    let required = obj.entry("required".to_string())
+                        .or_insert_with(|| serde_json::json!([]));
+
+                    if let serde_json::Value::Array(required_arr) = required {
+                        for key in prop_keys {
+                            if !required_arr.iter().any(|v| v.as_str() == Some(&key)) {
+                                required_arr.push(serde_json::json!(key));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Recursively process nested objects
+            if let Some(serde_json::Value::Object(props)) = obj.get_mut("properties") {
+                for (_, prop_schema) in props.iter_mut() {
+                    ensure_valid_json_schema(prop_schema);
+                }
+            }
+            if let Some(serde_json::Value::Object(defs)) = obj.get_mut("$defs") {
+                for (_, def_schema) in defs.iter_mut() {
+                    ensure_valid_json_schema(def_schema);
+                }
+            }
+            if let Some(items_schema) = obj.get_mut("items") {
+                ensure_valid_json_schema(items_schema);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,411 @@
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, CompletionMetadata, Capability, ReasonerOperation, EmbeddingMetadata, get_prompt_hash, get_model_routes};
+use crate::environment::get_optional_env_variable;
+use crate::prompt_registry::PromptRegistry;
+use crate::config::{CONFIG, ModelRoute};
+use crate::hash::Hash;
+use crate::json_schema::JsonSchema;
+
+use super::ensure_valid_json_schema;
+#[cfg(debug_assertions)]
+use super::write_debug_log;
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatCompletionChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionChoice {
+    message: ChatCompletionMessage,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct Usage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+// Talks to any server exposing the OpenAI chat completions and embeddings endpoints, such as
+// Ollama, llama.cpp or vLLM, so that documents never have to leave the premises
+pub struct OpenAICompatibleReasoner {
+    client: Client,
+    prompts: PromptRegistry,
+    base_url: String,
+    api_key: Option<String>,
+    fast_model: String,
+    capable_model: String,
+    embedding_model: String,
+    json_schema_support: AtomicBool,
+    concurrency_limit: Arc<Semaphore>,
+}
+
+impl OpenAICompatibleReasoner {
+    pub fn new(prompts: PromptRegistry) -> Result<Self, Errors> {
+        let config = &read_lock!(CONFIG).reasoner.openai_compatible;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| Errors::UnexpectedError(format!("Could not build HTTP client: {}", e)))?;
+
+        let api_key = match config.api_key_variable.as_deref() {
+            Some(variable) => Some(get_optional_env_variable(variable).ok_or_else(|| {
+                Errors::InsufficientPrerequisites(format!("{} environment variable must be set", variable))
+            })?),
+            None => None,
+        };
+
+        log::info!("OpenAI compatible server: {}", config.base_url);
+
+        Ok(OpenAICompatibleReasoner {
+            client,
+            prompts,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            api_key,
+            fast_model: config.fast_model.clone(),
+            capable_model: config.capable_model.clone(),
+            embedding_model: config.embedding_model.clone(),
+            json_schema_support: AtomicBool::new(config.json_schema_support),
+            concurrency_limit: Arc::new(Semaphore::new(config.max_concurrency.max(1))),
+        })
+    }
+
+    async fn post(&self, endpoint: &str, body: &Value) -> Result<reqwest::Response, Errors> {
+        let mut request = self.client
+            .post(format!("{}/{}", self.base_url, endpoint))
+            .json(body);
+
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        request.send().await.map_err(|e| {
+            log::error!("Request to {} failed: {}", self.base_url, e);
+
+            if e.is_timeout() {
+                Errors::RequestTimeout(e.to_string())
+            } else if e.is_connect() {
+                // Nothing listening at the configured address is not going to change by retrying
+                Errors::BackendUnreachable(format!("Could not connect to {}: {}", self.base_url, e))
+            } else {
+                Errors::UnexpectedError(format!("OpenAI compatible backend error: {}", e))
+            }
+        })
+    }
+
+    async fn send_chat_completion(&self, body: &Value) -> Result<Result<ChatCompletionResponse, (StatusCode, String)>, Errors> {
+        let response = self.post("chat/completions", body).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Ok(Err((status, text)));
+        }
+
+        response.json::<ChatCompletionResponse>().await
+            .map(Ok)
+            .map_err(|e| Errors::UnexpectedError(format!("Invalid chat completion response: {}", e)))
+    }
+
+    async fn complete_with_route(
+        &self,
+        route: &ModelRoute,
+        system_prompt: &str,
+        user_prompt: &str,
+        schema: &Value,
+        prompt_hash: &Hash,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        let model = &route.model;
+        let mut response = None;
+
+        if self.json_schema_support.load(Ordering::Relaxed) {
+            let body = with_route(json!({
+                "model": model,
+                "messages": [
+                    { "role": "system", "content": system_prompt },
+                    { "role": "user", "content": user_prompt },
+                ],
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "structured_response",
+                        "strict": true,
+                        "schema": schema,
+                    },
+                },
+            }), route);
+
+            match self.send_chat_completion(&body).await? {
+                Ok(completion) => response = Some(completion),
+                Err((status, text)) if is_unsupported_response_format(status, &text) => {
+                    log::warn!("Server does not support JSON schema output, falling back to JSON mode: {}", text);
+                    self.json_schema_support.store(false, Ordering::Relaxed);
+                }
+                Err((status, text)) => return Err(status_to_error(status, text, model)),
+            }
+        }
+
+        let validate = response.is_none();
+
+        let response = match response {
+            Some(response) => response,
+            None => {
+                let system_prompt = format!(
+                    "{}\n\nRespond only with a JSON object conforming to this JSON schema:\n{}",
+                    system_prompt,
+                    serde_json::to_string_pretty(schema).unwrap_or_default()
+                );
+
+                let body = with_route(json!({
+                    "model": model,
+                    "messages": [
+                        { "role": "system", "content": system_prompt },
+                        { "role": "user", "content": user_prompt },
+                    ],
+                    "response_format": { "type": "json_object" },
+                }), route);
+
+                self.send_chat_completion(&body).await?
+                    .map_err(|(status, text)| status_to_error(status, text, model))?
+            }
+        };
+
+        log::debug!("┌─── RAW LLM RESPONSE ──────────────────────────────────────────┐");
+        log::debug!("{:?}", response);
+        log::debug!("└───────────────────────────────────────────────────────────────┘");
+
+        #[cfg(debug_assertions)]
+        write_debug_log(system_prompt, user_prompt, &response, prompt_hash);
+
+        let content = response.choices
+            .first()
+            .and_then(|choice| choice.message.content.clone())
+            .ok_or_else(|| {
+                log::error!("No content in LLM response");
+                Errors::UnexpectedError("No content in LLM response".to_string())
+            })?;
+
+        let content = if validate {
+            let content = strip_code_fence(&content).to_string();
+
+            // Without server side enforcement a model may well answer with something else,
+            // which is worth another attempt
+            let value: Value = serde_json::from_str(&content).map_err(|e| {
+                log::warn!("Response is not valid JSON: {}", e);
+                Errors::TransientBackendError(format!("Response is not valid JSON: {}", e))
+            })?;
+
+            let violations = JsonSchema::from_value(schema.clone()).validate(&value);
+
+            if !violations.is_empty() {
+                let violations = violations
+                    .iter()
+                    .map(|violation| format!("{}: {}", violation.path, violation.message))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+
+                log::warn!("Response does not conform to schema: {}", violations);
+                return Err(Errors::TransientBackendError(format!("Response does not conform to schema: {}", violations)));
+            }
+
+            content
+        } else {
+            content
+        };
+
+        let metadata = CompletionMetadata {
+            model: model.clone(),
+            input_tokens: response.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0),
+            output_tokens: response.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0),
+            prompt_hash: prompt_hash.clone(),
+        };
+
+        Ok((content, metadata))
+    }
+}
+
+#[async_trait]
+impl Reasoner for OpenAICompatibleReasoner {
+    fn prompts(&self) -> &PromptRegistry { &self.prompts }
+
+    async fn complete(
+        &self,
+        capability: &Capability,
+        operation: &ReasonerOperation,
+        system_prompt: &str,
+        user_prompt: &str,
+        schema: serde_json::Value,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        let _permit = self.concurrency_limit.acquire().await
+            .expect("Semaphore should never be closed");
+
+        let prompt_hash = get_prompt_hash(system_prompt, user_prompt);
+
+        let mut schema = schema.clone();
+        ensure_valid_json_schema(&mut schema);
+
+        let routes = get_model_routes(capability, operation).unwrap_or_else(|| {
+            match capability {
+                Capability::Fast => vec![ModelRoute::new(&self.fast_model)],
+                Capability::Capable => vec![ModelRoute::new(&self.capable_model)],
+            }
+        });
+
+        let mut last_error = Errors::ReasonerNotConfigured;
+
+        for (index, route) in routes.iter().enumerate() {
+            match self.complete_with_route(route, system_prompt, user_prompt, &schema, &prompt_hash).await {
+                Ok(result) => return Ok(result),
+                // Every route goes to the same server
+                Err(e @ Errors::BackendUnreachable(_)) => return Err(e),
+                Err(e) => {
+                    if let Some(next) = routes.get(index + 1) {
+                        log::warn!("Model {} failed for {:?}, falling back to {}: {:?}", route.model, operation, next.model, e);
+                    }
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn embed(
+        &self,
+        inputs: Vec<String>
+    ) -> Result<(Vec<Vec<f32>>, EmbeddingMetadata), Errors> {
+        let _permit = self.concurrency_limit.acquire().await
+            .expect("Semaphore should never be closed");
+
+        let input_count = inputs.len();
+
+        let body = json!({
+            "model": self.embedding_model,
+            "input": inputs,
+        });
+
+        let response = self.post("embeddings", &body).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            log::error!("Embedding request failed for {} inputs", input_count);
+
+            return Err(match status_to_error(status, text, &self.embedding_model) {
+                Errors::UnexpectedError(message) => Errors::EmbeddingError(message),
+                error => error,
+            });
+        }
+
+        let response = response.json::<EmbeddingResponse>().await
+            .map_err(|e| Errors::EmbeddingError(format!("Invalid embedding response: {}", e)))?;
+
+        if response.data.len() != input_count {
+            log::error!("Embedding response count mismatch: expected {}, got {}", input_count, response.data.len());
+            return Err(Errors::EmbeddingError(format!(
+                "Embedding response count ({}) doesn't match input count ({})",
+                response.data.len(),
+                input_count
+            )));
+        }
+
+        let mut data = response.data;
+        data.sort_by_key(|d| d.index);
+
+        let vectors: Vec<Vec<f32>> = data.into_iter().map(|d| d.embedding).collect();
+
+        let metadata = EmbeddingMetadata {
+            input_tokens: response.usage.map(|u| u.prompt_tokens).unwrap_or(0),
+        };
+
+        log::debug!("Embedding succeeded: {} vectors, {} tokens", vectors.len(), metadata.input_tokens);
+        Ok((vectors, metadata))
+    }
+}
+
+fn with_route(mut body: Value, route: &ModelRoute) -> Value {
+    if let Some(fields) = body.as_object_mut() {
+        if let Some(temperature) = route.temperature {
+            fields.insert("temperature".to_string(), json!(temperature));
+        }
+        if let Some(max_tokens) = route.max_tokens {
+            fields.insert("max_tokens".to_string(), json!(max_tokens));
+        }
+        for (key, parameter) in &route.parameters {
+            fields.insert(key.clone(), parameter.clone());
+        }
+    }
+
+    body
+}
+
+fn status_to_error(status: StatusCode, text: String, model: &str) -> Errors {
+    log::error!("╔═══════════════════════════════════════════════════════════════╗");
+    log::error!("║                    REQUEST ERROR                              ║");
+    log::error!("╚═══════════════════════════════════════════════════════════════╝");
+    log::error!("Model: {}", model);
+    log::error!("Status: {}", status);
+    log::error!("Error: {}", text);
+
+    match status {
+        StatusCode::PAYMENT_REQUIRED => Errors::InsufficientBackendQuota(text),
+        StatusCode::TOO_MANY_REQUESTS => Errors::RateLimitError(text),
+        StatusCode::REQUEST_TIMEOUT => Errors::RequestTimeout(text),
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            Errors::TransientBackendError(text)
+        }
+        _ => Errors::UnexpectedError(format!("Unexpected OpenAI compatible backend error {}: {}", status, text)),
+    }
+}
+
+// Servers differ in how they reject a response format they do not know, but they all
+// complain about the request rather than fail
+fn is_unsupported_response_format(status: StatusCode, text: &str) -> bool {
+    let text = text.to_lowercase();
+
+    matches!(status, StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY | StatusCode::NOT_IMPLEMENTED) &&
+        (text.contains("response_format") || text.contains("json_schema") || text.contains("schema"))
+}
+
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+
+    match trimmed.strip_prefix("```") {
+        Some(rest) => {
+            let rest = rest.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            rest.strip_suffix("```").unwrap_or(rest).trim()
+        }
+        None => trimmed,
+    }
+}
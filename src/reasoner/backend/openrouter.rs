@@ -7,17 +7,20 @@ use openrouter_rs::{
 };
 use openrouter_rs::error::{ApiErrorKind, OpenRouterError};
 use http::StatusCode;
-use std::path::PathBuf;
 use tokio::sync::Semaphore;
 use std::sync::Arc;
 
 use crate::prelude::*;
-use crate::reasoner::{Reasoner, CompletionMetadata, Capability, EmbeddingMetadata};
-use crate::environment::get_env_variable;
+use crate::reasoner::{Reasoner, CompletionMetadata, Capability, ReasonerOperation, EmbeddingMetadata, get_prompt_hash, get_model_routes};
+use crate::environment::get_optional_env_variable;
 use crate::prompt_registry::PromptRegistry;
-use crate::config::CONFIG;
+use crate::config::ModelRoute;
 use crate::hash::Hash;
 
+use super::ensure_valid_json_schema;
+#[cfg(debug_assertions)]
+use super::write_debug_log;
+
 #[cfg(feature = "openrouter-reasoner")]
 pub struct OpenRouterReasoner {
     client: OpenRouterClient,
@@ -28,7 +31,8 @@ pub struct OpenRouterReasoner {
 #[cfg(feature = "openrouter-reasoner")]
 impl OpenRouterReasoner {
     pub fn new(prompts: PromptRegistry) -> Self {
-        let api_key = get_env_variable("OPENROUTER_API_KEY");
+        // A missing key only matters once a request is made, which an offline run never does
+        let api_key = get_optional_env_variable("OPENROUTER_API_KEY").unwrap_or_default();
         let client = OpenRouterClient::builder()
             .api_key(api_key)
             .build()
@@ -40,34 +44,16 @@ impl OpenRouterReasoner {
             concurrency_limit: Arc::new(Semaphore::new(37)),
         }
     }
-}
-
-#[async_trait]
-#[cfg(feature = "openrouter-reasoner")]
-impl Reasoner for OpenRouterReasoner {
-    fn prompts(&self) -> &PromptRegistry { &self.prompts }
 
-    async fn complete(
+    async fn complete_with_route(
         &self,
-        capability: &Capability,
+        route: &ModelRoute,
         system_prompt: &str,
         user_prompt: &str,
         schema: serde_json::Value,
+        prompt_hash: &Hash,
     ) -> Result<(String, CompletionMetadata), Errors> {
-        let _permit = self.concurrency_limit.acquire().await
-            .expect("Semaphore should never be closed");
-
-        let combined_prompt = format!("{}{}", system_prompt, user_prompt);
-        let prompt_hash = Hash::from_str(&combined_prompt);
-
-        let model = match capability {
-            Capability::Fast => "gpt-5-mini",
-            Capability::Capable => "gpt-5",
-        };
-
-        // Clone and fix the schema - additionalProperties causes problems
-        let mut schema = schema.clone();
-        ensure_valid_json_schema(&mut schema);
+        let model = &route.model;
 
         let response_format = ResponseFormat::json_schema(
             "structured_response",
@@ -76,7 +62,7 @@ impl Reasoner for OpenRouterReasoner {
         );
 
         let request = ChatCompletionRequest::builder()
-            .model(model)
+            .model(model.as_str())
             .messages(vec![
                 Message::new(Role::System, system_prompt),
                 Message::new(Role::User, user_prompt),
@@ -85,6 +71,8 @@ impl Reasoner for OpenRouterReasoner {
             .build()
             .expect("Could not construct ChatCompletionRequest");
 
+        let request = apply_route(request, route)?;
+
         match self.client.send_chat_completion(&request).await {
             Ok(response) => {
                 log::debug!("┌─── RAW LLM RESPONSE ──────────────────────────────────────────┐");
@@ -107,15 +95,17 @@ impl Reasoner for OpenRouterReasoner {
 
                     let metadata = if let Some(usage) = response.usage {
                         CompletionMetadata {
+                            model: model.clone(),
                             input_tokens: usage.prompt_tokens as u32,
                             output_tokens: usage.completion_tokens as u32,
-                            prompt_hash: prompt_hash,
+                            prompt_hash: prompt_hash.clone(),
                         }
                     } else {
                         CompletionMetadata {
+                            model: model.clone(),
                             input_tokens: 0,
                             output_tokens: 0,
-                            prompt_hash: prompt_hash,
+                            prompt_hash: prompt_hash.clone(),
                         }
                     };
 
@@ -163,6 +153,53 @@ impl Reasoner for OpenRouterReasoner {
             }
         }
     }
+}
+
+#[async_trait]
+#[cfg(feature = "openrouter-reasoner")]
+impl Reasoner for OpenRouterReasoner {
+    fn prompts(&self) -> &PromptRegistry { &self.prompts }
+
+    async fn complete(
+        &self,
+        capability: &Capability,
+        operation: &ReasonerOperation,
+        system_prompt: &str,
+        user_prompt: &str,
+        schema: serde_json::Value,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        let _permit = self.concurrency_limit.acquire().await
+            .expect("Semaphore should never be closed");
+
+        let prompt_hash = get_prompt_hash(system_prompt, user_prompt);
+
+        let routes = get_model_routes(capability, operation).unwrap_or_else(|| {
+            match capability {
+                Capability::Fast => vec![ModelRoute::new("gpt-5-mini")],
+                Capability::Capable => vec![ModelRoute::new("gpt-5")],
+            }
+        });
+
+        // Clone and fix the schema - additionalProperties causes problems
+        let mut schema = schema.clone();
+        ensure_valid_json_schema(&mut schema);
+
+        let mut last_error = Errors::ReasonerNotConfigured;
+
+        for (index, route) in routes.iter().enumerate() {
+            match self.complete_with_route(route, system_prompt, user_prompt, schema.clone(), &prompt_hash).await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    if let Some(next) = routes.get(index + 1) {
+                        log::warn!("Model {} failed for {:?}, falling back to {}: {:?}", route.model, operation, next.model, e);
+                    }
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
 
     async fn embed(
         &self,
@@ -249,69 +286,33 @@ impl Reasoner for OpenRouterReasoner {
     }
 }
 
-#[cfg(debug_assertions)]
-fn write_debug_log<T: std::fmt::Debug>(system_prompt: &str, user_prompt: &str, response: &T, hash: &Hash) {
-    use std::fs;
+// Temperature and token limits have their own fields, anything else is merged into the
+// request as is. Parameters OpenRouter does not know are dropped with a warning.
+fn apply_route(request: ChatCompletionRequest, route: &ModelRoute) -> Result<ChatCompletionRequest, Errors> {
+    let mut value = serde_json::to_value(&request)
+        .map_err(|e| Errors::UnexpectedError(format!("Could not serialize request: {}", e)))?;
 
-    let debug_dir = {
-        let config = CONFIG.read().unwrap();
-        PathBuf::from(&config.dev.debug_dir)
-    };
-
-    let file_path = debug_dir.join(format!("{}.txt", hash));
-
-    let content = format!(
-        "=== SYSTEM PROMPT ===\n{}\n\n=== USER PROMPT ===\n{}\n\n=== LLM RESPONSE ===\n{:#?}",
-        system_prompt,
-        user_prompt,
-        response
-    );
-
-    if let Err(e) = fs::write(&file_path, content) {
-        log::warn!("Failed to write debug log to {:?}: {}", file_path, e);
+    if let Some(fields) = value.as_object_mut() {
+        if let Some(temperature) = route.temperature {
+            fields.insert("temperature".to_string(), serde_json::json!(temperature));
+        }
+        if let Some(max_tokens) = route.max_tokens {
+            fields.insert("max_tokens".to_string(), serde_json::json!(max_tokens));
+        }
+        for (key, parameter) in &route.parameters {
+            fields.insert(key.clone(), parameter.clone());
+        }
     }
-}
-
-fn ensure_valid_json_schema(schema: &mut serde_json::Value) {
-    match schema {
-        serde_json::Value::Object(obj) => {
-            // Add additionalProperties: false
-            if obj.get("type").map(|t| t.as_str()) == Some(Some("object")) {
-                if !obj.contains_key("additionalProperties") {
-                    obj.insert("additionalProperties".to_string(), serde_json::json!(false));
-                }
 
-                // Ensure ALL properties are in required array
-                if let Some(serde_json::Value::Object(props)) = obj.get("properties") {
-                    let prop_keys: Vec<String> = props.keys().cloned().collect();
-                    let required = obj.entry("required".to_string())
-                        .or_insert_with(|| serde_json::json!([]));
-
-                    if let serde_json::Value::Array(required_arr) = required {
-                        for key in prop_keys {
-                            if !required_arr.iter().any(|v| v.as_str() == Some(&key)) {
-                                required_arr.push(serde_json::json!(key));
-                            }
-                        }
-                    }
-                }
-            }
+    let request: ChatCompletionRequest = serde_json::from_value(value)
+        .map_err(|e| Errors::UnexpectedParameter(format!("Invalid parameters for model {}: {}", route.model, e)))?;
 
-            // Recursively process nested objects
-            if let Some(serde_json::Value::Object(props)) = obj.get_mut("properties") {
-                for (_, prop_schema) in props.iter_mut() {
-                    ensure_valid_json_schema(prop_schema);
-                }
-            }
-            if let Some(serde_json::Value::Object(defs)) = obj.get_mut("$defs") {
-                for (_, def_schema) in defs.iter_mut() {
-                    ensure_valid_json_schema(def_schema);
-                }
-            }
-            if let Some(items_schema) = obj.get_mut("items") {
-                ensure_valid_json_schema(items_schema);
-            }
+    if let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(&request) {
+        for key in route.parameters.keys().filter(|key| !fields.contains_key(*key)) {
+            log::warn!("Parameter {} is not supported by OpenRouter and was ignored", key);
         }
-        _ => {}
     }
+
+    Ok(request)
 }
+
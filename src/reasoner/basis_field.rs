@@ -3,7 +3,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::prelude::*;
-use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, CompletionMetadata};
+use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, ReasonerOperation, CompletionMetadata};
 use crate::basis_field::{BasisField, BasisFieldMetadata};
 use super::sampling::{pre_sample_context_group, sample_most_different};
 
@@ -60,13 +60,16 @@ pub async fn basis_field<R: Reasoner>(
 
     let (result, metadata) = reasoner.execute::<BasisFieldResponse>(
         &capability,
+        &ReasonerOperation::BasisField,
         &system_prompt,
         &user_prompt,
         schema
     ).await?;
 
     let reasoner_metadata = ReasonerMetadata {
-        tokens: metadata.input_tokens + metadata.output_tokens,
+        model: metadata.model.clone(),
+        input_tokens: metadata.input_tokens,
+        output_tokens: metadata.output_tokens,
         prompt_hash: metadata.prompt_hash.clone(),
     };
 
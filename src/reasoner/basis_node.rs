@@ -3,7 +3,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::prelude::*;
-use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, CompletionMetadata};
+use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, ReasonerOperation, CompletionMetadata};
 use crate::basis_node::{BasisNode, BasisNodeMetadata};
 use crate::basis_group::BasisGroup;
 use crate::data_node::DataNodeFields;
@@ -79,13 +79,16 @@ pub async fn basis_node<R: Reasoner>(
 
     let (result, metadata) = reasoner.execute::<BasisNodeResponse>(
         &capability,
+        &ReasonerOperation::BasisNode,
         &system_prompt,
         &user_prompt,
         schema
     ).await?;
 
     let reasoner_metadata = ReasonerMetadata {
-        tokens: metadata.input_tokens + metadata.output_tokens,
+        model: metadata.model.clone(),
+        input_tokens: metadata.input_tokens,
+        output_tokens: metadata.output_tokens,
         prompt_hash: metadata.prompt_hash.clone(),
     };
 
@@ -143,7 +146,7 @@ async fn get_user_prompt<R: Reasoner>(
 
     let group = pre_sample_context_group(group);
 
-    let basis_fields = {
+    let mut basis_fields = {
         let lock = read_lock!(normalization_context);
         lock.basis_fields
             .as_ref()
@@ -154,6 +157,7 @@ async fn get_user_prompt<R: Reasoner>(
             .cloned()
             .collect::<Vec<_>>()
     };
+    basis_fields.sort_by(|a, b| a.name.cmp(&b.name));
 
     let basis_fields_context_string = basis_fields.iter().fold(String::new(), |acc, item| {
         if group.iter().any(|context| {
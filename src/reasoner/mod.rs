@@ -10,6 +10,8 @@ use crate::basis_field::BasisField;
 use crate::basis_group::BasisGroup;
 use crate::basis_node::BasisNode;
 use crate::basis_network::{BasisNetwork, NodeRelationship};
+use crate::config::{CONFIG, ModelRoute};
+use crate::transformation::{FieldTranslationTransformation, NetworkTranslationTransformation};
 
 mod backend;
 mod classify;
@@ -18,11 +20,21 @@ mod basis_group;
 mod basis_node;
 mod sampling;
 mod node_relationship;
+mod recording;
+mod translation;
+mod schema_to_instance;
 
+pub use backend::ReasonerBackend;
 #[cfg(feature = "openrouter-reasoner")]
 pub use backend::openrouter;
+#[cfg(feature = "openai-compatible-reasoner")]
+pub use backend::openai_compatible;
+#[cfg(feature = "fixture-reasoner")]
+pub use backend::fixture;
+pub use recording::{RecordingReasoner, RecordingMode};
 
 pub struct CompletionMetadata {
+    pub model: String,
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub prompt_hash: Hash,
@@ -38,8 +50,23 @@ pub enum Capability {
     Capable,
 }
 
+// The analysis a completion is requested for, so that each can be routed to its own model
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReasonerOperation {
+    Classify,
+    BasisField,
+    BasisGroup,
+    BasisNode,
+    NodeRelationship,
+    NodeTranslation,
+    NetworkTranslation,
+    SchemaToInstance,
+}
+
 pub struct ReasonerMetadata {
-    pub tokens: u32,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
     pub prompt_hash: Hash,
 }
 
@@ -50,6 +77,7 @@ pub trait Reasoner: Send + Sync + Sized + 'static {
     async fn complete(
         &self,
         capability: &Capability,
+        operation: &ReasonerOperation,
         system_prompt: &str,
         user_prompt: &str,
         schema: serde_json::Value
@@ -58,6 +86,7 @@ pub trait Reasoner: Send + Sync + Sized + 'static {
     async fn execute<T: for<'de> serde::Deserialize<'de>>(
         &self,
         capability: &Capability,
+        operation: &ReasonerOperation,
         system_prompt: &str,
         user_prompt: &str,
         schema: serde_json::Value
@@ -65,10 +94,12 @@ pub trait Reasoner: Send + Sync + Sized + 'static {
         let mut backoff = std::time::Duration::from_millis(100);
         let max_backoff = std::time::Duration::from_secs(30);
         let max_retries = 5;
+        let mut last_error = Errors::ReasonerNotConfigured;
 
         for attempt in 0..=max_retries {
             match self.complete(
                 capability,
+                operation,
                 system_prompt,
                 user_prompt,
                 schema.clone()
@@ -82,16 +113,21 @@ pub trait Reasoner: Send + Sync + Sized + 'static {
                     return Ok((parsed, metadata));
                 }
                 Err(e) if is_retryable(&e) => {
-                    log::warn!("Retryable error on attempt {}, backing off: {:?}", attempt + 1, e);
-                    let jitter = std::time::Duration::from_millis(rand::random::<u64>() % 100);
-                    tokio::time::sleep(backoff + jitter).await;
-                    backoff = (backoff * 2).min(max_backoff);
+                    if attempt < max_retries {
+                        log::warn!("Retryable error on attempt {}, backing off: {:?}", attempt + 1, e);
+                        let jitter = std::time::Duration::from_millis(rand::random::<u64>() % 100);
+                        tokio::time::sleep(backoff + jitter).await;
+                        backoff = (backoff * 2).min(max_backoff);
+                    }
+                    last_error = e;
                 }
                 Err(e) => return Err(e),
             }
         }
-        
-        unreachable!()
+
+        log::error!("Giving up after {} attempts: {:?}", max_retries + 1, last_error);
+
+        Err(last_error)
     }
 
     async fn embed(
@@ -166,6 +202,78 @@ pub trait Reasoner: Send + Sync + Sized + 'static {
             ).await?
         )
     }
+    async fn node_translation(
+        &self,
+        translation_context: Arc<RwLock<TranslationContext>>,
+        input_context: Arc<Context>,
+        target_context: Arc<Context>,
+    ) -> Result<(Vec<FieldTranslationTransformation>, ReasonerMetadata), Errors> {
+        Ok(
+            translation::node_translation(
+                self,
+                translation_context,
+                input_context,
+                target_context
+            ).await?
+        )
+    }
+
+    async fn network_translation(
+        &self,
+        translation_context: Arc<RwLock<TranslationContext>>,
+        input_context: Arc<Context>,
+        target_context: Arc<Context>,
+    ) -> Result<(Option<NetworkTranslationTransformation>, ReasonerMetadata), Errors> {
+        Ok(
+            translation::network_translation(
+                self,
+                translation_context,
+                input_context,
+                target_context
+            ).await?
+        )
+    }
+
+    async fn schema_to_instance(
+        &self,
+        schema: String,
+    ) -> Result<(String, ReasonerMetadata), Errors> {
+        Ok(schema_to_instance::schema_to_instance(self, schema).await?)
+    }
+}
+
+// The models configured for an operation, or otherwise for its capability, in the order they
+// are to be tried. None leaves the choice of model to the backend.
+pub fn get_model_routes(capability: &Capability, operation: &ReasonerOperation) -> Option<Vec<ModelRoute>> {
+    let config = read_lock!(CONFIG);
+    let models = &config.reasoner.models;
+
+    let operation_config = match operation {
+        ReasonerOperation::Classify => &models.classify,
+        ReasonerOperation::BasisField => &models.basis_field,
+        ReasonerOperation::BasisGroup => &models.basis_group,
+        ReasonerOperation::BasisNode => &models.basis_node,
+        ReasonerOperation::NodeRelationship => &models.node_relationship,
+        ReasonerOperation::NodeTranslation => &models.node_translation,
+        ReasonerOperation::NetworkTranslation => &models.network_translation,
+        ReasonerOperation::SchemaToInstance => &models.schema_to_instance,
+    };
+
+    let capability_config = match capability {
+        Capability::Fast => &models.fast,
+        Capability::Capable => &models.capable,
+    };
+
+    operation_config.as_ref().or(capability_config.as_ref()).map(|model_config| {
+        std::iter::once(model_config.route.clone())
+            .chain(model_config.fallbacks.iter().cloned())
+            .collect()
+    })
+}
+
+// Completions are identified by their prompts, e.g. to look up recorded responses
+pub fn get_prompt_hash(system_prompt: &str, user_prompt: &str) -> Hash {
+    Hash::from_str(&format!("{}{}", system_prompt, user_prompt))
 }
 
 fn is_retryable(error: &Errors) -> bool {
@@ -175,3 +283,4 @@ fn is_retryable(error: &Errors) -> bool {
         | Errors::RequestTimeout(_)
     )
 }
+
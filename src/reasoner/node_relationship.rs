@@ -3,7 +3,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::prelude::*;
-use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, CompletionMetadata};
+use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, ReasonerOperation, CompletionMetadata};
 use crate::basis_network::{NodeRelationship, NodeRelationshipType};
 use crate::basis_node::BasisNode;
 
@@ -70,13 +70,16 @@ pub async fn node_relationship<R: Reasoner>(
 
     let (result, metadata) = reasoner.execute::<NodeRelationshipResponse>(
         &capability,
+        &ReasonerOperation::NodeRelationship,
         &system_prompt,
         &user_prompt,
         schema
     ).await?;
 
     let reasoner_metadata = ReasonerMetadata {
-        tokens: metadata.input_tokens + metadata.output_tokens,
+        model: metadata.model.clone(),
+        input_tokens: metadata.input_tokens,
+        output_tokens: metadata.output_tokens,
         prompt_hash: metadata.prompt_hash.clone(),
     };
 
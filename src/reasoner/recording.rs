@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, CompletionMetadata, Capability, ReasonerOperation, EmbeddingMetadata, get_prompt_hash};
+use crate::prompt_registry::PromptRegistry;
+use crate::hash::Hash;
+
+const COMPLETIONS_DIRECTORY: &str = "completions";
+const EMBEDDINGS_DIRECTORY: &str = "embeddings";
+const REPLAY_MODEL: &str = "recording";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordingMode {
+    // Requests are only forwarded to the inner reasoner
+    Off,
+    // Requests are forwarded to the inner reasoner and every response is saved
+    Record,
+    // Responses are served from a previous recording, there is no inner reasoner
+    Replay,
+}
+
+impl FromStr for RecordingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(RecordingMode::Off),
+            "record" => Ok(RecordingMode::Record),
+            "replay" => Ok(RecordingMode::Replay),
+            other => Err(format!("Invalid recording mode: {}", other)),
+        }
+    }
+}
+
+// A completion as both recordings and fixtures store it, at `completions/<prompt hash>.json`.
+// The content may be written by hand as a JSON value for readability, it is handed out
+// serialized. The prompts are kept next to the response so that a recording can be read
+// like the debug log of the request it was made for.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompletionRecording {
+    pub content: serde_json::Value,
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+    // The model the response stands in for, which prices it in usage reports
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_prompt: Option<String>,
+}
+
+impl CompletionRecording {
+    pub fn into_completion(self, prompt_hash: Hash, default_model: &str) -> (String, CompletionMetadata) {
+        let content = match self.content {
+            serde_json::Value::String(content) => content,
+            other => other.to_string(),
+        };
+
+        let metadata = CompletionMetadata {
+            model: self.model.unwrap_or_else(|| default_model.to_string()),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            prompt_hash,
+        };
+
+        (content, metadata)
+    }
+}
+
+// A recorded embedding of a single input, stored at `embeddings/<input hash>.json`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmbeddingRecording {
+    pub embedding: Vec<f32>,
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+}
+
+// Completions are looked up by their prompts alone, see `get_prompt_hash`
+pub fn get_completion_path(location: &Path, prompt_hash: &Hash) -> PathBuf {
+    location.join(COMPLETIONS_DIRECTORY).join(format!("{}.json", prompt_hash))
+}
+
+pub fn get_embedding_path(location: &Path, input: &str) -> PathBuf {
+    location.join(EMBEDDINGS_DIRECTORY).join(format!("{}.json", Hash::from_str(input)))
+}
+
+pub fn read_recording<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Errors> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        Errors::RecordingNotFound(format!("{}: {}", path.display(), e))
+    })?;
+
+    serde_json::from_str(&contents).map_err(|e| {
+        Errors::JsonParseError(format!("Invalid recording {}: {}", path.display(), e))
+    })
+}
+
+// Wraps any reasoner to save its responses, keyed by prompt, so that a run can later be
+// replayed without the backend and without spending tokens
+pub struct RecordingReasoner<R: Reasoner> {
+    inner: Option<R>,
+    prompts: Option<PromptRegistry>,
+    mode: RecordingMode,
+    location: PathBuf,
+}
+
+impl<R: Reasoner> RecordingReasoner<R> {
+    pub fn new(inner: R, mode: RecordingMode, location: PathBuf) -> Self {
+        RecordingReasoner {
+            inner: Some(inner),
+            prompts: None,
+            mode,
+            location,
+        }
+    }
+
+    pub fn replay(prompts: PromptRegistry, location: PathBuf) -> Self {
+        RecordingReasoner {
+            inner: None,
+            prompts: Some(prompts),
+            mode: RecordingMode::Replay,
+            location,
+        }
+    }
+
+    fn inner(&self) -> Result<&R, Errors> {
+        self.inner.as_ref().ok_or(Errors::ReasonerNotConfigured)
+    }
+
+    fn write<T: Serialize>(path: &Path, recording: &T) -> Result<(), Errors> {
+        let contents = serde_json::to_string_pretty(recording)
+            .map_err(|e| Errors::JsonParseError(e.to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                log::error!("Could not create recording directory {}: {}", parent.display(), e);
+                Errors::FileOutputError
+            })?;
+        }
+
+        std::fs::write(path, contents).map_err(|e| {
+            log::error!("Could not write recording {}: {}", path.display(), e);
+            Errors::FileOutputError
+        })
+    }
+}
+
+#[async_trait]
+impl<R: Reasoner> Reasoner for RecordingReasoner<R> {
+    fn prompts(&self) -> &PromptRegistry {
+        match (&self.inner, &self.prompts) {
+            (Some(inner), _) => inner.prompts(),
+            (None, Some(prompts)) => prompts,
+            (None, None) => unreachable!("A recording reasoner has either an inner reasoner or prompts"),
+        }
+    }
+
+    async fn complete(
+        &self,
+        capability: &Capability,
+        operation: &ReasonerOperation,
+        system_prompt: &str,
+        user_prompt: &str,
+        schema: serde_json::Value,
+    ) -> Result<(String, CompletionMetadata), Errors> {
+        let prompt_hash = get_prompt_hash(system_prompt, user_prompt);
+        let path = get_completion_path(&self.location, &prompt_hash);
+
+        match self.mode {
+            RecordingMode::Off => {
+                self.inner()?.complete(capability, operation, system_prompt, user_prompt, schema).await
+            }
+            RecordingMode::Replay => {
+                log::debug!("Replaying completion from {}", path.display());
+
+                let recording: CompletionRecording = read_recording(&path).map_err(|e| {
+                    log::error!("No recorded {:?} {:?} completion for prompt hash {}", capability, operation, prompt_hash);
+                    log::error!("Expected at: {}", path.display());
+                    e
+                })?;
+
+                Ok(recording.into_completion(prompt_hash, REPLAY_MODEL))
+            }
+            RecordingMode::Record => {
+                let (content, metadata) = self.inner()?
+                    .complete(capability, operation, system_prompt, user_prompt, schema)
+                    .await?;
+
+                let recording = CompletionRecording {
+                    content: serde_json::Value::String(content.clone()),
+                    input_tokens: metadata.input_tokens,
+                    output_tokens: metadata.output_tokens,
+                    model: Some(metadata.model.clone()),
+                    operation: Some(format!("{:?}", operation)),
+                    system_prompt: Some(system_prompt.to_string()),
+                    user_prompt: Some(user_prompt.to_string()),
+                };
+
+                log::debug!("Recording completion to {}", path.display());
+                Self::write(&path, &recording)?;
+
+                Ok((content, metadata))
+            }
+        }
+    }
+
+    async fn embed(
+        &self,
+        inputs: Vec<String>
+    ) -> Result<(Vec<Vec<f32>>, EmbeddingMetadata), Errors> {
+        match self.mode {
+            RecordingMode::Off => self.inner()?.embed(inputs).await,
+            RecordingMode::Replay => {
+                let mut vectors: Vec<Vec<f32>> = Vec::new();
+                let mut input_tokens = 0;
+
+                for input in &inputs {
+                    let path = get_embedding_path(&self.location, input);
+                    let recording: EmbeddingRecording = read_recording(&path).map_err(|e| {
+                        log::error!("No recorded embedding for input: {}", input);
+                        e
+                    })?;
+                    input_tokens += recording.input_tokens;
+                    vectors.push(recording.embedding);
+                }
+
+                Ok((vectors, EmbeddingMetadata { input_tokens }))
+            }
+            RecordingMode::Record => {
+                let (vectors, metadata) = self.inner()?.embed(inputs.clone()).await?;
+
+                // Usage is only reported for the whole batch, so it is shared out evenly
+                let input_tokens = metadata.input_tokens / inputs.len().max(1) as u32;
+
+                for (input, embedding) in inputs.iter().zip(&vectors) {
+                    let recording = EmbeddingRecording {
+                        embedding: embedding.clone(),
+                        input_tokens,
+                        input: Some(input.clone()),
+                    };
+
+                    Self::write(&get_embedding_path(&self.location, input), &recording)?;
+                }
+
+                Ok((vectors, metadata))
+            }
+        }
+    }
+}
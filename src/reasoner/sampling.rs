@@ -1,17 +1,21 @@
 use std::sync::Arc;
 use rand::seq::SliceRandom;
-use rand::rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 use crate::context::Context;
 
 const CAP: usize = 50;
 
+// Sampled with a fixed seed so that the same group always makes the same prompt
 pub(super) fn pre_sample_context_group(mut group: Vec<Arc<Context>>) -> Vec<Arc<Context>> {
+    Context::sort_contexts(&mut group);
+
     if group.len() <= CAP {
         return group;
     }
 
-    group.shuffle(&mut rng());
+    group.shuffle(&mut StdRng::seed_from_u64(0));
     group.truncate(CAP);
     group
 }
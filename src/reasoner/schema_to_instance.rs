@@ -0,0 +1,61 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, ReasonerOperation};
+
+const SCHEMA_TO_INSTANCE_PROMPT: &str = r##"
+Your task is to convert a schema to an instance of that schema with several examples capturing everything the schema permits.
+        "##;
+
+#[derive(Deserialize, JsonSchema, Debug)]
+pub struct SchemaToInstanceResponse {
+    /// A document that is an instance of a schema
+    pub instance_document: String,
+}
+
+pub async fn schema_to_instance<R: Reasoner>(
+    reasoner: &R,
+    schema: String,
+) -> Result<(String, ReasonerMetadata), Errors> {
+    log::trace!("In schema_to_instance");
+
+    let user_prompt = format!(r##"
+[Schema]
+{}
+"##, schema);
+    let response_schema = serde_json::to_value(schemars::schema_for!(SchemaToInstanceResponse))
+        .expect("Failed to serialise SchemaToInstanceResponse schema");
+    let capability = Capability::Fast;
+
+    log::debug!("");
+    log::debug!("╔═══════════════════════════════════════════════════════════════╗");
+    log::debug!("║                                                               ║");
+    log::debug!("║                   SCHEMA TO INSTANCE                          ║");
+    log::debug!("║                                                               ║");
+    log::debug!("╚═══════════════════════════════════════════════════════════════╝");
+    log::debug!("");
+    log::debug!("  Capability : {:?}", capability);
+    log::debug!("");
+    log::debug!("┌─── USER PROMPT ───────────────────────────────────────────────┐");
+    log::debug!("{}", user_prompt);
+    log::debug!("└───────────────────────────────────────────────────────────────┘");
+    log::debug!("");
+
+    let (result, metadata) = reasoner.execute::<SchemaToInstanceResponse>(
+        &capability,
+        &ReasonerOperation::SchemaToInstance,
+        SCHEMA_TO_INSTANCE_PROMPT,
+        &user_prompt,
+        response_schema,
+    ).await?;
+
+    let reasoner_metadata = ReasonerMetadata {
+        model: metadata.model.clone(),
+        input_tokens: metadata.input_tokens,
+        output_tokens: metadata.output_tokens,
+        prompt_hash: metadata.prompt_hash.clone(),
+    };
+
+    Ok((result.instance_document, reasoner_metadata))
+}
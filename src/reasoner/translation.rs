@@ -0,0 +1,254 @@
+use std::sync::{Arc, RwLock};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::prelude::*;
+use crate::reasoner::{Reasoner, ReasonerMetadata, Capability, ReasonerOperation, CompletionMetadata};
+use crate::context::Context;
+use crate::transformation::{
+    FieldTranslationTransformation,
+    NetworkTranslationTransformation,
+    Runtime,
+};
+
+const NODE_TRANSLATION_PROMPT: &str = r##"
+You are an expert data integration engineer specializing in JSON schema mapping and ETL transformations.
+
+Your task is to compare candidate keys from exactly ONE Source JSON node against ONE Target JSON node, identify ALL semantically equivalent keys, and write data transformation code if the values require formatting changes.
+
+CONCEPTS:
+1. FIRST DOCUMENT (Source): The original data source.
+2. SECOND DOCUMENT (Target): The desired final data shape.
+3. SPATIAL CONTEXT: An incomplete fragment (a small zoomed-in neighborhood) of the original JSON document centered directly around the node being evaluated. This is provided to save tokens while giving you the actual values and immediate siblings to deduce semantic meaning. (Note: "_omitted": true implies data exists in the original document but was removed for brevity).
+4. POSITIONAL CONTEXT: The complete, absolute JSON path from the root of the original document down to the candidate keys being evaluated (e.g., "root -> entries -> author -> url"). This provides the full structural lineage of the keys.
+
+CRITICAL RULES FOR DETERMINING A MATCH:
+- Combine contexts: Use the Positional Context to understand the full structural lineage of the key, and use the Spatial Context to analyze its actual value and immediate siblings.
+- DO NOT blindly map keys just because they share the same name.
+- You MUST analyze the SPATIAL CONTEXT (the actual values) to prove that the two fields represent the exact same real-world entity.
+- Example: If the Source Positional Context ends in "url" (value: "github.com/user") but the Target Positional Context ends in "url" (value: "example.com/article"), these DO NOT MATCH because one is an author profile and the other is an article link.
+- Example: If the Source ends in "submitted_at" (value: "2025-10-28T13:22") and the Target ends in "timestamp" (value: 1746280800), these DO MATCH because the values prove they represent the same publication time.
+
+INSTRUCTIONS:
+1. Evaluate the Candidate Keys found at the end of the Positional Context paths from the First Document against those from the Second Document.
+2. Identify ALL valid semantic matches based on the rules above.
+3. For each match, output ONLY the final `source_key` and `target_key` (e.g., output "url", NOT the full positional path).
+4. For each match, examine the values in the Spatial Context. If the data formats differ, you MUST write a pure JavaScript function to convert the source value to the target format.
+5. The JavaScript code must be a valid, standalone function named `transform` that takes a single parameter `value` and returns the converted result.
+
+EXAMPLE JAVASCRIPT:
+```javascript
+function transform(value) {
+    return Math.floor(new Date(value).getTime() / 1000);
+}
+```
+
+If the values are already in the exact same format and type, `transform_code` should be null. If no valid semantic matches exist between the two objects, return an empty array `[]` for matches.
+        "##;
+
+const NETWORK_TRANSLATION_PROMPT: &str = r##"
+You are an expert data integration engineer specializing in JSON schema mapping and ETL transformations.
+
+Your task is to determine whether a Source network and a Target network represent the same semantic concept, based on their structural position and content.
+
+CONCEPTS:
+1. FIRST DOCUMENT (Source): The original data source.
+2. SECOND DOCUMENT (Target): The desired final data shape.
+3. SPATIAL CONTEXT: An incomplete fragment (a small zoomed-in neighborhood) of the original JSON document centered directly around the network being evaluated. This is provided to save tokens while giving you the actual values and immediate siblings to deduce semantic meaning. (Note: "_omitted": true implies data exists in the original document but was removed for brevity).
+4. POSITIONAL CONTEXT: The complete, absolute JSON path from the root of the original document down to the network being evaluated (e.g., "root -> entries -> author"). This provides the full structural lineage of the network.
+
+CRITICAL RULES:
+- Use the Positional Context to understand the full structural lineage of each network.
+- Use the Spatial Context to analyze the actual values and immediate siblings.
+- DO NOT match networks just because they share a similar name or relate to the same broad topic.
+- PLURALITY & CARDINALITY: The same concept may be held once in one document and as a collection in the other, e.g. a single author object and an array of authors. Such networks DO MATCH, and the translation wraps or unwraps the values to fit the target. Report both cardinalities accurately.
+- GRANULARITY & SCOPE: You MUST verify the hierarchical scope. A top-level container holding multiple attributes DOES NOT match a deeply nested sub-component, even if they share related data.
+- Example 1 (Match): A source network at "submissions -> item -> author" and a target network at "entries -> author" DO MATCH if both represent the author of a content item.
+- Example 2 (Mismatch - Scope): A source network at "submissions -> metadata" and a target network at "entries -> author" DO NOT MATCH even if both contain a name field.
+- Example 3 (Mismatch - Cardinality): A source network at "submissions -> item -> details" (a singular object) and a target network at "entries" (an array of items) DO NOT MATCH. The array maps to the parent array, not the nested item details.
+
+OUTPUT FORMAT:
+Return a strictly formatted JSON object with the following keys:
+- "source_cardinality": Evaluate the Source network and output either "array", "object", or "primitive" (string/number/boolean).
+- "target_cardinality": Evaluate the Target network and output either "array", "object", or "primitive".
+- "is_match": Set to true ONLY if the Source and Target networks represent the exact same semantic concept and structural role. Set to false otherwise.
+
+Example Output:
+{
+  "source_cardinality": "object",
+  "target_cardinality": "array",
+  "is_match": false
+}
+        "##;
+
+#[derive(Deserialize, JsonSchema, Debug)]
+pub struct NodeMatch {
+    /// The exact key name from the FIRST DOCUMENT node.
+    pub source_key: String,
+    /// The exact key name from the SECOND DOCUMENT node.
+    pub target_key: String,
+    /// A standalone JS function named `transform(value)`. Null if no conversion is needed.
+    pub transform_code: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema, Debug)]
+pub struct NodeTranslationResponse {
+    /// List of all semantically matched keys between the Source and Target nodes.
+    pub matches: Vec<NodeMatch>,
+}
+
+#[derive(Deserialize, JsonSchema, Debug)]
+pub struct NetworkTranslationResponse {
+    /// True if the Source and Target networks represent the same semantic concept and structural role.
+    pub is_match: bool,
+    /// The cardinality of FIRST DOCUMENT network.
+    pub source_cardinality: String,
+    /// The cardinality of SECOND DOCUMENT network.
+    pub target_cardinality: String,
+}
+
+pub async fn node_translation<R: Reasoner>(
+    reasoner: &R,
+    translation_context: Arc<RwLock<TranslationContext>>,
+    input_context: Arc<Context>,
+    target_context: Arc<Context>,
+) -> Result<(Vec<FieldTranslationTransformation>, ReasonerMetadata), Errors> {
+    log::trace!("In node_translation");
+
+    let user_prompt = get_user_prompt(translation_context, &input_context, &target_context)?;
+    let schema = serde_json::to_value(schemars::schema_for!(NodeTranslationResponse))
+        .expect("Failed to serialise NodeTranslationResponse schema");
+    let capability = Capability::Fast;
+
+    log::debug!("");
+    log::debug!("╔═══════════════════════════════════════════════════════════════╗");
+    log::debug!("║                                                               ║");
+    log::debug!("║                   TRANSLATE NODES                             ║");
+    log::debug!("║                                                               ║");
+    log::debug!("╚═══════════════════════════════════════════════════════════════╝");
+    log::debug!("");
+    log::debug!("  Capability : {:?}", capability);
+    log::debug!("");
+    log::debug!("┌─── USER PROMPT ───────────────────────────────────────────────┐");
+    log::debug!("{}", user_prompt);
+    log::debug!("└───────────────────────────────────────────────────────────────┘");
+    log::debug!("");
+
+    let (result, metadata) = reasoner.execute::<NodeTranslationResponse>(
+        &capability,
+        &ReasonerOperation::NodeTranslation,
+        NODE_TRANSLATION_PROMPT,
+        &user_prompt,
+        schema,
+    ).await?;
+
+    log::debug!("┌─── PARSED RESPONSE ───────────────────────────────────────────┐");
+    log::debug!("{:#?}", result);
+    log::debug!("└───────────────────────────────────────────────────────────────┘");
+
+    let transformations = result
+        .matches
+        .into_iter()
+        .map(|node_match| {
+            FieldTranslationTransformation {
+                id: ID::new(),
+                field: node_match.source_key,
+                image: node_match.target_key,
+                code: node_match.transform_code,
+                runtime: Runtime::QuickJS,
+            }
+        })
+        .collect();
+
+    Ok((transformations, to_reasoner_metadata(metadata)))
+}
+
+pub async fn network_translation<R: Reasoner>(
+    reasoner: &R,
+    translation_context: Arc<RwLock<TranslationContext>>,
+    input_context: Arc<Context>,
+    target_context: Arc<Context>,
+) -> Result<(Option<NetworkTranslationTransformation>, ReasonerMetadata), Errors> {
+    log::trace!("In network_translation");
+
+    let user_prompt = get_user_prompt(translation_context, &input_context, &target_context)?;
+    let schema = serde_json::to_value(schemars::schema_for!(NetworkTranslationResponse))
+        .expect("Failed to serialise NetworkTranslationResponse schema");
+    let capability = Capability::Fast;
+
+    log::debug!("");
+    log::debug!("╔═══════════════════════════════════════════════════════════════╗");
+    log::debug!("║                                                               ║");
+    log::debug!("║                   TRANSLATE NETWORKS                          ║");
+    log::debug!("║                                                               ║");
+    log::debug!("╚═══════════════════════════════════════════════════════════════╝");
+    log::debug!("");
+    log::debug!("  Capability : {:?}", capability);
+    log::debug!("");
+    log::debug!("┌─── USER PROMPT ───────────────────────────────────────────────┐");
+    log::debug!("{}", user_prompt);
+    log::debug!("└───────────────────────────────────────────────────────────────┘");
+    log::debug!("");
+
+    let (result, metadata) = reasoner.execute::<NetworkTranslationResponse>(
+        &capability,
+        &ReasonerOperation::NetworkTranslation,
+        NETWORK_TRANSLATION_PROMPT,
+        &user_prompt,
+        schema,
+    ).await?;
+
+    log::debug!("┌─── PARSED RESPONSE ───────────────────────────────────────────┐");
+    log::debug!("{:#?}", result);
+    log::debug!("└───────────────────────────────────────────────────────────────┘");
+
+    let transformation = if result.is_match {
+        Some(NetworkTranslationTransformation {
+            id: ID::new(),
+            image: target_context.network_name.clone(),
+            cardinality: result.target_cardinality,
+            source_cardinality: Some(result.source_cardinality),
+            code: None,
+            runtime: Runtime::QuickJS,
+        })
+    } else {
+        None
+    };
+
+    Ok((transformation, to_reasoner_metadata(metadata)))
+}
+
+fn get_user_prompt(
+    translation_context: Arc<RwLock<TranslationContext>>,
+    input_context: &Context,
+    target_context: &Context,
+) -> Result<String, Errors> {
+    let lock = read_lock!(translation_context);
+
+    let input_meta_context = lock.input_meta_context.as_ref().ok_or_else(|| {
+        Errors::DeficientTranslationContextError("Input meta context missing in translation context".to_string())
+    })?;
+    let target_meta_context = lock.target_meta_context.as_ref().ok_or_else(|| {
+        Errors::DeficientTranslationContextError("Target meta context missing in translation context".to_string())
+    })?;
+
+    let input_context_string = input_context.generate_context_string(input_meta_context, Vec::new())?;
+    let target_context_string = target_context.generate_context_string(target_meta_context, Vec::new())?;
+
+    Ok(format!(r##"
+            [FIRST DOCUMENT]
+            {}
+            
+            [SECOND DOCUMENT]
+            {}
+        "##, input_context_string, target_context_string))
+}
+
+fn to_reasoner_metadata(metadata: CompletionMetadata) -> ReasonerMetadata {
+    ReasonerMetadata {
+        model: metadata.model,
+        input_tokens: metadata.input_tokens,
+        output_tokens: metadata.output_tokens,
+        prompt_hash: metadata.prompt_hash,
+    }
+}
@@ -175,12 +175,13 @@ pub async fn report_basis_nodes<P: Provider>(
 
         let basis_lineage = basis_group.get_basis_lineage();
 
-        let basis_node = basis_nodes
+        // Offline runs leave groups without a basis node when there is none in the provider
+        let Some(basis_node) = basis_nodes
             .values()
-            .find(|node| node.lineage == basis_lineage)
-            .ok_or_else(|| {
-                Errors::DeficientNormalizationContextError(format!("Basis node not found for lineage {}", basis_lineage.to_string()))
-            })?;
+            .find(|node| node.lineage == basis_lineage) else {
+            println!("{}--- No basis node for lineage {} ---{}", GREEN, basis_lineage.to_string(), RESET);
+            continue;
+        };
 
         if covered_nodes.contains(&basis_node.id) {
             continue;
@@ -268,6 +269,7 @@ pub async fn report_basis_networks(
                     wrap_text: None,
                     exclude_nulls: None,
                     custom_delimiter: None,
+                    flatten: None,
                 };
                 match Document::from_normal_meta_context(&normal_meta_context, &format) {
                     Ok(document) => {
@@ -0,0 +1,390 @@
+use axum::{
+    extract::{DefaultBodyLimit, Json, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::{Event, KeepAlive, Sse}, IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::prelude::*;
+use crate::config::CONFIG;
+use crate::document::{Document, DocumentRole};
+use crate::drift_analysis::DriftPolicy;
+use crate::options::{parse_regeneration_stages, EmbeddedDataPolicy};
+use crate::entrypoint::{parse_document_format, parse_document_type};
+use crate::normalization;
+use crate::translation;
+use crate::translation_context::TranslationWarning;
+use crate::translation_coverage::CoverageReport;
+use crate::json_schema::SchemaViolation;
+
+// One provider and reasoner serve every request, so that basis artifacts inferred for one
+// document are reused for the next without reinitializing anything
+struct ServerState<P: Provider, R: Reasoner> {
+    provider: Arc<P>,
+    reasoner: Arc<R>,
+}
+
+impl<P: Provider, R: Reasoner> Clone for ServerState<P, R> {
+    fn clone(&self) -> Self {
+        ServerState {
+            provider: Arc::clone(&self.provider),
+            reasoner: Arc::clone(&self.reasoner),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentInput {
+    document: String,
+    document_type: String,
+    origin: Option<String>,
+    role: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OutputOptions {
+    output_format: Option<String>,
+    flatten: Option<String>,
+    #[serde(default)]
+    regenerate: Regenerate,
+    #[serde(default)]
+    offline: bool,
+    on_drift: Option<String>,
+    drift_threshold: Option<f64>,
+    embedded_data: Option<String>,
+    #[serde(default)]
+    coverage: bool,
+}
+
+// Either true for every stage, or the names of the stages to regenerate
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Regenerate {
+    All(bool),
+    Stages(Vec<String>),
+}
+
+impl Default for Regenerate {
+    fn default() -> Self {
+        Regenerate::All(false)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NormalizeRequest {
+    #[serde(flatten)]
+    source: DocumentInput,
+    #[serde(flatten)]
+    output: OutputOptions,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslateRequest {
+    #[serde(flatten)]
+    source: DocumentInput,
+    target: DocumentInput,
+    #[serde(flatten)]
+    output: OutputOptions,
+}
+
+enum Job {
+    Normalize(NormalizeRequest),
+    Translate(TranslateRequest),
+}
+
+impl Job {
+    fn reports_coverage(&self) -> bool {
+        match self {
+            Job::Normalize(_) => false,
+            Job::Translate(request) => request.output.coverage,
+        }
+    }
+}
+
+// The document carries its metadata, e.g. name, description and semantic content types
+#[derive(Serialize)]
+struct JobResponse {
+    document: Document,
+    usage: UsageReport,
+    // What translating into the shape of the target left out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<TranslationWarning>,
+    // How the translation maps the target, when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coverage: Option<CoverageReport>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+    usage: UsageReport,
+    // Where the output did not conform to a target schema
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<SchemaViolation>,
+}
+
+impl ErrorResponse {
+    fn new(error: &Errors, usage: UsageReport) -> Self {
+        ErrorResponse {
+            error: format!("{:?}", error),
+            usage,
+            violations: match error {
+                Errors::SchemaValidationError(violations) => violations.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+pub async fn serve<P: Provider, R: Reasoner>(
+    provider: Arc<P>,
+    reasoner: Arc<R>,
+    address: &str,
+) -> Result<(), Errors> {
+    let max_body_size = read_lock!(CONFIG).server.max_body_size;
+
+    let app = Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/normalize", post(normalize_handler::<P, R>))
+        .route("/translate", post(translate_handler::<P, R>))
+        .layer(DefaultBodyLimit::max(max_body_size))
+        .with_state(ServerState { provider, reasoner });
+
+    let listener = tokio::net::TcpListener::bind(address).await.map_err(|e| {
+        Errors::UnexpectedParameter(format!("Could not listen on {}: {}", address, e))
+    })?;
+
+    log::info!("Listening on {}", address);
+    eprintln!("Listening on {}", address);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .map_err(|e| Errors::UnexpectedError(format!("Server error: {}", e)))
+}
+
+async fn normalize_handler<P: Provider, R: Reasoner>(
+    State(state): State<ServerState<P, R>>,
+    headers: HeaderMap,
+    Json(request): Json<NormalizeRequest>,
+) -> Response {
+    respond(state, headers, Job::Normalize(request)).await
+}
+
+async fn translate_handler<P: Provider, R: Reasoner>(
+    State(state): State<ServerState<P, R>>,
+    headers: HeaderMap,
+    Json(request): Json<TranslateRequest>,
+) -> Response {
+    respond(state, headers, Job::Translate(request)).await
+}
+
+// Clients asking for an event stream get every progress event as it happens, followed by
+// a single result or error event. Everyone else gets the result once the job is done.
+async fn respond<P: Provider, R: Reasoner>(
+    state: ServerState<P, R>,
+    headers: HeaderMap,
+    job: Job,
+) -> Response {
+    let reports_coverage = job.reports_coverage();
+
+    if !accepts_event_stream(&headers) {
+        let execution_context = ExecutionContext::new();
+        let result = process(state, job, Arc::clone(&execution_context)).await;
+
+        return match result {
+            Ok(document) => Json(JobResponse {
+                document,
+                usage: execution_context.usage_report(),
+                warnings: execution_context.translation_warnings(),
+                coverage: get_coverage(&execution_context, reports_coverage),
+            }).into_response(),
+            Err(e) => {
+                let status = get_status_code(&e);
+                (status, Json(ErrorResponse::new(&e, execution_context.usage_report()))).into_response()
+            }
+        };
+    }
+
+    let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+    let (result_tx, result_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let execution_context = ExecutionContext::with_progress(progress_tx);
+        let result = process(state, job, Arc::clone(&execution_context)).await;
+        let usage = execution_context.usage_report();
+        let warnings = execution_context.translation_warnings();
+        let coverage = get_coverage(&execution_context, reports_coverage);
+
+        // The progress stream ends once the last sender is gone
+        drop(execution_context);
+
+        let _ = result_tx.send((result, usage, warnings, coverage));
+    });
+
+    let progress = stream::unfold(progress_rx, |mut progress_rx| async move {
+        let event = progress_rx.recv().await?;
+        Some((Event::default().event("progress").json_data(&event), progress_rx))
+    });
+
+    let outcome = stream::once(async move {
+        match result_rx.await {
+            Ok((Ok(document), usage, warnings, coverage)) => {
+                Event::default().event("result").json_data(JobResponse { document, usage, warnings, coverage })
+            }
+            Ok((Err(e), usage, _, _)) => {
+                Event::default().event("error").json_data(ErrorResponse::new(&e, usage))
+            }
+            Err(_) => {
+                Event::default().event("error").json_data(ErrorResponse {
+                    error: "Job ended without a result".to_string(),
+                    usage: UsageReport::default(),
+                    violations: Vec::new(),
+                })
+            }
+        }
+    });
+
+    Sse::new(progress.chain(outcome))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn process<P: Provider, R: Reasoner>(
+    state: ServerState<P, R>,
+    job: Job,
+    execution_context: Arc<ExecutionContext>,
+) -> Result<Document, Errors> {
+    match job {
+        Job::Normalize(request) => {
+            let metadata = get_metadata(&request.source, None)?;
+            let options = get_options(&request.output)?;
+            let document_format = get_document_format(&request.output)?;
+
+            normalization::normalize_text_to_document(
+                state.provider,
+                state.reasoner,
+                request.source.document,
+                &options,
+                &metadata,
+                &document_format,
+                execution_context,
+            ).await
+        }
+        Job::Translate(request) => {
+            let metadata = get_metadata(&request.source, None)?;
+            let target_metadata = get_metadata(&request.target, Some(&metadata.origin))?;
+            let options = get_options(&request.output)?;
+            let document_format = get_document_format(&request.output)?;
+
+            translation::translate_text_to_document(
+                state.provider,
+                state.reasoner,
+                (request.source.document, &metadata),
+                (request.target.document, &target_metadata),
+                &options,
+                &document_format,
+                execution_context,
+            ).await
+        }
+    }
+}
+
+// A target document without an origin takes the one of the source document
+fn get_metadata(input: &DocumentInput, fallback_origin: Option<&String>) -> Result<Metadata, Errors> {
+    let origin = input.origin
+        .as_ref()
+        .or(fallback_origin)
+        .cloned()
+        .ok_or(Errors::OriginNotProvidedError)?;
+
+    let role = input.role
+        .as_deref()
+        .map(DocumentRole::from_str)
+        .transpose()
+        .map_err(Errors::InvalidRole)?
+        .unwrap_or(DocumentRole::Instance);
+
+    Ok(Metadata {
+        document_type: Some(parse_document_type(&input.document_type)?),
+        origin,
+        role,
+    })
+}
+
+fn get_options(output: &OutputOptions) -> Result<Options, Errors> {
+    Ok(Options {
+        regenerate: match &output.regenerate {
+            Regenerate::All(true) => InferenceStage::ALL.into_iter().collect(),
+            Regenerate::All(false) => Default::default(),
+            Regenerate::Stages(stages) => parse_regeneration_stages(stages)
+                .map_err(Errors::UnexpectedParameter)?,
+        },
+        offline: output.offline,
+        drift_policy: output.on_drift
+            .as_deref()
+            .map(DriftPolicy::from_str)
+            .transpose()
+            .map_err(Errors::UnexpectedParameter)?,
+        drift_threshold: output.drift_threshold,
+        embedded_data: output.embedded_data
+            .as_deref()
+            .map(EmbeddedDataPolicy::from_str)
+            .transpose()
+            .map_err(Errors::UnexpectedParameter)?
+            .unwrap_or_default(),
+        ..Options::default()
+    })
+}
+
+fn get_document_format(output: &OutputOptions) -> Result<crate::document_format::DocumentFormat, Errors> {
+    parse_document_format(output.output_format.as_deref(), output.flatten.as_deref())
+}
+
+fn get_coverage(execution_context: &ExecutionContext, reports_coverage: bool) -> Option<CoverageReport> {
+    if reports_coverage {
+        execution_context.coverage_reports().pop()
+    } else {
+        None
+    }
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains("text/event-stream"))
+}
+
+fn get_status_code(error: &Errors) -> StatusCode {
+    match error {
+        Errors::DocumentNotProvided
+        | Errors::UnexpectedDocumentType
+        | Errors::DocumentTypeNotProvided
+        | Errors::OriginNotProvidedError
+        | Errors::InvalidRole(_)
+        | Errors::UnexpectedParameter(_)
+        | Errors::JsonParseError(_)
+        | Errors::XmlParseError
+        | Errors::YamlParseError(_)
+        | Errors::JavaScriptParseError(_) => StatusCode::BAD_REQUEST,
+        Errors::BudgetExceeded(_)
+        | Errors::InsufficientBackendQuota(_) => StatusCode::PAYMENT_REQUIRED,
+        Errors::StructuralDrift(_) => StatusCode::CONFLICT,
+        Errors::SchemaValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        Errors::RateLimitError(_) => StatusCode::TOO_MANY_REQUESTS,
+        Errors::TransientBackendError(_)
+        | Errors::BackendUnreachable(_) => StatusCode::BAD_GATEWAY,
+        Errors::RequestTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
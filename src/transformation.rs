@@ -1,79 +1,238 @@
-use quick_js::Context as QuickContext;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashSet, HashMap};
+use std::fmt;
 use std::sync::{Arc, RwLock};
 
 use crate::data_node::{DataNode, DataNodeFields};
 use crate::id::ID;
 use crate::prelude::*;
+use crate::transformation_runtime::{self, get_typed_value};
 use crate::basis_network::BasisNetwork;
 use crate::traversal::Traversal;
 use crate::graph_node::Graph;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+// What transformation code is run with. QuickJS is embedded, the others are interpreters
+// run as subprocesses.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum Runtime {
     AWK,
     NodeJS,
     Python,
+    #[default]
     QuickJS,
 }
 
+impl Runtime {
+    pub const ALL: [Runtime; 4] = [Runtime::QuickJS, Runtime::NodeJS, Runtime::Python, Runtime::AWK];
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Runtime::AWK => write!(f, "AWK"),
+            Runtime::NodeJS => write!(f, "Node.js"),
+            Runtime::Python => write!(f, "Python"),
+            Runtime::QuickJS => write!(f, "QuickJS"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cardinality {
+    Array,
+    Object,
+    Primitive,
+}
 
+impl Cardinality {
+    // Cardinalities come from the reasoner, which does not always stick to the three names
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "array" | "list" | "collection" => Some(Cardinality::Array),
+            "object" => Some(Cardinality::Object),
+            "primitive" | "string" | "number" | "boolean" => Some(Cardinality::Primitive),
+            _ => None,
+        }
+    }
+}
 
+impl fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cardinality::Array => write!(f, "array"),
+            Cardinality::Object => write!(f, "object"),
+            Cardinality::Primitive => write!(f, "primitive"),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NetworkTranslationTransformation {
     pub id: ID,
     pub image: String,
+    // Of the target network
     pub cardinality: String,
+    // Translations stored before the source cardinality was kept take the target one
+    #[serde(default)]
+    pub source_cardinality: Option<String>,
+    // Applied to each item of the source network, or to the network itself when it is not
+    // an array
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub runtime: Runtime,
 }
 
+impl NetworkTranslationTransformation {
+    pub fn transform(&self, value: Value) -> Result<Value, Errors> {
+        let Some(code) = &self.code else {
+            return Ok(value);
+        };
 
+        transformation_runtime::execute(self.runtime, code, value).map_err(|e| {
+            describe_failure(e, &format!("Code transforming network {}", self.image))
+        })
+    }
 
+    pub fn target_cardinality(&self) -> Cardinality {
+        Cardinality::parse(&self.cardinality).unwrap_or(Cardinality::Object)
+    }
 
+    pub fn source_cardinality(&self) -> Cardinality {
+        self.source_cardinality
+            .as_deref()
+            .and_then(Cardinality::parse)
+            .unwrap_or_else(|| self.target_cardinality())
+    }
 
-
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct FieldTranslationTransformation {
-    pub id: ID,
-    pub field: String,
-    pub image: String,
-    pub code: Option<String>,
+    // Wraps, unwraps or aggregates the items of the source network into the shape of the
+    // target network, along with a description of anything that did not fit
+    pub fn shape(&self, items: Vec<Value>) -> (Option<Value>, Option<String>) {
+        match self.target_cardinality() {
+            Cardinality::Array => (Some(Value::Array(items)), None),
+            Cardinality::Object => merge_items(items),
+            Cardinality::Primitive => join_items(items),
+        }
+    }
 }
 
-impl FieldTranslationTransformation {
-    pub fn transform(&self, data_node: Arc<DataNode>) -> Result<DataNode, Errors> {
-        let mut new_fields = DataNodeFields::new();
+fn merge_items(items: Vec<Value>) -> (Option<Value>, Option<String>) {
+    if items.len() < 2 {
+        return (items.into_iter().next(), None);
+    }
 
-        for value in data_node.fields.get(&self.field) {
-            new_fields.insert(self.image.clone(), value.to_string());
+    let item_count = items.len();
+    let mut merged = serde_json::Map::new();
+    let mut dropped = 0;
+
+    for item in items {
+        match item {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match merged.get(&key) {
+                        None => {
+                            merged.insert(key, value);
+                        }
+                        Some(existing) if *existing == value => {}
+                        Some(_) => dropped += 1,
+                    }
+                }
+            }
+            Value::Null => {}
+            _ => dropped += 1,
         }
+    }
 
-        let transformed = DataNode {
-            id: ID::new(),
-            hash: data_node.hash.clone(),
-            lineage: data_node.lineage.clone(),
-            description: data_node.description.clone(),
-            fields: new_fields,
-        };
+    let loss = (dropped > 0).then(|| {
+        format!("merged {} items into one object, dropping {} conflicting values", item_count, dropped)
+    });
 
-        Ok(transformed)
-    }
+    (Some(Value::Object(merged)), loss)
 }
 
+fn join_items(items: Vec<Value>) -> (Option<Value>, Option<String>) {
+    let mut values = Vec::new();
+    let mut dropped = 0;
 
+    for item in items {
+        let mut item_values: Vec<Value> = match item {
+            Value::Object(map) => map.into_iter().map(|(_, value)| value).collect(),
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        item_values.retain(|value| !value.is_null());
 
+        if item_values.len() > 1 {
+            dropped += item_values.len() - 1;
+        }
+        values.extend(item_values.into_iter().next());
+    }
 
+    let mut losses = Vec::new();
+    if dropped > 0 {
+        losses.push(format!("dropped {} values that did not fit into a primitive", dropped));
+    }
 
+    let value = match values.len() {
+        0 => None,
+        1 => values.pop(),
+        count => {
+            losses.push(format!("joined {} values into one string", count));
+            Some(Value::String(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))
+        }
+    };
 
+    (value, (!losses.is_empty()).then(|| losses.join(", ")))
+}
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FieldTranslationTransformation {
+    pub id: ID,
+    pub field: String,
+    pub image: String,
+    pub code: Option<String>,
+    #[serde(default)]
+    pub runtime: Runtime,
+}
 
+impl FieldTranslationTransformation {
+    // Values of the field for the image, as returned by the code if there is any
+    pub fn transform(&self, data_node: Arc<DataNode>) -> Result<Vec<Value>, Errors> {
+        let values = data_node.fields.get(&self.field);
 
+        let Some(code) = &self.code else {
+            return Ok(values.into_iter().map(|value| Value::String(value.to_string())).collect());
+        };
 
+        values
+            .into_iter()
+            .map(|value| {
+                transformation_runtime::execute(self.runtime, code, get_typed_value(value)).map_err(|e| {
+                    describe_failure(e, &format!("Code transforming {} into {} for value {:?}", self.field, self.image, value))
+                })
+            })
+            .collect()
+    }
+}
 
-
+fn describe_failure(error: Errors, subject: &str) -> Errors {
+    match error {
+        Errors::TransformationError(message) => {
+            Errors::TransformationError(format!("{} failed: {}", subject, message))
+        }
+        other => other,
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BasisFieldTransformation {
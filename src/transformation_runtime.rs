@@ -0,0 +1,695 @@
+use lazy_static::lazy_static;
+use quick_js::{Context as QuickContext, ExecutionError, JsValue};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+use crate::config::{CONFIG, TransformationConfig};
+use crate::transformation::Runtime;
+
+// Workers beyond this many are let go once they are done
+const MAX_IDLE_QUICKJS_WORKERS: usize = 8;
+
+// QuickJS scripts that ran over and are still running, as nothing can stop them
+static RUNAWAY_SCRIPTS: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    static ref QUICKJS_WORKERS: Mutex<Vec<QuickJsWorker>> = Mutex::new(Vec::new());
+
+    static ref AVAILABILITY: HashMap<Runtime, Result<String, String>> = Runtime::ALL
+        .into_iter()
+        .map(|runtime| (runtime, probe(runtime)))
+        .collect();
+
+    // The permission model is behind an experimental flag before Node.js 23.5
+    static ref NODE_PERMISSION_FLAG: &'static str = {
+        let node_command = read_lock!(CONFIG).transformation.node_command.clone();
+
+        let is_stable = Command::new(node_command)
+            .args(["--permission", "-e", ""])
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_or(false, |status| status.success());
+
+        if is_stable { "--permission" } else { "--experimental-permission" }
+    };
+}
+
+// Whether each runtime can be used, with its version or the reason it cannot
+pub fn detect_runtimes() -> Vec<(Runtime, Result<String, String>)> {
+    Runtime::ALL
+        .into_iter()
+        .map(|runtime| (runtime, AVAILABILITY[&runtime].clone()))
+        .collect()
+}
+
+pub fn execute(runtime: Runtime, code: &str, value: Value) -> Result<Value, Errors> {
+    if let Err(reason) = &AVAILABILITY[&runtime] {
+        return Err(Errors::RuntimeNotAvailable(format!("{}: {}", runtime, reason)));
+    }
+
+    let result = match runtime {
+        Runtime::QuickJS => run_quickjs(code, value),
+        Runtime::NodeJS => run_framed(runtime, NODE_HARNESS, code, value),
+        Runtime::Python => run_framed(runtime, PYTHON_HARNESS, code, value),
+        Runtime::AWK => run_awk(code, value),
+    };
+
+    result.map_err(Errors::TransformationError)
+}
+
+// Field values are always text, but code doing arithmetic expects numbers and booleans as such
+pub fn get_typed_value(value: &str) -> Value {
+    let trimmed = value.trim();
+
+    match serde_json::from_str::<Value>(trimmed) {
+        Ok(typed @ (Value::Number(_) | Value::Bool(_))) if typed.to_string() == trimmed => typed,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+// Values cross into and out of the runtime as JSON, which keeps their types and turns dates
+// into ISO strings
+const QUICKJS_ENTRYPOINT: &str = r#"
+function __parversion_transform(input) {
+    const result = transform(JSON.parse(input));
+    return result === undefined ? null : JSON.stringify(result);
+}
+"#;
+
+struct QuickJsJob {
+    code: String,
+    value: Value,
+    memory_limit: usize,
+    reply: mpsc::Sender<Result<Value, String>>,
+}
+
+struct QuickJsWorker {
+    jobs: mpsc::Sender<QuickJsJob>,
+    abandoned: Arc<AtomicBool>,
+    // The code the worker ran last, whose context it keeps
+    code: String,
+}
+
+// The context has no access to the file system, the network or the console. Code runs on
+// worker threads that are kept between values, one per script running at the same time.
+// QuickJS cannot interrupt a running script, so a worker that runs over is abandoned and
+// counted as runaway until its script ends, if it ever does.
+fn run_quickjs(code: &str, value: Value) -> Result<Value, String> {
+    let (timeout_milliseconds, memory_limit, max_runaway_scripts) = {
+        let lock = read_lock!(CONFIG);
+        let config = &lock.transformation;
+        (config.timeout_milliseconds, config.memory_limit, config.max_runaway_scripts)
+    };
+
+    let runaway_scripts = RUNAWAY_SCRIPTS.load(Ordering::SeqCst);
+    if runaway_scripts >= max_runaway_scripts {
+        return Err(format!("not run, {} scripts are still running past their timeout", runaway_scripts));
+    }
+
+    // Preferably the worker that ran the same code last and still has its context
+    let idle_worker = {
+        let mut idle_workers = QUICKJS_WORKERS.lock().unwrap();
+        let index = idle_workers.iter().position(|worker| worker.code == code);
+
+        match index {
+            Some(index) => Some(idle_workers.swap_remove(index)),
+            None => idle_workers.pop(),
+        }
+    };
+
+    let mut current = match idle_worker {
+        Some(current) => current,
+        None => start_quickjs_worker()?,
+    };
+    current.code = code.to_string();
+
+    let (reply, receiver) = mpsc::channel();
+    current.jobs
+        .send(QuickJsJob {
+            code: code.to_string(),
+            value,
+            memory_limit,
+            reply,
+        })
+        .map_err(|_| "QuickJS stopped without a result".to_string())?;
+
+    match receiver.recv_timeout(Duration::from_millis(timeout_milliseconds)) {
+        Ok(result) => {
+            let mut idle_workers = QUICKJS_WORKERS.lock().unwrap();
+            if idle_workers.len() < MAX_IDLE_QUICKJS_WORKERS {
+                idle_workers.push(current);
+            }
+
+            result
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            // Counted before the worker can see that it is abandoned, which it only does once
+            // its jobs are dropped
+            RUNAWAY_SCRIPTS.fetch_add(1, Ordering::SeqCst);
+            current.abandoned.store(true, Ordering::SeqCst);
+            drop(current);
+
+            Err(format!("did not finish within {} ms", timeout_milliseconds))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err("QuickJS stopped without a result".to_string()),
+    }
+}
+
+fn start_quickjs_worker() -> Result<QuickJsWorker, String> {
+    let (jobs, receiver) = mpsc::channel::<QuickJsJob>();
+    let abandoned = Arc::new(AtomicBool::new(false));
+    let is_abandoned = Arc::clone(&abandoned);
+
+    thread::Builder::new()
+        .name("quickjs".to_string())
+        .spawn(move || {
+            // The same code is run for every value of a field, so its context is kept
+            let mut cached: Option<(String, usize, QuickContext)> = None;
+
+            for job in receiver {
+                let result = evaluate_quickjs(&mut cached, &job.code, &job.value, job.memory_limit);
+                let _ = job.reply.send(result);
+            }
+
+            if is_abandoned.load(Ordering::SeqCst) {
+                RUNAWAY_SCRIPTS.fetch_sub(1, Ordering::SeqCst);
+            }
+        })
+        .map_err(|e| format!("could not start QuickJS: {}", e))?;
+
+    Ok(QuickJsWorker {
+        jobs,
+        abandoned,
+        code: String::new(),
+    })
+}
+
+fn evaluate_quickjs(
+    cached: &mut Option<(String, usize, QuickContext)>,
+    code: &str,
+    value: &Value,
+    memory_limit: usize,
+) -> Result<Value, String> {
+    let is_cached = matches!(
+        cached,
+        Some((cached_code, cached_memory_limit, _)) if cached_code == code && *cached_memory_limit == memory_limit
+    );
+
+    let context = match cached {
+        Some((_, _, context)) if is_cached => context,
+        _ => {
+            *cached = None;
+            &cached.insert((code.to_string(), memory_limit, create_quickjs_context(code, memory_limit)?)).2
+        }
+    };
+
+    let result = call_quickjs(context, value);
+
+    // A context that threw or ran out of memory may be left in any state
+    if result.is_err() {
+        *cached = None;
+    }
+
+    result
+}
+
+fn create_quickjs_context(code: &str, memory_limit: usize) -> Result<QuickContext, String> {
+    let context = QuickContext::builder()
+        .memory_limit(memory_limit)
+        .build()
+        .map_err(|e| format!("could not create QuickJS context: {}", e))?;
+
+    context.eval(code).map_err(describe_execution_error)?;
+    context.eval(QUICKJS_ENTRYPOINT).map_err(describe_execution_error)?;
+
+    Ok(context)
+}
+
+fn call_quickjs(context: &QuickContext, value: &Value) -> Result<Value, String> {
+    let input = serde_json::to_string(value).map_err(|e| e.to_string())?;
+
+    match context.call_function("__parversion_transform", vec![input]).map_err(describe_execution_error)? {
+        JsValue::String(output) => serde_json::from_str(&output).map_err(|e| e.to_string()),
+        _ => Ok(Value::Null),
+    }
+}
+
+fn describe_execution_error(error: ExecutionError) -> String {
+    match error {
+        ExecutionError::Exception(JsValue::String(message)) => format!("threw {}", message),
+        // This is how QuickJS reports running into the memory limit
+        ExecutionError::Exception(JsValue::Null) | ExecutionError::OutOfMemory => {
+            "exceeded the memory limit".to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+// Harnesses read a single request with the code and the value from stdin and write a single
+// response with either the value or the error to stdout. Anything the code itself prints goes
+// to stderr so that it cannot break the framing.
+const PYTHON_HARNESS: &str = r#"
+import json, sys
+request = json.loads(sys.stdin.read())
+stdout, sys.stdout = sys.stdout, sys.stderr
+try:
+    namespace = {}
+    exec(request["code"], namespace)
+    response = {"value": namespace["transform"](request["value"])}
+except Exception as e:
+    response = {"error": "threw %s: %s" % (type(e).__name__, e)}
+stdout.write(json.dumps(response, default=str) + "\n")
+"#;
+
+const NODE_HARNESS: &str = r#"
+let input = "";
+process.stdin.on("data", (chunk) => { input += chunk; });
+process.stdin.on("end", () => {
+    const request = JSON.parse(input);
+    console.log = console.error;
+    let response;
+    try {
+        const transform = new Function(request.code + "\nreturn transform;")();
+        const result = transform(request.value);
+        response = { value: result === undefined ? null : result };
+    } catch (e) {
+        response = { error: "threw " + String(e) };
+    }
+    process.stdout.write(JSON.stringify(response) + "\n");
+});
+"#;
+
+fn run_framed(runtime: Runtime, harness: &str, code: &str, value: Value) -> Result<Value, String> {
+    let request = json!({ "code": code, "value": value }).to_string();
+    let output = run_subprocess(runtime, &[harness], request)?;
+
+    let response: Value = serde_json::from_str(output.trim())
+        .map_err(|e| format!("returned a malformed response: {}", e))?;
+
+    match response {
+        Value::Object(mut map) => match map.remove("error") {
+            Some(Value::String(error)) => Err(error),
+            _ => Ok(map.remove("value").unwrap_or(Value::Null)),
+        },
+        _ => Err("returned a malformed response".to_string()),
+    }
+}
+
+// AWK cannot read JSON, so the program is given the value as a line of text and its output
+// is typed the way field values are
+fn run_awk(code: &str, value: Value) -> Result<Value, String> {
+    let input = match value {
+        Value::String(value) => value,
+        value => value.to_string(),
+    };
+
+    let output = run_subprocess(Runtime::AWK, &[code], format!("{}\n", input))?;
+
+    Ok(get_typed_value(output.trim_end_matches('\n')))
+}
+
+// Interpreters run with nothing but the allowed environment variables, from the temporary
+// directory, and are killed once they run over. On Unix they are also kept from writing data
+// to files, though not from creating or truncating them, and from using more memory than
+// allowed, and on Linux from reaching the network. Node.js code gets no file access at all.
+fn run_subprocess(runtime: Runtime, arguments: &[&str], input: String) -> Result<String, String> {
+    let (mut command, timeout_milliseconds) = {
+        let lock = read_lock!(CONFIG);
+        let config = &lock.transformation;
+
+        let (program, program_arguments) = get_interpreter(runtime, config);
+        let mut command = Command::new(program);
+        command
+            .args(program_arguments)
+            .args(arguments)
+            .env_clear()
+            .envs(config.environment_allowlist.iter().filter_map(|name| {
+                env::var_os(name).map(|value| (name.clone(), value))
+            }));
+
+        #[cfg(unix)]
+        restrict(&mut command, runtime, config);
+
+        (command, config.timeout_milliseconds)
+    };
+
+    let mut child = command
+        .current_dir(env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start {}: {}", runtime, e))?;
+
+    let mut stdin = child.stdin.take().ok_or("stdin not captured")?;
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_milliseconds);
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("did not finish within {} ms", timeout_milliseconds));
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        return Err(format!("exited with {}: {}", status, stderr.trim()));
+    }
+
+    Ok(stdout)
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut buffer);
+        }
+        buffer
+    })
+}
+
+// Applied in the child between fork and exec, where only system calls are safe to make
+#[cfg(unix)]
+fn restrict(command: &mut Command, runtime: Runtime, config: &TransformationConfig) {
+    use std::os::unix::process::CommandExt;
+
+    // The timeout kills the process first, this is for whatever it may have started
+    let cpu_seconds = config.timeout_milliseconds.div_ceil(1000) + 1;
+    // V8 reserves far more address space than it uses, so Node.js is held to a heap size
+    let address_space = (runtime != Runtime::NodeJS).then_some(config.subprocess_memory_limit as u64);
+    #[cfg(target_os = "linux")]
+    let isolate_network = config.isolate_network;
+
+    unsafe {
+        command.pre_exec(move || {
+            check(libc::setrlimit(libc::RLIMIT_CPU, &to_rlimit(cpu_seconds)))?;
+            check(libc::setrlimit(libc::RLIMIT_FSIZE, &to_rlimit(0)))?;
+            check(libc::setrlimit(libc::RLIMIT_CORE, &to_rlimit(0)))?;
+
+            if let Some(address_space) = address_space {
+                check(libc::setrlimit(libc::RLIMIT_AS, &to_rlimit(address_space)))?;
+            }
+
+            // A new user namespace is what lets an unprivileged process have a network
+            // namespace, which starts out with only a loopback device that is down
+            #[cfg(target_os = "linux")]
+            if isolate_network {
+                check(libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET))?;
+            }
+
+            Ok(())
+        });
+    }
+}
+
+#[cfg(unix)]
+fn to_rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+#[cfg(unix)]
+fn check(result: libc::c_int) -> std::io::Result<()> {
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+fn get_interpreter(runtime: Runtime, config: &TransformationConfig) -> (String, Vec<String>) {
+    match runtime {
+        // Isolated mode ignores PYTHON* variables and the user site directory, and no
+        // bytecode is written since the process may not write files
+        Runtime::Python => (config.python_command.clone(), vec!["-I".to_string(), "-B".to_string(), "-c".to_string()]),
+        // Without any --allow-* flag the permission model denies file system access, child
+        // processes and workers
+        Runtime::NodeJS => (config.node_command.clone(), vec![
+            NODE_PERMISSION_FLAG.to_string(),
+            format!("--max-old-space-size={}", config.subprocess_memory_limit / (1024 * 1024)),
+            "-e".to_string(),
+        ]),
+        Runtime::AWK => (config.awk_command.clone(), vec![]),
+        Runtime::QuickJS => (String::new(), vec![]),
+    }
+}
+
+fn probe(runtime: Runtime) -> Result<String, String> {
+    let (command, output) = match runtime {
+        Runtime::QuickJS => return Ok("embedded".to_string()),
+        Runtime::Python => (
+            read_lock!(CONFIG).transformation.python_command.clone(),
+            run_subprocess(runtime, &["import sys; print(sys.version.split()[0])"], String::new())?,
+        ),
+        Runtime::NodeJS => (
+            read_lock!(CONFIG).transformation.node_command.clone(),
+            run_subprocess(runtime, &["console.log(process.version)"], String::new())?,
+        ),
+        Runtime::AWK => (
+            read_lock!(CONFIG).transformation.awk_command.clone(),
+            run_subprocess(runtime, &["BEGIN { exit 0 }"], String::new())?,
+        ),
+    };
+
+    Ok(format!("{} {}", command, output.trim()).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Scripts that run over are never stopped, so the ones that count them are not run
+    // alongside each other
+    lazy_static! {
+        static ref RUNAWAY_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    fn run(code: &str, value: Value) -> Result<Value, String> {
+        let _lock = RUNAWAY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        match execute(Runtime::QuickJS, code, value) {
+            Err(Errors::TransformationError(message)) => Err(message),
+            Err(e) => panic!("unexpected error: {:?}", e),
+            Ok(value) => Ok(value),
+        }
+    }
+
+    #[test]
+    fn keeps_numbers_and_booleans_typed() {
+        let code = "function transform(value) { return typeof value === 'number' ? value * 2 : !value; }";
+
+        assert_eq!(run(code, get_typed_value("21")), Ok(json!(42)));
+        assert_eq!(run(code, get_typed_value("true")), Ok(json!(false)));
+        assert_eq!(run(code, get_typed_value(" 1.25 ")), Ok(json!(2.5)));
+    }
+
+    #[test]
+    fn keeps_text_that_only_looks_like_a_number() {
+        assert_eq!(get_typed_value("007"), json!("007"));
+        assert_eq!(get_typed_value("1e3"), json!("1e3"));
+
+        let code = "function transform(value) { return typeof value; }";
+        assert_eq!(run(code, get_typed_value("007")), Ok(json!("string")));
+    }
+
+    #[test]
+    fn reports_what_a_script_threw() {
+        let error = run("function transform(value) { throw new Error('no date in ' + value); }", json!("soon")).unwrap_err();
+
+        assert!(error.contains("threw"), "{}", error);
+        assert!(error.contains("no date in soon"), "{}", error);
+    }
+
+    #[test]
+    fn stops_waiting_for_a_script_that_never_ends() {
+        let error = run("function transform(value) { while (true) {} }", json!(1)).unwrap_err();
+
+        assert!(error.starts_with("did not finish within"), "{}", error);
+    }
+
+    #[test]
+    fn reports_running_into_the_memory_limit() {
+        let code = "function transform(value) { return new Array(16 * 1024 * 1024).fill(value).length; }";
+
+        assert_eq!(run(code, json!(1)), Err("exceeded the memory limit".to_string()));
+    }
+
+    #[test]
+    fn runs_scripts_alongside_a_slow_one() {
+        let _lock = RUNAWAY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let slow = thread::spawn(|| {
+            let code = "function transform(value) { const start = Date.now(); while (Date.now() - start < 500) {} return value; }";
+            execute(Runtime::QuickJS, code, json!("slow"))
+        });
+        thread::sleep(Duration::from_millis(100));
+
+        let start = Instant::now();
+        let quick = execute(Runtime::QuickJS, "function transform(value) { return value; }", json!("quick"));
+
+        assert_eq!(quick.ok(), Some(json!("quick")));
+        assert!(start.elapsed() < Duration::from_millis(300), "waited {:?}", start.elapsed());
+        assert_eq!(slow.join().unwrap().ok(), Some(json!("slow")));
+    }
+
+    #[test]
+    fn starts_no_script_once_too_many_run_over() {
+        let _lock = RUNAWAY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let max_runaway_scripts = read_lock!(CONFIG).transformation.max_runaway_scripts;
+
+        // Stands in for scripts that ran over, which would otherwise never stop
+        let stand_ins = max_runaway_scripts.saturating_sub(RUNAWAY_SCRIPTS.load(Ordering::SeqCst));
+        RUNAWAY_SCRIPTS.fetch_add(stand_ins, Ordering::SeqCst);
+        let result = execute(Runtime::QuickJS, "function transform(value) { return value; }", json!(1));
+        RUNAWAY_SCRIPTS.fetch_sub(stand_ins, Ordering::SeqCst);
+
+        match result {
+            Err(Errors::TransformationError(message)) => assert!(message.starts_with("not run"), "{}", message),
+            other => panic!("expected the script not to run, got {:?}", other),
+        }
+    }
+
+    // Interpreters are not there on every machine, tests of the ones that are missing pass
+    fn is_available(runtime: Runtime) -> bool {
+        match &AVAILABILITY[&runtime] {
+            Ok(_) => true,
+            Err(reason) => {
+                eprintln!("Skipping, {} is not available: {}", runtime, reason);
+                false
+            }
+        }
+    }
+
+    fn run_in(runtime: Runtime, code: &str, value: Value) -> Result<Value, String> {
+        match execute(runtime, code, value) {
+            Err(Errors::TransformationError(message)) => Err(message),
+            Err(e) => panic!("unexpected error: {:?}", e),
+            Ok(value) => Ok(value),
+        }
+    }
+
+    #[test]
+    fn leaves_out_variables_not_allowed() {
+        env::set_var("PARVERSION_TEST_SECRET", "secret");
+
+        if is_available(Runtime::Python) {
+            let code = "import os\ndef transform(value):\n    return [os.environ.get(value), 'PATH' in os.environ]";
+            assert_eq!(run_in(Runtime::Python, code, json!("PARVERSION_TEST_SECRET")), Ok(json!([null, true])));
+        }
+
+        if is_available(Runtime::NodeJS) {
+            let code = "function transform(value) { return [process.env[value] ?? null, 'PATH' in process.env]; }";
+            assert_eq!(run_in(Runtime::NodeJS, code, json!("PARVERSION_TEST_SECRET")), Ok(json!([null, true])));
+        }
+    }
+
+    #[test]
+    fn kills_an_interpreter_that_runs_over() {
+        if !is_available(Runtime::Python) {
+            return;
+        }
+
+        let start = Instant::now();
+        let code = "import time\ndef transform(value):\n    while True:\n        time.sleep(0.01)";
+        let error = run_in(Runtime::Python, code, json!(1)).unwrap_err();
+
+        assert!(error.starts_with("did not finish within"), "{}", error);
+        assert!(start.elapsed() < Duration::from_secs(5), "took {:?}", start.elapsed());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_no_data_to_files() {
+        let path = env::temp_dir().join(format!("parversion-write-test-{}", std::process::id()));
+
+        if is_available(Runtime::Python) {
+            let code = "def transform(path):\n    with open(path, 'w') as f:\n        f.write('written')\n    return path";
+            let result = run_in(Runtime::Python, code, json!(path.to_string_lossy()));
+
+            assert!(result.as_ref().is_err_and(|error| error.contains("File too large")), "{:?}", result);
+            assert_eq!(std::fs::read_to_string(&path).unwrap_or_default(), "");
+            let _ = std::fs::remove_file(&path);
+        }
+
+        if is_available(Runtime::AWK) {
+            let code = format!("{{ print \"written\" > \"{}\"; print $0 }}", path.to_string_lossy());
+            let result = run_in(Runtime::AWK, &code, json!("kept"));
+
+            assert!(result.is_err(), "{:?}", result);
+            assert_eq!(std::fs::read_to_string(&path).unwrap_or_default(), "");
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn gives_node_no_file_system_or_child_processes() {
+        if !is_available(Runtime::NodeJS) {
+            return;
+        }
+
+        for code in [
+            "function transform(value) { return require('fs').readdirSync(value); }",
+            "function transform(value) { return require('child_process').execSync('ls ' + value).toString(); }",
+        ] {
+            let error = run_in(Runtime::NodeJS, code, json!("/")).unwrap_err();
+            assert!(error.contains("ERR_ACCESS_DENIED") || error.contains("restricted"), "{}", error);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cuts_interpreters_off_the_network() {
+        if !read_lock!(CONFIG).transformation.isolate_network || !is_available(Runtime::Python) {
+            return;
+        }
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let code = "import socket\ndef transform(port):\n    socket.create_connection(('127.0.0.1', port), 1).close()\n    return 'connected'";
+        let result = run_in(Runtime::Python, code, json!(port));
+
+        assert!(result.as_ref().is_err_and(|error| error.contains("OSError")), "{:?}", result);
+    }
+
+    #[test]
+    fn types_the_output_of_awk() {
+        if !is_available(Runtime::AWK) {
+            return;
+        }
+
+        assert_eq!(run_in(Runtime::AWK, "{ print $1 * 2 }", json!(21)), Ok(json!(42)));
+        assert_eq!(run_in(Runtime::AWK, "{ print ($1 > 1) ? \"true\" : \"false\" }", json!("3")), Ok(json!(true)));
+        assert_eq!(run_in(Runtime::AWK, "{ print toupper($0) }", json!("ada lovelace")), Ok(json!("ADA LOVELACE")));
+    }
+}
@@ -3,8 +3,10 @@ use serde_json::{json, Value, Map};
 
 use crate::document::{Document, DocumentType, DocumentRole};
 use crate::document_format::DocumentFormat;
+use crate::json_schema::JsonSchema;
 use crate::normalization_context::NormalizationContext;
 use crate::translation_context::TranslationContext;
+use crate::translation_coverage::CoverageReport;
 use crate::normalization::normalize;
 use crate::package::Package;
 use crate::prelude::*;
@@ -16,6 +18,7 @@ use crate::graph_node::Graph;
 use crate::translation_node::TranslationNode;
 use crate::translation_network::TranslationNetwork;
 use crate::data_node::DataNode;
+use crate::drift_analysis::index_translation;
 
 pub async fn translate<P: Provider, R: Reasoner>(
     provider: Arc<P>,
@@ -99,11 +102,19 @@ pub async fn translate_json<P: Provider, R: Reasoner>(
             wrap_text: None,
             exclude_nulls: None,
             custom_delimiter: None,
+            flatten: None,
         }
     )?;
 
     let normalized_meta_context = normalized_document.to_meta_context()?;
 
+    index_translation(
+        Arc::clone(&provider),
+        Arc::clone(&normalization_context),
+        &normalized_meta_context,
+        options,
+    ).await?;
+
     {
         let mut lock = write_lock!(translation_context);
         lock.update_meta_contexts(normalized_meta_context, translation_meta_context);
@@ -129,14 +140,21 @@ pub async fn translate_text_to_document<P: Provider, R: Reasoner>(
         source,
         target,
         options,
-        execution_context,
+        Arc::clone(&execution_context),
     ).await?;
 
+    // Recorded before the output is built, so that mappings can be reviewed when it fails
+    // validation against the target schema
+    let coverage_report = CoverageReport::from_translation_context(&read_lock!(translation_context))?;
+    execution_context.record_coverage_report(coverage_report);
+
     let translated_document = Document::from_translation(
         Arc::clone(&translation_context),
         document_format
     )?;
 
+    execution_context.record_translation_warnings(read_lock!(translation_context).warnings.clone());
+
     Ok(translated_document)
 }
 
@@ -152,19 +170,30 @@ pub async fn translate_text<P: Provider, R: Reasoner>(
 
     let source_document = Document::from_string(source.0, options, source.1)?;
 
+    let target_schema = match target.1.role {
+        DocumentRole::Schema => JsonSchema::parse(&target.0),
+        DocumentRole::Instance => None,
+    };
+
     let target_document = {
         match target.1.role {
             DocumentRole::Instance => {
                 Document::from_string(target.0, options, target.1)?
             },
             DocumentRole::Schema => {
-                Document::from_schema_string(
+                let stage = execution_context.enter_stage("Schema analysis");
+
+                let document = Document::from_schema_string(
                     Arc::clone(&provider),
                     Arc::clone(&reasoner),
                     target.0,
                     options,
-                    target.1
-                ).await?
+                    target.1,
+                    &stage,
+                ).await?;
+
+                stage.finish();
+                document
             }
         }
     };
@@ -178,6 +207,10 @@ pub async fn translate_text<P: Provider, R: Reasoner>(
         execution_context.clone(),
     ).await?;
 
+    if let Some(target_schema) = target_schema {
+        write_lock!(translation_context).update_target_schema(target_schema);
+    }
+
     Ok(translation_context)
 }
 
@@ -252,6 +285,7 @@ async fn init_translation_context<P: Provider, R: Reasoner>(
         DocumentType::Xml => {
             unimplemented!()
         }
+        DocumentType::Csv | DocumentType::Tsv => return Err(Errors::UnexpectedDocumentType),
     }
 
     Ok(translation_context)
@@ -1,3 +1,4 @@
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -5,12 +6,26 @@ use crate::prelude::*;
 use crate::context::Context;
 use crate::translation_node::TranslationNode;
 use crate::translation_network::TranslationNetwork;
+use crate::json_schema::JsonSchema;
+
+// Something the source network held that its translation into the shape of the target
+// network could not keep
+#[derive(Clone, Debug, Serialize)]
+pub struct TranslationWarning {
+    pub network: String,
+    pub source_cardinality: String,
+    pub target_cardinality: String,
+    pub message: String,
+}
 
 pub struct TranslationContext {
     pub input_meta_context: Option<Arc<MetaContext>>,
     pub target_meta_context: Option<Arc<MetaContext>>,
     pub translation_nodes: Option<HashMap<ID, Arc<TranslationNode>>>,
     pub translation_networks: Option<HashMap<ID, Arc<TranslationNetwork>>>,
+    pub warnings: Vec<TranslationWarning>,
+    // The output is checked against it when the target was given as a JSON Schema
+    pub target_schema: Option<Arc<JsonSchema>>,
 }
 
 impl TranslationContext {
@@ -20,6 +35,8 @@ impl TranslationContext {
             target_meta_context: None,
             translation_nodes: None,
             translation_networks: None,
+            warnings: Vec::new(),
+            target_schema: None,
         }
     }
 
@@ -48,6 +65,14 @@ impl TranslationContext {
         self.translation_networks = Some(networks);
     }
 
+    pub fn update_target_schema(&mut self, target_schema: JsonSchema) {
+        self.target_schema = Some(Arc::new(target_schema));
+    }
+
+    pub fn update_warnings(&mut self, warnings: Vec<TranslationWarning>) {
+        self.warnings = warnings;
+    }
+
     fn unique_contexts_from(maybe_meta_context: &Option<Arc<MetaContext>>) -> Result<Vec<Arc<Context>>, Errors> {
         let meta_context = maybe_meta_context.as_ref().ok_or_else(|| {
             Errors::DeficientTranslationContextError("Meta context missing in translation context".to_string())
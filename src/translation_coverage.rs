@@ -0,0 +1,446 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::prelude::*;
+use crate::context::Context;
+use crate::translation_context::TranslationContext;
+
+// Longest piece of transformation code shown in a table cell
+const MAX_CODE_WIDTH: usize = 60;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CoverageFormat {
+    Table,
+    Json,
+}
+
+impl FromStr for CoverageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(CoverageFormat::Table),
+            "json" => Ok(CoverageFormat::Json),
+            other => Err(format!("Invalid coverage format: {}", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldMapping {
+    pub source_lineage: String,
+    pub source_path: String,
+    pub source_field: String,
+    // Without code the source value is copied as it is
+    pub runtime: Option<String>,
+    pub code: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TargetFieldCoverage {
+    pub target_lineage: String,
+    pub target_path: String,
+    pub target_field: String,
+    // Empty when nothing in the source maps onto the field
+    pub mappings: Vec<FieldMapping>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NetworkMapping {
+    pub source_lineage: String,
+    pub source_path: String,
+    pub source_cardinality: String,
+    pub target_cardinality: String,
+    pub runtime: Option<String>,
+    pub code: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TargetNetworkCoverage {
+    pub target_lineage: String,
+    pub target_path: String,
+    pub mappings: Vec<NetworkMapping>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceField {
+    pub source_lineage: String,
+    pub source_path: String,
+    pub source_field: String,
+}
+
+// How the translation nodes and networks inferred for a translation cover the target, and
+// what of the source they leave out, for reviewing mappings before trusting them
+#[derive(Clone, Debug, Serialize)]
+pub struct CoverageReport {
+    pub mapped_target_fields: usize,
+    pub total_target_fields: usize,
+    pub dropped_source_fields: usize,
+    pub total_source_fields: usize,
+    pub target_fields: Vec<TargetFieldCoverage>,
+    pub target_networks: Vec<TargetNetworkCoverage>,
+    pub unmapped_source_fields: Vec<SourceField>,
+}
+
+impl CoverageReport {
+    pub fn from_translation_context(translation_context: &TranslationContext) -> Result<Self, Errors> {
+        let input_meta_context = translation_context.input_meta_context.as_ref().ok_or_else(|| {
+            Errors::DeficientTranslationContextError("Input meta context missing in translation context".to_string())
+        })?;
+        let target_meta_context = translation_context.target_meta_context.as_ref().ok_or_else(|| {
+            Errors::DeficientTranslationContextError("Target meta context missing in translation context".to_string())
+        })?;
+
+        let translation_nodes: Vec<_> = translation_context.translation_nodes
+            .iter()
+            .flat_map(|nodes| nodes.values().cloned())
+            .collect();
+        let translation_networks: Vec<_> = translation_context.translation_networks
+            .iter()
+            .flat_map(|networks| networks.values().cloned())
+            .collect();
+
+        let source_contexts = unique_by_lineage(translation_context.must_get_unique_input_contexts()?);
+        let target_contexts = unique_by_lineage(translation_context.must_get_unique_target_contexts()?);
+
+        let get_source_path = |lineage: &Lineage| {
+            source_contexts
+                .iter()
+                .find(|context| context.lineage == *lineage)
+                .map(|context| context.get_network_path(input_meta_context))
+                .unwrap_or_default()
+        };
+
+        let mut target_fields = Vec::new();
+        for context in &target_contexts {
+            let target_path = context.get_network_path(target_meta_context);
+
+            for target_field in field_names(context) {
+                let mappings = translation_nodes
+                    .iter()
+                    .filter(|node| node.target_lineage == context.lineage)
+                    .flat_map(|node| {
+                        node.transformations
+                            .iter()
+                            .filter(|transformation| transformation.image == target_field)
+                            .map(|transformation| FieldMapping {
+                                source_lineage: node.source_lineage.to_string(),
+                                source_path: get_source_path(&node.source_lineage),
+                                source_field: transformation.field.clone(),
+                                runtime: transformation.code.as_ref().map(|_| transformation.runtime.to_string()),
+                                code: transformation.code.clone(),
+                            })
+                    })
+                    .collect();
+
+                target_fields.push(TargetFieldCoverage {
+                    target_lineage: context.lineage.to_string(),
+                    target_path: target_path.clone(),
+                    target_field,
+                    mappings,
+                });
+            }
+        }
+
+        let target_network_contexts = unique_by_lineage(
+            target_meta_context.contexts
+                .values()
+                .filter(|context| !context.network_name.is_empty())
+                .cloned()
+                .collect()
+        );
+
+        let target_networks = target_network_contexts
+            .iter()
+            .map(|context| TargetNetworkCoverage {
+                target_lineage: context.lineage.to_string(),
+                target_path: context.get_network_path(target_meta_context),
+                mappings: translation_networks
+                    .iter()
+                    .filter(|network| network.target_lineage == context.lineage)
+                    .map(|network| {
+                        let transformation = &network.transformation;
+
+                        NetworkMapping {
+                            source_lineage: network.source_lineage.to_string(),
+                            source_path: input_meta_context.contexts
+                                .values()
+                                .find(|context| context.lineage == network.source_lineage)
+                                .map(|context| context.get_network_path(input_meta_context))
+                                .unwrap_or_default(),
+                            source_cardinality: transformation.source_cardinality().to_string(),
+                            target_cardinality: transformation.target_cardinality().to_string(),
+                            runtime: transformation.code.as_ref().map(|_| transformation.runtime.to_string()),
+                            code: transformation.code.clone(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let mut total_source_fields = 0;
+        let mut unmapped_source_fields = Vec::new();
+        for context in &source_contexts {
+            let mapped: HashSet<&String> = translation_nodes
+                .iter()
+                .filter(|node| node.source_lineage == context.lineage)
+                .flat_map(|node| node.transformations.iter().map(|transformation| &transformation.field))
+                .collect();
+
+            for source_field in field_names(context) {
+                total_source_fields += 1;
+
+                if !mapped.contains(&source_field) {
+                    unmapped_source_fields.push(SourceField {
+                        source_lineage: context.lineage.to_string(),
+                        source_path: context.get_network_path(input_meta_context),
+                        source_field,
+                    });
+                }
+            }
+        }
+
+        let mut report = CoverageReport {
+            mapped_target_fields: target_fields.iter().filter(|field| !field.mappings.is_empty()).count(),
+            total_target_fields: target_fields.len(),
+            dropped_source_fields: unmapped_source_fields.len(),
+            total_source_fields,
+            target_fields,
+            target_networks,
+            unmapped_source_fields,
+        };
+
+        report.target_fields.sort_by(|a, b| (&a.target_path, &a.target_field).cmp(&(&b.target_path, &b.target_field)));
+        report.target_networks.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+        report.unmapped_source_fields.sort_by(|a, b| (&a.source_path, &a.source_field).cmp(&(&b.source_path, &b.source_field)));
+
+        Ok(report)
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut field_rows = Vec::new();
+        for field in &self.target_fields {
+            let target = join_path(&field.target_path, &field.target_field);
+
+            if field.mappings.is_empty() {
+                field_rows.push([target.clone(), "unmapped".to_string(), String::new()]);
+            }
+
+            for mapping in &field.mappings {
+                field_rows.push([
+                    target.clone(),
+                    join_path(&mapping.source_path, &mapping.source_field),
+                    describe_transform(&mapping.runtime, &mapping.code),
+                ]);
+            }
+        }
+
+        let mut network_rows = Vec::new();
+        for network in &self.target_networks {
+            if network.mappings.is_empty() {
+                network_rows.push([network.target_path.clone(), "unmapped".to_string(), String::new()]);
+            }
+
+            for mapping in &network.mappings {
+                network_rows.push([
+                    format!("{} ({})", network.target_path, mapping.target_cardinality),
+                    format!("{} ({})", mapping.source_path, mapping.source_cardinality),
+                    describe_transform(&mapping.runtime, &mapping.code),
+                ]);
+            }
+        }
+
+        let source_rows: Vec<[String; 3]> = self.unmapped_source_fields
+            .iter()
+            .map(|field| [join_path(&field.source_path, &field.source_field), "none".to_string(), String::new()])
+            .collect();
+
+        let mut output = format!(
+            "Target fields mapped: {} of {}\n",
+            self.mapped_target_fields,
+            self.total_target_fields,
+        );
+        output.push_str(&render_table(["TARGET FIELD", "SOURCE", "TRANSFORM"], &field_rows));
+        output.push('\n');
+        output.push_str(&render_table(["TARGET NETWORK", "SOURCE", "TRANSFORM"], &network_rows));
+        output.push_str(&format!(
+            "\nSource fields without a target: {} of {}\n",
+            self.dropped_source_fields,
+            self.total_source_fields,
+        ));
+        output.push_str(&render_table(["SOURCE FIELD", "TARGET", ""], &source_rows));
+
+        output
+    }
+}
+
+fn unique_by_lineage(contexts: Vec<Arc<Context>>) -> Vec<Arc<Context>> {
+    let mut seen: HashSet<Lineage> = HashSet::new();
+
+    contexts
+        .into_iter()
+        .filter(|context| seen.insert(context.lineage.clone()))
+        .collect()
+}
+
+fn field_names(context: &Context) -> BTreeSet<String> {
+    context.data_node.fields.keys().cloned().collect()
+}
+
+fn join_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{} -> {}", path, field)
+    }
+}
+
+fn describe_transform(runtime: &Option<String>, code: &Option<String>) -> String {
+    let (Some(runtime), Some(code)) = (runtime, code) else {
+        return "copied".to_string();
+    };
+
+    let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if code.chars().count() > MAX_CODE_WIDTH {
+        format!("{}: {}...", runtime, code.chars().take(MAX_CODE_WIDTH).collect::<String>())
+    } else {
+        format!("{}: {}", runtime, code)
+    }
+}
+
+fn render_table(headers: [&str; 3], rows: &[[String; 3]]) -> String {
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let render_row = |cells: [&str; 3]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut output = render_row(headers);
+    for row in rows {
+        output.push_str(&render_row([&row[0], &row[1], &row[2]]));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use crate::document::{Document, DocumentMetadata, DocumentType};
+    use crate::meta_context::MetaContext;
+    use crate::transformation::{FieldTranslationTransformation, Runtime};
+    use crate::translation_node::TranslationNode;
+
+    fn meta_context(data: Value) -> MetaContext {
+        Document {
+            document_type: DocumentType::Json,
+            data: data.to_string(),
+            metadata: DocumentMetadata {
+                origin: None,
+                date: None,
+                name: None,
+                description: None,
+                semantic_content_types: None,
+            },
+        }
+        .to_meta_context()
+        .unwrap()
+    }
+
+    // Lineage of the only context that has the field
+    fn lineage_of(meta_context: &MetaContext, field: &str) -> Lineage {
+        meta_context.contexts
+            .values()
+            .find(|context| context.data_node.fields.contains_key(field))
+            .map(|context| context.lineage.clone())
+            .unwrap()
+    }
+
+    fn translation_context() -> TranslationContext {
+        let input_meta_context = meta_context(json!({ "person": { "name": "Ada", "age": "36" } }));
+        let target_meta_context = meta_context(json!({ "author": { "full_name": "", "email": "" } }));
+
+        let translation_node = Arc::new(TranslationNode {
+            id: ID::new(),
+            source_lineage: lineage_of(&input_meta_context, "name"),
+            target_lineage: lineage_of(&target_meta_context, "full_name"),
+            transformations: vec![FieldTranslationTransformation {
+                id: ID::new(),
+                field: "name".to_string(),
+                image: "full_name".to_string(),
+                code: Some("value.toUpperCase()".to_string()),
+                runtime: Runtime::QuickJS,
+            }],
+        });
+
+        let mut translation_context = TranslationContext::new();
+        translation_context.update_meta_contexts(input_meta_context, target_meta_context);
+        translation_context.update_translation_nodes(HashMap::from([
+            (translation_node.id.clone(), translation_node),
+        ]));
+
+        translation_context
+    }
+
+    #[test]
+    fn counts_mapped_unmapped_and_dropped_fields() {
+        let report = CoverageReport::from_translation_context(&translation_context()).unwrap();
+
+        assert_eq!(report.mapped_target_fields, 1);
+        assert_eq!(report.total_target_fields, 2);
+        assert_eq!(report.dropped_source_fields, 1);
+        assert_eq!(report.total_source_fields, 2);
+
+        let target_fields: Vec<(&str, &str, usize)> = report.target_fields
+            .iter()
+            .map(|field| (field.target_path.as_str(), field.target_field.as_str(), field.mappings.len()))
+            .collect();
+        assert_eq!(target_fields, vec![("author", "email", 0), ("author", "full_name", 1)]);
+
+        let mapping = &report.target_fields[1].mappings[0];
+        assert_eq!((mapping.source_path.as_str(), mapping.source_field.as_str()), ("person", "name"));
+        assert_eq!(mapping.runtime.as_deref(), Some("QuickJS"));
+
+        let unmapped_source_fields: Vec<(&str, &str)> = report.unmapped_source_fields
+            .iter()
+            .map(|field| (field.source_path.as_str(), field.source_field.as_str()))
+            .collect();
+        assert_eq!(unmapped_source_fields, vec![("person", "age")]);
+    }
+
+    #[test]
+    fn renders_a_row_per_field() {
+        let report = CoverageReport::from_translation_context(&translation_context()).unwrap();
+
+        assert_eq!(report.to_table(), concat!(
+            "Target fields mapped: 1 of 2\n",
+            "TARGET FIELD         SOURCE          TRANSFORM\n",
+            "author -> email      unmapped\n",
+            "author -> full_name  person -> name  QuickJS: value.toUpperCase()\n",
+            "\n",
+            "TARGET NETWORK  SOURCE    TRANSFORM\n",
+            "author          unmapped\n",
+            "\n",
+            "Source fields without a target: 1 of 2\n",
+            "SOURCE FIELD   TARGET\n",
+            "person -> age  none\n",
+        ));
+    }
+}
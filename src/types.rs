@@ -1,9 +1,13 @@
 use tokio::task::JoinError;
 
+use crate::json_schema::SchemaViolation;
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum DocumentVersion {
     InputDocument,
     OrganizedDocument,
+    // Structured data embedded in the input document, by order of appearance
+    ExtractedDocument(usize),
 }
 
 #[derive(Clone, Debug)]
@@ -17,6 +21,7 @@ pub enum Errors {
     DocumentTypeNotProvided,
     UnexpectedError(String),
     XmlParseError,
+    JavaScriptParseError(String),
     PathConversionError,
     FetchUrlError(String),
     FieldTransformationFieldNotFound,
@@ -36,14 +41,23 @@ pub enum Errors {
     TooManyTranslationDocuments,
     InvalidRole(String),
     ReasonerNotConfigured,
+    FixtureNotFound(String),
+    RecordingNotFound(String),
     PromptRegistryError(String),
     UnavailableSystemPrompt(String),
     InsufficientBackendQuota(String),
     RateLimitError(String),
     TransientBackendError(String),
+    BackendUnreachable(String),
     RequestTimeout(String),
     EmbeddingError(String),
     TaskJoinError(String),
+    BudgetExceeded(String),
+    InferenceNotAvailable(String),
+    StructuralDrift(String),
+    TransformationError(String),
+    RuntimeNotAvailable(String),
+    SchemaValidationError(Vec<SchemaViolation>),
 }
 
 impl From<JoinError> for Errors {